@@ -0,0 +1,5 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=proto/policy.proto");
+    tonic_build::compile_protos("proto/policy.proto")?;
+    Ok(())
+}
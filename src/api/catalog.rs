@@ -0,0 +1,307 @@
+//! Model catalog with prices, limits and token estimation.
+
+use super::{EvaluationContext, LlmContext};
+use crate::{Error, Result};
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The catalog shipped with the engine.
+const BUILTIN_CATALOG: &str = include_str!("models.yaml");
+
+/// Attribute namespace describing the requested model.
+pub(crate) const MODEL_NAMESPACE: &str = "model";
+
+/// Approximation used for models of no known family.
+static DEFAULT_FAMILY: ModelFamily = ModelFamily {
+    chars_per_token: 4.0,
+    tokens_per_message: 4,
+    prefixes: Vec::new(),
+};
+
+/// Known models with their limits and prices, and the tokenizer
+/// approximations of their families.
+///
+/// The engine derives request attributes from it, so budget policies can
+/// read `llm.estimated_prompt_tokens`, `llm.estimated_cost_usd` and the
+/// `model` namespace (e.g. `model.context_window`) without calling out to a
+/// cost service.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelCatalog {
+    /// Tokenizer approximations by family name
+    #[serde(default)]
+    pub families: HashMap<String, ModelFamily>,
+    /// Models by name
+    #[serde(default)]
+    pub models: HashMap<String, ModelInfo>,
+}
+
+/// Tokenizer approximation of a model family.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelFamily {
+    /// Average characters per token
+    pub chars_per_token: f64,
+    /// Tokens spent on the framing of each chat message
+    #[serde(default)]
+    pub tokens_per_message: u64,
+    /// Name prefixes of models in the family that are not catalogued
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prefixes: Vec<String>,
+}
+
+/// Limits and prices of a model.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelInfo {
+    /// Provider serving the model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Family whose tokenizer approximation applies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub family: Option<String>,
+    /// Maximum prompt and completion tokens together
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u64>,
+    /// Maximum completion tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u64>,
+    /// Price of a million prompt tokens in USD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_cost_per_million: Option<f64>,
+    /// Price of a million completion tokens in USD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_cost_per_million: Option<f64>,
+}
+
+impl ModelCatalog {
+    /// Get the catalog shipped with the engine.
+    pub fn builtin() -> Self {
+        Self::from_yaml(BUILTIN_CATALOG).expect("built-in model catalog is valid")
+    }
+
+    /// Parse a catalog from YAML.
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        serde_yaml::from_str(yaml).map_err(Error::from)
+    }
+
+    /// Load a catalog from a YAML file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_yaml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Add the families and models of another catalog, replacing those with
+    /// the same name.
+    pub fn extend(&mut self, other: ModelCatalog) {
+        self.families.extend(other.families);
+        self.models.extend(other.models);
+    }
+
+    /// Check that ratios and prices are positive and every model's family
+    /// is catalogued.
+    pub fn validate(&self) -> Result<()> {
+        for (name, family) in &self.families {
+            if !family.chars_per_token.is_finite() || family.chars_per_token <= 0.0 {
+                return Err(Error::config(format!(
+                    "Model family '{}' needs a positive chars_per_token",
+                    name
+                )));
+            }
+        }
+        for (name, model) in &self.models {
+            if let Some(family) = model.family.as_deref() {
+                if !self.families.contains_key(family) {
+                    return Err(Error::config(format!(
+                        "Model '{}' has unknown family '{}'",
+                        name, family
+                    )));
+                }
+            }
+            let prices = [model.input_cost_per_million, model.output_cost_per_million];
+            if prices
+                .into_iter()
+                .flatten()
+                .any(|price| price.is_nan() || price < 0.0)
+            {
+                return Err(Error::config(format!(
+                    "Model '{}' has an invalid price",
+                    name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Look up a model by name.
+    ///
+    /// Versioned names such as `gpt-4o-2024-08-06` fall back to the longest
+    /// catalogued name they extend.
+    pub fn model(&self, name: &str) -> Option<(&str, &ModelInfo)> {
+        if let Some((name, model)) = self.models.get_key_value(name) {
+            return Some((name.as_str(), model));
+        }
+        self.models
+            .iter()
+            .filter(|(known, _)| {
+                name.strip_prefix(known.as_str())
+                    .is_some_and(|version| version.starts_with(['-', '@', ':']))
+            })
+            .max_by_key(|(known, _)| known.len())
+            .map(|(known, model)| (known.as_str(), model))
+    }
+
+    /// Get the tokenizer approximation for a model.
+    ///
+    /// Models that are not catalogued are matched to a family by name
+    /// prefix; a generic approximation is used when none matches.
+    pub fn family(&self, model: &str) -> &ModelFamily {
+        let catalogued = self
+            .model(model)
+            .and_then(|(_, info)| info.family.as_deref())
+            .and_then(|family| self.families.get(family));
+        catalogued
+            .or_else(|| {
+                self.families
+                    .values()
+                    .flat_map(|family| family.prefixes.iter().map(move |prefix| (prefix, family)))
+                    .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+                    .max_by_key(|(prefix, _)| prefix.len())
+                    .map(|(_, family)| family)
+            })
+            .unwrap_or(&DEFAULT_FAMILY)
+    }
+
+    /// Estimate the prompt tokens of a request from its prompt, messages
+    /// and tool definitions.
+    pub fn estimate_prompt_tokens(&self, llm: &LlmContext) -> u64 {
+        let family = self.family(llm.model.as_deref().unwrap_or_default());
+        let tokens =
+            |text: &str| (text.chars().count() as f64 / family.chars_per_token).ceil() as u64;
+
+        let prompt = llm.prompt.as_deref().map_or(0, tokens);
+        let messages: u64 = llm
+            .messages
+            .iter()
+            .map(|message| tokens(&message.content) + family.tokens_per_message)
+            .sum();
+        let tools: u64 = llm
+            .tools
+            .iter()
+            .filter_map(|tool| serde_json::to_string(tool).ok())
+            .map(|tool| tokens(&tool))
+            .sum();
+        prompt + messages + tools
+    }
+
+    /// Estimate the most a request can cost in USD.
+    ///
+    /// The completion is assumed to use `max_tokens`, or the model's output
+    /// limit when the request sets none. Returns `None` for models without
+    /// a prompt price.
+    pub fn estimate_cost_usd(&self, llm: &LlmContext) -> Option<f64> {
+        self.cost(llm, self.estimate_prompt_tokens(llm))
+    }
+
+    fn cost(&self, llm: &LlmContext, prompt_tokens: u64) -> Option<f64> {
+        let (_, model) = self.model(llm.model.as_deref()?)?;
+        let completion_tokens = llm
+            .max_tokens
+            .map(u64::from)
+            .or(model.max_output_tokens)
+            .unwrap_or_default();
+        let input = model.input_cost_per_million?;
+        let output = model.output_cost_per_million.unwrap_or_default();
+        Some((prompt_tokens as f64 * input + completion_tokens as f64 * output) / 1_000_000.0)
+    }
+
+    /// Add the derived attributes to a request, keeping any it carries.
+    ///
+    /// Returns `None` for requests without an LLM section.
+    pub(crate) fn enrich(&self, context: &EvaluationContext) -> Option<EvaluationContext> {
+        let llm = context.llm.as_ref()?;
+        let mut enriched = context.clone();
+        let derived = enriched.llm.as_mut()?;
+
+        let prompt_tokens = *derived
+            .estimated_prompt_tokens
+            .get_or_insert_with(|| self.estimate_prompt_tokens(llm));
+        if derived.estimated_cost_usd.is_none() {
+            derived.estimated_cost_usd = self.cost(llm, prompt_tokens);
+        }
+
+        if let Some((name, model)) = llm.model.as_deref().and_then(|name| self.model(name)) {
+            if let Ok(serde_json::Value::Object(mut attributes)) = serde_json::to_value(model) {
+                attributes.insert("name".to_string(), serde_json::json!(name));
+                enriched
+                    .attributes
+                    .entry(MODEL_NAMESPACE.to_string())
+                    .or_insert(serde_json::Value::Object(attributes));
+            }
+        }
+        Some(enriched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ChatMessage;
+
+    #[test]
+    fn test_builtin_catalog() {
+        let catalog = ModelCatalog::builtin();
+        catalog.validate().unwrap();
+
+        let (name, model) = catalog.model("gpt-4o-mini-2024-07-18").unwrap();
+        assert_eq!(name, "gpt-4o-mini");
+        assert_eq!(model.context_window, Some(128000));
+        assert!(catalog.model("gpt-4oo").is_none());
+
+        assert_eq!(catalog.family("claude-3-7-sonnet").chars_per_token, 3.5);
+        assert_eq!(catalog.family("unknown"), &DEFAULT_FAMILY);
+    }
+
+    #[test]
+    fn test_estimates() {
+        let mut catalog = ModelCatalog::builtin();
+        catalog.extend(
+            ModelCatalog::from_yaml(
+                "models:\n  house-model:\n    family: gpt\n    input_cost_per_million: 1000000\n",
+            )
+            .unwrap(),
+        );
+        catalog.validate().unwrap();
+
+        let llm = LlmContext {
+            model: Some("house-model".to_string()),
+            prompt: Some("a".repeat(40)),
+            max_tokens: Some(100),
+            messages: vec![ChatMessage::user("b".repeat(8))],
+            ..Default::default()
+        };
+        // 10 prompt tokens, 2 message tokens and 4 for the message framing
+        assert_eq!(catalog.estimate_prompt_tokens(&llm), 16);
+        assert_eq!(catalog.estimate_cost_usd(&llm), Some(16.0));
+
+        let context = EvaluationContext {
+            llm: Some(llm),
+            ..Default::default()
+        };
+        let enriched = catalog.enrich(&context).unwrap();
+        assert_eq!(
+            enriched.get("llm.estimated_prompt_tokens"),
+            Some(serde_json::json!(16))
+        );
+        assert_eq!(
+            enriched.get("model.name"),
+            Some(serde_json::json!("house-model"))
+        );
+        assert_eq!(enriched.get("model.family"), Some(serde_json::json!("gpt")));
+
+        let invalid = ModelCatalog::from_yaml("models:\n  m:\n    family: nope\n").unwrap();
+        assert!(invalid.validate().is_err());
+        assert!(ModelCatalog::from_yaml("models:\n  m:\n    price: 1\n").is_err());
+    }
+}
@@ -3,6 +3,9 @@
 //! This module defines the context structures passed to policy evaluation,
 //! matching the LLM Dev Ops platform conventions.
 
+use super::{
+    ChatMessage, FieldPath, MessageRole, PathSegment, ResponseContext, ToolCall, ToolDefinition,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -24,9 +27,16 @@ pub struct EvaluationContext {
     /// Request context (IP, user agent, timestamp)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub request: Option<RequestContext>,
+    /// Model response, present when evaluating the response phase
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<ResponseContext>,
     /// Additional metadata
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub metadata: HashMap<String, serde_json::Value>,
+    /// External attributes by namespace, such as `budget` or `directory`,
+    /// usually filled in by context resolvers
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub attributes: HashMap<String, serde_json::Value>,
 }
 
 impl EvaluationContext {
@@ -40,43 +50,43 @@ impl EvaluationContext {
         EvaluationContextBuilder::new()
     }
 
-    /// Get a value from the context by path (e.g., "llm.model", "user.roles").
+    /// Get a value from the context by path (e.g., "llm.model", "user.roles",
+    /// "metadata.messages[0].role"); see [`FieldPath`] for the syntax.
     pub fn get(&self, path: &str) -> Option<serde_json::Value> {
-        let parts: Vec<&str> = path.split('.').collect();
-        if parts.is_empty() {
-            return None;
-        }
+        self.resolve(&FieldPath::parse(path).ok()?)
+    }
 
-        match parts[0] {
-            "llm" => {
-                let llm = self.llm.as_ref()?;
-                get_llm_field(llm, &parts[1..])
-            }
-            "user" => {
-                let user = self.user.as_ref()?;
-                get_user_field(user, &parts[1..])
-            }
-            "team" => {
-                let team = self.team.as_ref()?;
-                get_team_field(team, &parts[1..])
-            }
-            "project" => {
-                let project = self.project.as_ref()?;
-                get_project_field(project, &parts[1..])
+    /// Get a value from the context by a pre-parsed path.
+    ///
+    /// The section and field are read directly; any further segments are
+    /// followed through the field's JSON value. Any other section names an
+    /// attribute namespace.
+    pub fn resolve(&self, path: &FieldPath) -> Option<serde_json::Value> {
+        let keys: Vec<&str> = path
+            .segments()
+            .iter()
+            .take(2)
+            .map_while(PathSegment::as_key)
+            .collect();
+        let (section, field) = (*keys.first()?, keys.get(1).copied());
+
+        let value = match section {
+            "llm" => get_llm_field(self.llm.as_ref()?, field),
+            "user" => get_user_field(self.user.as_ref()?, field),
+            "team" => get_team_field(self.team.as_ref()?, field),
+            "project" => get_project_field(self.project.as_ref()?, field),
+            "request" => get_request_field(self.request.as_ref()?, field),
+            "response" => get_response_field(self.response.as_ref()?, field),
+            "metadata" => match field {
+                Some(key) => self.metadata.get(key).cloned(),
+                None => serde_json::to_value(&self.metadata).ok(),
+            },
+            namespace => {
+                let attributes = self.attributes.get(namespace)?;
+                return path.select(attributes, 1);
             }
-            "request" => {
-                let request = self.request.as_ref()?;
-                get_request_field(request, &parts[1..])
-            }
-            "metadata" => {
-                if parts.len() > 1 {
-                    self.metadata.get(parts[1]).cloned()
-                } else {
-                    Some(serde_json::to_value(&self.metadata).ok()?)
-                }
-            }
-            _ => None,
-        }
+        }?;
+        path.select(&value, keys.len())
     }
 
     /// Convert to a JSON value for expression evaluation.
@@ -85,25 +95,77 @@ impl EvaluationContext {
     }
 }
 
-fn get_llm_field(llm: &LlmContext, parts: &[&str]) -> Option<serde_json::Value> {
-    if parts.is_empty() {
-        return Some(serde_json::to_value(llm).ok()?);
+/// Rewrite the camelCase aliases accepted by field lookups to the
+/// serialized field names.
+pub(crate) fn canonical_field(path: &str) -> String {
+    let mut parts: Vec<&str> = path.split('.').collect();
+    if parts.len() > 1 {
+        parts[1] = match (parts[0], parts[1]) {
+            ("llm", "maxTokens") => "max_tokens",
+            ("llm", "functions") => "tools",
+            ("llm", "toolCalls") => "tool_calls",
+            ("llm", "estimatedPromptTokens") => "estimated_prompt_tokens",
+            ("llm", "estimatedCostUsd") => "estimated_cost_usd",
+            ("request", "ipAddress") => "ip_address",
+            ("request", "userAgent") => "user_agent",
+            ("response", "finishReason") => "finish_reason",
+            ("response", "toolCalls") => "tool_calls",
+            (_, field) => field,
+        };
     }
-    match parts[0] {
+    parts.join(".")
+}
+
+fn get_llm_field(llm: &LlmContext, field: Option<&str>) -> Option<serde_json::Value> {
+    let Some(field) = field else {
+        return serde_json::to_value(llm).ok();
+    };
+    match field {
         "provider" => llm.provider.as_ref().map(|v| serde_json::json!(v)),
         "model" => llm.model.as_ref().map(|v| serde_json::json!(v)),
         "prompt" => llm.prompt.as_ref().map(|v| serde_json::json!(v)),
         "maxTokens" | "max_tokens" => llm.max_tokens.map(|v| serde_json::json!(v)),
         "temperature" => llm.temperature.map(|v| serde_json::json!(v)),
+        "messages" => (!llm.messages.is_empty()).then(|| serde_json::json!(llm.messages)),
+        "turns" => Some(serde_json::json!(llm.turns())),
+        "systemPrompt" | "system_prompt" => llm.system_prompt().map(serde_json::Value::String),
+        "tools" | "functions" => (!llm.tools.is_empty()).then(|| serde_json::json!(llm.tools)),
+        "toolCalls" | "tool_calls" => {
+            (!llm.tool_calls.is_empty()).then(|| serde_json::json!(llm.tool_calls))
+        }
+        "estimatedPromptTokens" | "estimated_prompt_tokens" => {
+            llm.estimated_prompt_tokens.map(|v| serde_json::json!(v))
+        }
+        "estimatedCostUsd" | "estimated_cost_usd" => {
+            llm.estimated_cost_usd.map(|v| serde_json::json!(v))
+        }
         _ => None,
     }
 }
 
-fn get_user_field(user: &UserContext, parts: &[&str]) -> Option<serde_json::Value> {
-    if parts.is_empty() {
-        return Some(serde_json::to_value(user).ok()?);
+fn get_response_field(response: &ResponseContext, field: Option<&str>) -> Option<serde_json::Value> {
+    let Some(field) = field else {
+        return serde_json::to_value(response).ok();
+    };
+    match field {
+        "content" => response.content.as_ref().map(|v| serde_json::json!(v)),
+        "finishReason" | "finish_reason" => {
+            response.finish_reason.as_ref().map(|v| serde_json::json!(v))
+        }
+        "usage" => response.usage.map(|v| serde_json::json!(v)),
+        "model" => response.model.as_ref().map(|v| serde_json::json!(v)),
+        "toolCalls" | "tool_calls" => {
+            (!response.tool_calls.is_empty()).then(|| serde_json::json!(response.tool_calls))
+        }
+        _ => None,
     }
-    match parts[0] {
+}
+
+fn get_user_field(user: &UserContext, field: Option<&str>) -> Option<serde_json::Value> {
+    let Some(field) = field else {
+        return serde_json::to_value(user).ok();
+    };
+    match field {
         "id" => Some(serde_json::json!(&user.id)),
         "email" => user.email.as_ref().map(|v| serde_json::json!(v)),
         "roles" => Some(serde_json::json!(&user.roles)),
@@ -112,11 +174,11 @@ fn get_user_field(user: &UserContext, parts: &[&str]) -> Option<serde_json::Valu
     }
 }
 
-fn get_team_field(team: &TeamContext, parts: &[&str]) -> Option<serde_json::Value> {
-    if parts.is_empty() {
-        return Some(serde_json::to_value(team).ok()?);
-    }
-    match parts[0] {
+fn get_team_field(team: &TeamContext, field: Option<&str>) -> Option<serde_json::Value> {
+    let Some(field) = field else {
+        return serde_json::to_value(team).ok();
+    };
+    match field {
         "id" => Some(serde_json::json!(&team.id)),
         "name" => team.name.as_ref().map(|v| serde_json::json!(v)),
         "tier" => team.tier.as_ref().map(|v| serde_json::json!(v)),
@@ -124,11 +186,11 @@ fn get_team_field(team: &TeamContext, parts: &[&str]) -> Option<serde_json::Valu
     }
 }
 
-fn get_project_field(project: &ProjectContext, parts: &[&str]) -> Option<serde_json::Value> {
-    if parts.is_empty() {
-        return Some(serde_json::to_value(project).ok()?);
-    }
-    match parts[0] {
+fn get_project_field(project: &ProjectContext, field: Option<&str>) -> Option<serde_json::Value> {
+    let Some(field) = field else {
+        return serde_json::to_value(project).ok();
+    };
+    match field {
         "id" => Some(serde_json::json!(&project.id)),
         "name" => project.name.as_ref().map(|v| serde_json::json!(v)),
         "environment" => project.environment.as_ref().map(|v| serde_json::json!(v)),
@@ -136,11 +198,11 @@ fn get_project_field(project: &ProjectContext, parts: &[&str]) -> Option<serde_j
     }
 }
 
-fn get_request_field(request: &RequestContext, parts: &[&str]) -> Option<serde_json::Value> {
-    if parts.is_empty() {
-        return Some(serde_json::to_value(request).ok()?);
-    }
-    match parts[0] {
+fn get_request_field(request: &RequestContext, field: Option<&str>) -> Option<serde_json::Value> {
+    let Some(field) = field else {
+        return serde_json::to_value(request).ok();
+    };
+    match field {
         "id" => Some(serde_json::json!(&request.id)),
         "timestamp" => request.timestamp.map(|v| serde_json::json!(v)),
         "ipAddress" | "ip_address" => request.ip_address.as_ref().map(|v| serde_json::json!(v)),
@@ -167,9 +229,44 @@ pub struct LlmContext {
     /// Temperature setting
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f32>,
-    /// Function definitions (for function calling)
+    /// Chat messages of the conversation, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<ChatMessage>,
+    /// Tools offered to the model (`functions` is accepted as an alias)
+    #[serde(default, alias = "functions", skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<ToolDefinition>,
+    /// Tool calls made so far, oldest first, forming the call chain of an
+    /// agentic request
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// Estimated prompt tokens, derived from the model catalog unless given
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub functions: Option<Vec<serde_json::Value>>,
+    pub estimated_prompt_tokens: Option<u64>,
+    /// Estimated maximum cost of the request in USD, derived from the model
+    /// catalog unless given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_cost_usd: Option<f64>,
+}
+
+impl LlmContext {
+    /// Get the number of conversation turns, counted as user messages.
+    pub fn turns(&self) -> usize {
+        self.messages
+            .iter()
+            .filter(|m| m.role == MessageRole::User)
+            .count()
+    }
+
+    /// Get the system prompt, joining every system message.
+    pub fn system_prompt(&self) -> Option<String> {
+        let system: Vec<_> = self
+            .messages
+            .iter()
+            .filter(|m| m.role == MessageRole::System)
+            .map(|m| m.content.as_str())
+            .collect();
+        (!system.is_empty()).then(|| system.join("\n"))
+    }
 }
 
 /// User context.
@@ -238,7 +335,9 @@ pub struct EvaluationContextBuilder {
     team: Option<TeamContext>,
     project: Option<ProjectContext>,
     request: Option<RequestContext>,
+    response: Option<ResponseContext>,
     metadata: HashMap<String, serde_json::Value>,
+    attributes: HashMap<String, serde_json::Value>,
 }
 
 impl EvaluationContextBuilder {
@@ -277,6 +376,30 @@ impl EvaluationContextBuilder {
         self
     }
 
+    /// Add a message to the end of the conversation.
+    pub fn with_message(mut self, message: ChatMessage) -> Self {
+        self.llm
+            .get_or_insert_with(LlmContext::default)
+            .messages
+            .push(message);
+        self
+    }
+
+    /// Offer a tool to the model.
+    pub fn with_tool(mut self, tool: ToolDefinition) -> Self {
+        self.llm.get_or_insert_with(LlmContext::default).tools.push(tool);
+        self
+    }
+
+    /// Add a tool call to the end of the call chain.
+    pub fn with_tool_call(mut self, call: ToolCall) -> Self {
+        self.llm
+            .get_or_insert_with(LlmContext::default)
+            .tool_calls
+            .push(call);
+        self
+    }
+
     /// Set the user ID.
     pub fn with_user_id(mut self, id: impl Into<String>) -> Self {
         self.user = Some(UserContext {
@@ -356,12 +479,28 @@ impl EvaluationContextBuilder {
         self
     }
 
+    /// Set the model response.
+    pub fn with_response(mut self, response: ResponseContext) -> Self {
+        self.response = Some(response);
+        self
+    }
+
     /// Add metadata.
     pub fn with_metadata(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
         self.metadata.insert(key.into(), value);
         self
     }
 
+    /// Set the external attributes of a namespace.
+    pub fn with_attributes(
+        mut self,
+        namespace: impl Into<String>,
+        value: serde_json::Value,
+    ) -> Self {
+        self.attributes.insert(namespace.into(), value);
+        self
+    }
+
     /// Build the evaluation context.
     pub fn build(self) -> EvaluationContext {
         EvaluationContext {
@@ -370,7 +509,9 @@ impl EvaluationContextBuilder {
             team: self.team,
             project: self.project,
             request: self.request,
+            response: self.response,
             metadata: self.metadata,
+            attributes: self.attributes,
         }
     }
 }
@@ -404,6 +545,27 @@ mod tests {
         assert_eq!(ctx.get("llm.model"), Some(serde_json::json!("gpt-4")));
         assert_eq!(ctx.get("user.id"), Some(serde_json::json!("user-123")));
         assert_eq!(ctx.get("user.roles"), Some(serde_json::json!(["admin"])));
+        assert_eq!(ctx.get("user.roles[0]"), Some(serde_json::json!("admin")));
+        assert_eq!(ctx.get("user.roles.#"), Some(serde_json::json!(1)));
+        assert_eq!(ctx.get("user.email.#"), Some(serde_json::json!(16)));
+        assert_eq!(ctx.get("user.unknown"), None);
+    }
+
+    #[test]
+    fn test_context_get_nested() {
+        let ctx = EvaluationContext::builder()
+            .with_tool(ToolDefinition::new("search"))
+            .with_tool_call(ToolCall::new("search", serde_json::json!({"query": "rust"})))
+            .with_metadata("gateway", serde_json::json!({"region": {"name": "eu-west"}}))
+            .build();
+
+        assert_eq!(ctx.get("metadata.gateway.region.name"), Some(serde_json::json!("eu-west")));
+        assert_eq!(ctx.get("llm.functions[0].name"), Some(serde_json::json!("search")));
+        assert_eq!(ctx.get("llm.functions[*].name"), Some(serde_json::json!(["search"])));
+        assert_eq!(ctx.get("llm.tool_calls[0].arguments.query"), Some(serde_json::json!("rust")));
+        assert_eq!(ctx.get("llm.tool_calls.#"), Some(serde_json::json!(1)));
+        assert_eq!(ctx.get("metadata.gateway.zone"), None);
+        assert_eq!(ctx.get("metadata..gateway"), None);
     }
 
     #[test]
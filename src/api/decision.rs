@@ -1,6 +1,8 @@
 //! Policy decision types.
 
-use crate::policy::DecisionType;
+use super::context::canonical_field;
+use super::{EvaluationContext, ResponseContext};
+use crate::policy::{Action, ActionType, CombiningAlgorithm, DecisionType, Modification, Obligation};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Duration;
@@ -26,12 +28,36 @@ pub struct PolicyDecision {
     /// Modifications to apply (for modify decisions)
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub modifications: HashMap<String, serde_json::Value>,
+    /// Every modification requested by matched rules, in application order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub field_modifications: Vec<FieldModification>,
+    /// Fields modified by more than one policy
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modification_conflicts: Vec<ModificationConflict>,
     /// Additional metadata
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub metadata: HashMap<String, serde_json::Value>,
+    /// Actions requested by matched rules alongside the decision
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<RuleAction>,
+    /// Obligations of matched rules and whether they were fulfilled
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub obligations: Vec<DecisionObligation>,
+    /// Algorithm that combined the policy results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub combining_algorithm: Option<CombiningAlgorithm>,
+    /// Rule-combining algorithm of each matched policy
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub rule_combining_algorithms: HashMap<String, CombiningAlgorithm>,
     /// Evaluation trace for debugging
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub trace: Option<EvaluationTrace>,
+    /// Whether the decision was served from the cache
+    #[serde(default)]
+    pub cached: bool,
+    /// Generation of the policy snapshot that produced the decision
+    #[serde(default)]
+    pub policy_generation: u64,
 }
 
 impl PolicyDecision {
@@ -45,8 +71,16 @@ impl PolicyDecision {
             matched_rules: Vec::new(),
             evaluation_time_ms: 0.0,
             modifications: HashMap::new(),
+            field_modifications: Vec::new(),
+            modification_conflicts: Vec::new(),
             metadata: HashMap::new(),
+            actions: Vec::new(),
+            obligations: Vec::new(),
+            combining_algorithm: None,
+            rule_combining_algorithms: HashMap::new(),
             trace: None,
+            cached: false,
+            policy_generation: 0,
         }
     }
 
@@ -60,8 +94,16 @@ impl PolicyDecision {
             matched_rules: Vec::new(),
             evaluation_time_ms: 0.0,
             modifications: HashMap::new(),
+            field_modifications: Vec::new(),
+            modification_conflicts: Vec::new(),
             metadata: HashMap::new(),
+            actions: Vec::new(),
+            obligations: Vec::new(),
+            combining_algorithm: None,
+            rule_combining_algorithms: HashMap::new(),
             trace: None,
+            cached: false,
+            policy_generation: 0,
         }
     }
 
@@ -75,8 +117,16 @@ impl PolicyDecision {
             matched_rules: Vec::new(),
             evaluation_time_ms: 0.0,
             modifications: HashMap::new(),
+            field_modifications: Vec::new(),
+            modification_conflicts: Vec::new(),
             metadata: HashMap::new(),
+            actions: Vec::new(),
+            obligations: Vec::new(),
+            combining_algorithm: None,
+            rule_combining_algorithms: HashMap::new(),
             trace: None,
+            cached: false,
+            policy_generation: 0,
         }
     }
 
@@ -90,8 +140,38 @@ impl PolicyDecision {
             matched_rules: Vec::new(),
             evaluation_time_ms: 0.0,
             modifications,
+            field_modifications: Vec::new(),
+            modification_conflicts: Vec::new(),
             metadata: HashMap::new(),
+            actions: Vec::new(),
+            obligations: Vec::new(),
+            combining_algorithm: None,
+            rule_combining_algorithms: HashMap::new(),
             trace: None,
+            cached: false,
+            policy_generation: 0,
+        }
+    }
+
+    /// Create a decision recording that no policy applied.
+    pub fn not_applicable() -> Self {
+        Self {
+            decision: DecisionType::NotApplicable,
+            ..Self::allow()
+        }
+    }
+
+    /// Create a decision of the given type.
+    ///
+    /// The reason is kept for deny and warn decisions; modify decisions
+    /// carry no modifications.
+    pub fn from_decision_type(decision: DecisionType, reason: impl Into<String>) -> Self {
+        match decision {
+            DecisionType::Allow => Self::allow(),
+            DecisionType::Deny => Self::deny(reason),
+            DecisionType::Warn => Self::warn(reason),
+            DecisionType::Modify => Self::modify(HashMap::new()),
+            DecisionType::NotApplicable => Self::not_applicable(),
         }
     }
 
@@ -143,6 +223,71 @@ impl PolicyDecision {
         self
     }
 
+    /// Apply the decision's modifications to a request context.
+    ///
+    /// Fields may use the same camelCase aliases as conditions, such as
+    /// `llm.maxTokens`. Fails if a modification cannot be applied or leaves
+    /// a field with a value of the wrong type.
+    pub fn apply(&self, context: &EvaluationContext) -> crate::Result<EvaluationContext> {
+        let mut document = context.to_json();
+        for mut modification in self.planned_modifications() {
+            modification.field = canonical_field(&modification.field);
+            modification.apply(&mut document)?;
+        }
+        serde_json::from_value(document).map_err(|e| {
+            crate::Error::evaluation(format!("Modified context is invalid: {}", e))
+        })
+    }
+
+    /// Apply the decision's modifications of `response` fields to a model
+    /// response, such as redactions made by response-phase policies.
+    pub fn apply_response(&self, response: &ResponseContext) -> crate::Result<ResponseContext> {
+        let mut document = serde_json::json!({ "response": response });
+        for mut modification in self.planned_modifications() {
+            if modification.field.split('.').next() != Some("response") {
+                continue;
+            }
+            modification.field = canonical_field(&modification.field);
+            modification.apply(&mut document)?;
+        }
+        serde_json::from_value(document["response"].take()).map_err(|e| {
+            crate::Error::evaluation(format!("Modified response is invalid: {}", e))
+        })
+    }
+
+    /// Apply the decision's modifications to a raw JSON payload.
+    pub fn apply_json(&self, payload: &serde_json::Value) -> crate::Result<serde_json::Value> {
+        let mut document = payload.clone();
+        for modification in self.planned_modifications() {
+            modification.apply(&mut document)?;
+        }
+        Ok(document)
+    }
+
+    /// Get the modifications in application order.
+    ///
+    /// Entries of [`modifications`](Self::modifications) that no field
+    /// modification covers are set last, in field order.
+    fn planned_modifications(&self) -> Vec<Modification> {
+        let mut planned: Vec<_> = self
+            .field_modifications
+            .iter()
+            .map(|m| m.modification.clone())
+            .collect();
+
+        let mut sets: Vec<_> = self
+            .modifications
+            .iter()
+            .filter(|(field, _)| !planned.iter().any(|m| &m.field == *field))
+            .collect();
+        sets.sort_by(|a, b| a.0.cmp(b.0));
+        planned.extend(
+            sets.into_iter()
+                .map(|(field, value)| Modification::set(field, value.clone())),
+        );
+        planned
+    }
+
     /// Check if this decision is a success (not an error).
     pub fn is_success(&self) -> bool {
         true // Policy decisions are always successful; errors are handled separately
@@ -155,6 +300,180 @@ impl Default for PolicyDecision {
     }
 }
 
+/// An action requested by a matched rule in addition to its decision.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleAction {
+    /// Policy containing the rule
+    pub policy_id: String,
+    /// Rule that requested the action
+    pub rule_id: String,
+    /// Kind of action
+    #[serde(rename = "type")]
+    pub action_type: ActionType,
+    /// Message given by the rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Action parameters
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub parameters: HashMap<String, serde_json::Value>,
+}
+
+impl RuleAction {
+    /// Create a rule action from a policy action.
+    pub fn new(policy_id: impl Into<String>, rule_id: impl Into<String>, action: &Action) -> Self {
+        Self {
+            policy_id: policy_id.into(),
+            rule_id: rule_id.into(),
+            action_type: action.action_type,
+            message: action.reason.clone(),
+            parameters: action.metadata.clone(),
+        }
+    }
+}
+
+/// A modification requested by a matched rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldModification {
+    /// Policy containing the rule
+    pub policy_id: String,
+    /// Rule that requested the modification
+    pub rule_id: String,
+    /// The modification itself
+    #[serde(flatten)]
+    pub modification: Modification,
+}
+
+impl FieldModification {
+    /// Create a field modification.
+    pub fn new(
+        policy_id: impl Into<String>,
+        rule_id: impl Into<String>,
+        modification: Modification,
+    ) -> Self {
+        Self {
+            policy_id: policy_id.into(),
+            rule_id: rule_id.into(),
+            modification,
+        }
+    }
+}
+
+/// A field modified by several policies.
+///
+/// Modifications are applied in order, so the last policy listed wins.
+/// Fields conflict when they are equal or one contains the other.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModificationConflict {
+    /// The outermost of the overlapping fields
+    pub field: String,
+    /// Policies modifying the field, in application order
+    pub policies: Vec<String>,
+}
+
+impl ModificationConflict {
+    /// Find the fields modified by more than one policy.
+    pub fn detect(modifications: &[FieldModification]) -> Vec<Self> {
+        let segments: Vec<Vec<&str>> = modifications
+            .iter()
+            .map(|m| m.modification.field.split('.').collect())
+            .collect();
+        let mut conflicts: Vec<Self> = Vec::new();
+
+        for (i, first) in modifications.iter().enumerate() {
+            for (j, second) in modifications.iter().enumerate().skip(i + 1) {
+                if first.policy_id == second.policy_id {
+                    continue;
+                }
+                let (outer, inner) = if segments[i].len() <= segments[j].len() {
+                    (i, j)
+                } else {
+                    (j, i)
+                };
+                if !segments[inner].starts_with(&segments[outer]) {
+                    continue;
+                }
+
+                let field = &modifications[outer].modification.field;
+                let conflict = match conflicts.iter().position(|c| &c.field == field) {
+                    Some(position) => &mut conflicts[position],
+                    None => {
+                        conflicts.push(Self {
+                            field: field.clone(),
+                            policies: Vec::new(),
+                        });
+                        conflicts.last_mut().expect("just pushed")
+                    }
+                };
+                for policy_id in [&first.policy_id, &second.policy_id] {
+                    if !conflict.policies.contains(policy_id) {
+                        conflict.policies.push(policy_id.clone());
+                    }
+                }
+            }
+        }
+
+        conflicts
+    }
+}
+
+/// An obligation of a matched rule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionObligation {
+    /// Policy containing the rule
+    pub policy_id: String,
+    /// Rule that requested the obligation
+    pub rule_id: String,
+    /// The obligation itself
+    #[serde(flatten)]
+    pub obligation: Obligation,
+    /// Whether the request is denied when the obligation fails
+    #[serde(default)]
+    pub mandatory: bool,
+    /// Whether the obligation was fulfilled
+    #[serde(default)]
+    pub status: ObligationStatus,
+    /// Why the obligation failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DecisionObligation {
+    /// Create a pending obligation.
+    pub fn new(
+        policy_id: impl Into<String>,
+        rule_id: impl Into<String>,
+        obligation: Obligation,
+        mandatory: bool,
+    ) -> Self {
+        Self {
+            policy_id: policy_id.into(),
+            rule_id: rule_id.into(),
+            obligation,
+            mandatory,
+            status: ObligationStatus::Pending,
+            error: None,
+        }
+    }
+
+    /// Whether the caller still has to fulfil the obligation.
+    pub fn is_pending(&self) -> bool {
+        self.status == ObligationStatus::Pending
+    }
+}
+
+/// Progress of a decision obligation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObligationStatus {
+    /// Left for the caller to fulfil
+    #[default]
+    Pending,
+    /// Fulfilled by the engine
+    Fulfilled,
+    /// The engine tried and failed to fulfil it
+    Failed,
+}
+
 /// Trace information for debugging policy evaluation.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct EvaluationTrace {
@@ -164,6 +483,9 @@ pub struct EvaluationTrace {
     pub policies_evaluated: usize,
     /// Total number of rules evaluated
     pub rules_evaluated: usize,
+    /// Number of policies skipped because their target did not select the request
+    #[serde(default)]
+    pub policies_skipped: usize,
     /// Whether the result was cached
     pub cached: bool,
 }
@@ -196,6 +518,24 @@ pub struct TraceStep {
     pub result: String,
     /// Duration of this step in microseconds
     pub duration_us: u64,
+    /// Operator of a condition step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator: Option<String>,
+    /// Field read by a condition step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    /// Value resolved from the context for a condition step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actual: Option<serde_json::Value>,
+    /// Value a condition step compared against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<serde_json::Value>,
+    /// Boolean outcome of a condition step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<bool>,
+    /// Error that ended the step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 impl TraceStep {
@@ -206,6 +546,12 @@ impl TraceStep {
             id: id.into(),
             result: result.into(),
             duration_us: duration.as_micros() as u64,
+            operator: None,
+            field: None,
+            actual: None,
+            expected: None,
+            outcome: None,
+            error: None,
         }
     }
 
@@ -216,6 +562,12 @@ impl TraceStep {
             id: id.into(),
             result: result.into(),
             duration_us: duration.as_micros() as u64,
+            operator: None,
+            field: None,
+            actual: None,
+            expected: None,
+            outcome: None,
+            error: None,
         }
     }
 
@@ -226,8 +578,53 @@ impl TraceStep {
             id: id.into(),
             result: result.into(),
             duration_us: duration.as_micros() as u64,
+            operator: None,
+            field: None,
+            actual: None,
+            expected: None,
+            outcome: None,
+            error: None,
         }
     }
+
+    /// Set the operator.
+    pub fn with_operator(mut self, operator: impl Into<String>) -> Self {
+        self.operator = Some(operator.into());
+        self
+    }
+
+    /// Set the field.
+    pub fn with_field(mut self, field: impl Into<String>) -> Self {
+        self.field = Some(field.into());
+        self
+    }
+
+    /// Set the resolved context value.
+    pub fn with_actual(mut self, actual: Option<serde_json::Value>) -> Self {
+        self.actual = actual;
+        self
+    }
+
+    /// Set the expected value.
+    pub fn with_expected(mut self, expected: serde_json::Value) -> Self {
+        self.expected = Some(expected);
+        self
+    }
+
+    /// Record the outcome, or the error that prevented one.
+    pub fn with_outcome(mut self, outcome: &crate::Result<bool>) -> Self {
+        match outcome {
+            Ok(matched) => {
+                self.result = if *matched { "matched" } else { "not_matched" }.to_string();
+                self.outcome = Some(*matched);
+            }
+            Err(e) => {
+                self.result = "error".to_string();
+                self.error = Some(e.to_string());
+            }
+        }
+        self
+    }
 }
 
 /// Type of trace step.
@@ -246,6 +643,19 @@ pub enum TraceStepType {
     IntegrationCall,
 }
 
+impl TraceStepType {
+    /// Get the string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TraceStepType::PolicyEvaluated => "policy_evaluated",
+            TraceStepType::RuleEvaluated => "rule_evaluated",
+            TraceStepType::ConditionEvaluated => "condition_evaluated",
+            TraceStepType::CacheCheck => "cache_check",
+            TraceStepType::IntegrationCall => "integration_call",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -290,6 +700,65 @@ mod tests {
         assert!(decision.modifications.contains_key("llm.maxTokens"));
     }
 
+    #[test]
+    fn test_apply_to_context() {
+        let context = EvaluationContext::builder()
+            .with_provider("openai")
+            .with_prompt("call me at 555-0100")
+            .with_max_tokens(8000)
+            .build();
+
+        let mut decision =
+            PolicyDecision::allow().with_modification("llm.maxTokens", serde_json::json!(1000));
+        decision.field_modifications = vec![
+            FieldModification::new(
+                "p1",
+                "r1",
+                Modification::mask_pattern("llm.prompt", r"\d{3}-\d{4}"),
+            ),
+            FieldModification::new(
+                "p1",
+                "r2",
+                Modification::set("metadata.redacted", serde_json::json!(true)),
+            ),
+        ];
+
+        let modified = decision.apply(&context).unwrap();
+        let llm = modified.llm.unwrap();
+        assert_eq!(llm.prompt.as_deref(), Some("call me at [REDACTED]"));
+        assert_eq!(llm.max_tokens, Some(1000));
+        assert_eq!(modified.metadata["redacted"], true);
+
+        let payload = decision
+            .apply_json(&serde_json::json!({"llm": {"prompt": "555-0100"}}))
+            .unwrap();
+        assert_eq!(payload["llm"]["prompt"], "[REDACTED]");
+        assert_eq!(payload["llm"]["maxTokens"], 1000);
+
+        let invalid =
+            PolicyDecision::allow().with_modification("llm.maxTokens", serde_json::json!("many"));
+        assert!(invalid.apply(&context).is_err());
+    }
+
+    #[test]
+    fn test_modification_conflicts() {
+        let modifications = vec![
+            FieldModification::new("p1", "r1", Modification::set("llm.model", "a".into())),
+            FieldModification::new("p1", "r2", Modification::mask("llm.model")),
+            FieldModification::new("p2", "r1", Modification::remove("llm")),
+            FieldModification::new("p3", "r1", Modification::mask("llm.prompt")),
+            FieldModification::new("p3", "r2", Modification::truncate("user.email", 3)),
+        ];
+
+        assert_eq!(
+            ModificationConflict::detect(&modifications),
+            vec![ModificationConflict {
+                field: "llm".to_string(),
+                policies: vec!["p1".to_string(), "p2".to_string(), "p3".to_string()],
+            }]
+        );
+    }
+
     #[test]
     fn test_decision_serialization() {
         let decision = PolicyDecision::deny("Test")
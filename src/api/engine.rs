@@ -1,25 +1,45 @@
 //! Policy engine implementation.
 
-use super::{EvaluationContext, PolicyDecision};
+use super::fulfilment::Fulfiller;
+use super::resolver::Resolvers;
+use super::{
+    ContextResolver, EvaluationContext, ModelCatalog, ObligationHandler, PolicyDecision,
+    ResponseContext,
+};
 use crate::cache::DecisionCache;
-use crate::config::Config;
-use crate::core::Evaluator;
-use crate::policy::{DecisionType, Policy, PolicyDocument};
+use crate::config::{Config, TimeoutBehavior};
+use crate::core::{
+    Clock, CompiledPolicy, Deadline, Evaluator, PolicySet, ResponseStreamEvaluator,
+};
+use crate::policy::{CombiningAlgorithm, DecisionType, Policy, PolicyDocument, PolicyPhase};
 use crate::telemetry::Telemetry;
-use crate::Result;
+use crate::{Error, Result};
 
-use parking_lot::RwLock;
+use arc_swap::ArcSwap;
+use parking_lot::Mutex;
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tracing::warn;
 
 /// The main policy engine for evaluating policies.
 pub struct PolicyEngine {
-    /// Loaded policies indexed by ID
-    policies: Arc<RwLock<HashMap<String, Policy>>>,
+    /// Current policy snapshot
+    policies: ArcSwap<PolicySet>,
+    /// Serializes policy set updates
+    update_lock: Mutex<()>,
     /// Policy evaluator
     evaluator: Evaluator,
+    /// Caps the number of evaluations in flight
+    limiter: Semaphore,
+    /// Fulfils decision obligations
+    fulfiller: Fulfiller,
+    /// Resolves external context attributes
+    resolvers: Resolvers,
+    /// Models and prices for derived request attributes
+    catalog: ModelCatalog,
     /// Decision cache
     cache: Option<DecisionCache>,
     /// Telemetry instance
@@ -46,8 +66,17 @@ impl PolicyEngine {
         };
 
         Self {
-            policies: Arc::new(RwLock::new(HashMap::new())),
-            evaluator: Evaluator::new(),
+            policies: ArcSwap::from_pointee(PolicySet::default()),
+            update_lock: Mutex::new(()),
+            evaluator: Evaluator::new()
+                .with_cel_budget(config.performance.cel_budget())
+                .with_max_evaluation_time(config.performance.max_evaluation_time())
+                .with_parallelism(config.performance.evaluation_threads())
+                .with_combining_algorithm(config.evaluation.combining_algorithm),
+            limiter: Semaphore::new(config.performance.max_concurrent_evaluations.max(1)),
+            fulfiller: Fulfiller::default(),
+            resolvers: Resolvers::default(),
+            catalog: ModelCatalog::builtin(),
             cache,
             telemetry: None,
             config,
@@ -57,10 +86,22 @@ impl PolicyEngine {
     /// Evaluate policies against the given context.
     ///
     /// This is the main entry point for policy evaluation. It will:
-    /// 1. Check the cache for a cached decision
-    /// 2. Evaluate all enabled policies in priority order
-    /// 3. Return the first deny decision, or allow if no policies deny
-    /// 4. Cache the result for future requests
+    /// 1. Derive token and cost estimates from the model catalog and resolve
+    ///    the external attributes the loaded policies read
+    /// 2. Check the cache for a cached decision
+    /// 3. Evaluate all enabled policies in priority order
+    /// 4. Combine the applicable results, falling back to the configured
+    ///    default decision when no policy applies
+    /// 5. Cache the result for future requests
+    /// 6. Fulfil the decision's obligations, denying the request when a
+    ///    mandatory one fails or a rate limit is exceeded
+    ///
+    /// Evaluation and attribute resolution errors resolve to a deny
+    /// decision, or a warning when the engine is configured to fail open.
+    /// Attribute resolution, evaluation and obligation fulfilment share one
+    /// `max_evaluation_time_ms` budget per request; a request that exceeds
+    /// it is resolved the same way unless the engine is configured to
+    /// return [`Error::Timeout`].
     ///
     /// # Arguments
     /// * `context` - The evaluation context containing LLM, user, and request information
@@ -69,49 +110,298 @@ impl PolicyEngine {
     /// * `Ok(PolicyDecision)` - The result of the evaluation
     /// * `Err(Error)` - If an error occurred during evaluation
     pub async fn evaluate(&self, context: &EvaluationContext) -> Result<PolicyDecision> {
+        self.evaluate_with_options(context, &EvaluationOptions::default())
+            .await
+    }
+
+    /// Evaluate the response-phase policies against a model response.
+    ///
+    /// The response is added to the request context as its `response`
+    /// section. Deny and warn decisions apply to the output as they do to
+    /// requests; redactions are applied with
+    /// [`PolicyDecision::apply_response`]. Response evaluations are not
+    /// cached.
+    pub async fn evaluate_response(
+        &self,
+        context: &EvaluationContext,
+        response: &ResponseContext,
+    ) -> Result<PolicyDecision> {
+        let mut context = context.clone();
+        context.response = Some(response.clone());
+        let options = EvaluationOptions::default().with_phase(PolicyPhase::Response);
+        self.evaluate_with_options(&context, &options).await
+    }
+
+    /// Start evaluating a streamed model response.
+    ///
+    /// The returned evaluator runs the response-phase policies of the
+    /// current snapshot over the completion as its chunks arrive. Derived
+    /// and external attributes are resolved once, up front, and a
+    /// resolution failure is returned as an error. Stream decisions are not
+    /// cached and their obligations are not fulfilled.
+    pub async fn evaluate_response_stream(
+        &self,
+        context: &EvaluationContext,
+    ) -> Result<ResponseStreamEvaluator> {
+        let snapshot = self.policies.load_full();
+        let derived = self.catalog.enrich(context);
+        let context = derived.as_ref().unwrap_or(context);
+        let enriched = self.resolve(context, &snapshot, self.deadline()).await?;
+        Ok(ResponseStreamEvaluator::new(
+            self.evaluator.clone(),
+            snapshot.enabled().to_vec(),
+            enriched.as_ref().unwrap_or(context),
+        ))
+    }
+
+    /// Evaluate policies against the given context with per-request options.
+    ///
+    /// Only policies that run in the requested phase are evaluated.
+    /// Restricting `policy_ids` or `namespace`, evaluating the response
+    /// phase, requesting a trace or running as a dry run bypasses the
+    /// decision cache. A namespace also selects its own default decision
+    /// and failure mode. Dry runs also skip obligations and metrics
+    /// recording so they leave no trace in the engine's state.
+    ///
+    /// The evaluation runs against the policy snapshot current when it
+    /// starts; the decision records that snapshot's generation.
+    ///
+    /// # Arguments
+    /// * `context` - The evaluation context
+    /// * `options` - Options controlling this evaluation
+    ///
+    /// # Returns
+    /// * `Ok(PolicyDecision)` - The result of the evaluation
+    /// * `Err(Error)` - If a requested policy is not loaded or evaluation failed
+    pub async fn evaluate_with_options(
+        &self,
+        context: &EvaluationContext,
+        options: &EvaluationOptions,
+    ) -> Result<PolicyDecision> {
         let start = Instant::now();
+        let deadline = self.deadline();
+        let snapshot = self.policies.load_full();
+        let namespace = options.namespace.as_deref();
+        let use_cache = options.policy_ids.is_empty()
+            && namespace.is_none()
+            && options.phase == PolicyPhase::Request
+            && !options.trace
+            && !options.dry_run
+            // Time conditions of requests without a timestamp read the clock
+            && !(snapshot.reads_clock()
+                && context.request.as_ref().and_then(|r| r.timestamp).is_none());
+
+        // Derive and resolve attributes first so they are part of the cache key
+        let derived = self.catalog.enrich(context);
+        let context = derived.as_ref().unwrap_or(context);
+        let enriched = match self.resolve(context, &snapshot, deadline).await {
+            Ok(enriched) => enriched,
+            Err(e) => {
+                let mut decision = self.failure(e, namespace)?;
+                decision.policy_generation = snapshot.generation();
+                decision.evaluation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+                return Ok(decision);
+            }
+        };
+        let context = enriched.as_ref().unwrap_or(context);
 
         // Check cache
-        if let Some(ref cache) = self.cache {
-            if let Some(cached) = cache.get(context) {
+        if let (true, Some(cache)) = (use_cache, &self.cache) {
+            if let Some(cached) = cache.get(context, snapshot.generation()) {
                 let mut decision = cached;
+                if let Err(e) = self.fulfil(&mut decision, context, deadline).await {
+                    let mut decision = self.failure(e, namespace)?;
+                    decision.policy_generation = snapshot.generation();
+                    decision.evaluation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+                    return Ok(decision);
+                }
                 decision.evaluation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
-                if let Some(ref mut trace) = decision.trace {
-                    trace.cached = true;
+                decision.cached = true;
+                if let Some(ref telemetry) = self.telemetry {
+                    telemetry.record_evaluation(&decision.decision, decision.evaluation_time_ms, true);
                 }
                 return Ok(decision);
             }
         }
 
         // Get policies sorted by priority
-        let policies = self.get_enabled_policies();
+        let mut selected = if !options.policy_ids.is_empty() {
+            snapshot.select(&options.policy_ids)?
+        } else if options.trace || self.evaluator.is_tracing() {
+            // Traces report why policies were skipped, so the evaluator
+            // checks every target itself
+            snapshot.enabled().to_vec()
+        } else {
+            snapshot.applicable(context)
+        };
+        if let Some(namespace) = namespace {
+            selected.retain(|policy| policy.metadata.namespace.as_deref() == Some(namespace));
+        }
+        selected.retain(|policy| policy.phase.includes(options.phase));
 
-        // Evaluate policies
-        let decision = self.evaluator.evaluate(&policies, context)?;
+        // Evaluate policies; the semaphore is never closed
+        let _permit = self.limiter.acquire().await.ok();
+        let (mut final_decision, resolved) = match self
+            .evaluate_blocking(selected, context, options.trace, deadline)
+            .await
+        {
+            Ok(decision) => (self.apply_default(decision, namespace), true),
+            Err(e) => (self.failure(e, namespace)?, false),
+        };
 
         // Calculate final evaluation time
-        let mut final_decision = decision;
+        final_decision.policy_generation = snapshot.generation();
         final_decision.evaluation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
 
-        // Cache result
-        if let Some(ref cache) = self.cache {
+        if options.dry_run {
+            final_decision
+                .metadata
+                .insert("dry_run".to_string(), serde_json::json!(true));
+            return Ok(final_decision);
+        }
+
+        // Cache result; failures may be transient and are not cached.
+        // Obligations are cached unfulfilled and fulfilled on every request
+        if let (true, true, Some(cache)) = (use_cache, resolved, &self.cache) {
             cache.put(context, &final_decision);
         }
+        if let Err(e) = self.fulfil(&mut final_decision, context, deadline).await {
+            final_decision = self.failure(e, namespace)?;
+            final_decision.policy_generation = snapshot.generation();
+            final_decision.evaluation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+        }
 
         // Record metrics
         if let Some(ref telemetry) = self.telemetry {
             telemetry.record_evaluation(
                 &final_decision.decision,
                 final_decision.evaluation_time_ms,
-                self.cache.is_some(),
+                false,
             );
         }
 
         Ok(final_decision)
     }
 
+    /// Start the time budget of a request.
+    fn deadline(&self) -> Deadline {
+        Deadline::after(self.config.performance.max_evaluation_time())
+    }
+
+    /// Resolve the external attributes the snapshot's policies read.
+    ///
+    /// Resolvers that do not finish before the deadline fail with
+    /// [`Error::Timeout`].
+    async fn resolve(
+        &self,
+        context: &EvaluationContext,
+        snapshot: &PolicySet,
+        deadline: Deadline,
+    ) -> Result<Option<EvaluationContext>> {
+        tokio::time::timeout(
+            deadline.remaining(),
+            self.resolvers.enrich(context, snapshot.namespaces()),
+        )
+        .await
+        .unwrap_or_else(|_| Err(timed_out("Context attributes were not resolved", deadline)))
+    }
+
+    /// Evaluate policies on the runtime's blocking threads, so that CEL
+    /// programs and parallel evaluations do not hold up async workers.
+    ///
+    /// The evaluation stops at the deadline; should a single condition run
+    /// past it, the request stops waiting for the evaluation.
+    async fn evaluate_blocking(
+        &self,
+        policies: Vec<Arc<CompiledPolicy>>,
+        context: &EvaluationContext,
+        trace: bool,
+        deadline: Deadline,
+    ) -> Result<PolicyDecision> {
+        let evaluator = self.evaluator.clone();
+        let context = context.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            evaluator.evaluate_compiled_until(&policies, &context, trace, Some(deadline))
+        });
+        match tokio::time::timeout(deadline.remaining(), task).await {
+            Ok(joined) => joined
+                .map_err(|e| Error::evaluation(format!("Policy evaluation task failed: {}", e)))?,
+            Err(_) => Err(timed_out("Policies were not evaluated", deadline)),
+        }
+    }
+
+    /// Fulfil a decision's obligations before the deadline.
+    async fn fulfil(
+        &self,
+        decision: &mut PolicyDecision,
+        context: &EvaluationContext,
+        deadline: Deadline,
+    ) -> Result<()> {
+        tokio::time::timeout(deadline.remaining(), self.fulfiller.fulfil(decision, context))
+            .await
+            .map_err(|_| timed_out("Obligations were not fulfilled", deadline))
+    }
+
+    /// Resolve a failed request to a decision, or return the error when it
+    /// is a timeout and the engine is configured to return timeouts.
+    fn failure(&self, error: Error, namespace: Option<&str>) -> Result<PolicyDecision> {
+        if matches!(error, Error::Timeout { .. })
+            && self.config.evaluation.on_timeout == TimeoutBehavior::Error
+        {
+            if let Some(ref telemetry) = self.telemetry {
+                telemetry.record_error(error.category());
+            }
+            return Err(error);
+        }
+        Ok(self.fail(error, namespace))
+    }
+
+    /// Replace a not-applicable decision with the namespace's default.
+    fn apply_default(&self, mut decision: PolicyDecision, namespace: Option<&str>) -> PolicyDecision {
+        let default = self.config.evaluation.default_decision_for(namespace);
+        if decision.decision != DecisionType::NotApplicable || default == DecisionType::NotApplicable {
+            return decision;
+        }
+
+        let fallback = PolicyDecision::from_decision_type(default, "No policy applied to the request");
+        decision.decision = fallback.decision;
+        decision.allowed = fallback.allowed;
+        decision.reason = fallback.reason;
+        decision
+            .metadata
+            .insert("default_decision".to_string(), serde_json::json!(true));
+        decision
+    }
+
+    /// Resolve an evaluation error according to the namespace's failure mode.
+    fn fail(&self, error: Error, namespace: Option<&str>) -> PolicyDecision {
+        if let Some(ref telemetry) = self.telemetry {
+            telemetry.record_error(error.category());
+        }
+        warn!(error = %error, "Policy evaluation failed");
+
+        let mut decision = if self.config.evaluation.fail_open_for(namespace) {
+            PolicyDecision::warn(format!("Policy evaluation failed; failing open: {}", error))
+        } else {
+            PolicyDecision::deny(format!("Policy evaluation failed; failing closed: {}", error))
+        };
+        if let Error::Timeout {
+            policy_id: Some(ref policy_id),
+            ..
+        } = error
+        {
+            decision
+                .metadata
+                .insert("timed_out_policy".to_string(), serde_json::json!(policy_id));
+        }
+        decision.with_metadata("evaluation_error", serde_json::json!(error.to_string()))
+    }
+
     /// Validate a policy document without loading it.
     ///
+    /// Every policy is also compiled, so invalid regexes and operator/value
+    /// type mismatches are reported here.
+    ///
     /// # Arguments
     /// * `document` - The policy document to validate
     ///
@@ -119,7 +409,11 @@ impl PolicyEngine {
     /// * `Ok(())` - If the document is valid
     /// * `Err(Error)` - If validation fails
     pub fn validate_document(&self, document: &PolicyDocument) -> Result<()> {
-        document.validate()
+        document.validate()?;
+        for policy in &document.policies {
+            CompiledPolicy::compile(policy.clone())?;
+        }
+        Ok(())
     }
 
     /// Validate a policy.
@@ -131,7 +425,7 @@ impl PolicyEngine {
     /// * `Ok(())` - If the policy is valid
     /// * `Err(Error)` - If validation fails
     pub fn validate_policy(&self, policy: &Policy) -> Result<()> {
-        policy.validate()
+        compile(policy.clone()).map(|_| ())
     }
 
     /// Load a policy document from a file.
@@ -177,18 +471,13 @@ impl PolicyEngine {
     async fn load_document(&self, document: PolicyDocument) -> Result<Vec<String>> {
         document.validate()?;
 
-        let mut policies = self.policies.write();
-        let mut loaded_ids = Vec::new();
-
-        for policy in document.policies {
-            loaded_ids.push(policy.id.clone());
-            policies.insert(policy.id.clone(), policy);
-        }
-
-        // Clear cache when policies change
-        if let Some(ref cache) = self.cache {
-            cache.clear();
-        }
+        let loaded_ids = document.policies.iter().map(|p| p.id.clone()).collect();
+        let compiled = document
+            .policies
+            .into_iter()
+            .map(compile)
+            .collect::<Result<Vec<_>>>()?;
+        self.update(|current, generation| Ok(current.with_policies(generation, compiled)))?;
 
         Ok(loaded_ids)
     }
@@ -202,18 +491,57 @@ impl PolicyEngine {
     /// * `Ok(String)` - The ID of the loaded policy
     /// * `Err(Error)` - If loading fails
     pub async fn load_policy(&self, policy: Policy) -> Result<String> {
-        policy.validate()?;
-
         let id = policy.id.clone();
-        let mut policies = self.policies.write();
-        policies.insert(id.clone(), policy);
+        let compiled = compile(policy)?;
+        self.update(|current, generation| Ok(current.with_policies(generation, [compiled])))?;
 
-        // Clear cache when policies change
-        if let Some(ref cache) = self.cache {
-            cache.clear();
+        Ok(id)
+    }
+
+    /// Load a single policy unless a policy with its ID is already loaded.
+    ///
+    /// The check and the load are one step, so of two concurrent loads of
+    /// the same ID exactly one succeeds.
+    ///
+    /// # Returns
+    /// * `Ok(true)` - If the policy was loaded
+    /// * `Ok(false)` - If a policy with the same ID was already loaded
+    /// * `Err(Error)` - If the policy is invalid
+    pub async fn load_new_policy(&self, policy: Policy) -> Result<bool> {
+        let compiled = compile(policy)?;
+        self.try_update(|current, generation| {
+            Ok((!current.contains(&compiled.id))
+                .then(|| current.with_policies(generation, [compiled])))
+        })
+    }
+
+    /// Atomically replace every loaded policy with the given set.
+    ///
+    /// The whole set is validated before anything is swapped in. Evaluations
+    /// already running keep the policies they started with.
+    ///
+    /// # Arguments
+    /// * `policies` - The complete new policy set
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - The number of policies now loaded
+    /// * `Err(Error)` - If any policy is invalid or IDs collide
+    pub async fn replace_policies(&self, policies: Vec<Policy>) -> Result<usize> {
+        let mut replacement = HashMap::with_capacity(policies.len());
+        for policy in policies {
+            if replacement.contains_key(&policy.id) {
+                return Err(crate::Error::validation(format!(
+                    "Duplicate policy ID: {}",
+                    policy.id
+                )));
+            }
+            replacement.insert(policy.id.clone(), Arc::new(compile(policy)?));
         }
 
-        Ok(id)
+        let count = replacement.len();
+        self.update(|_, generation| Ok(PolicySet::new(generation, replacement.into_values())))?;
+
+        Ok(count)
     }
 
     /// Unload a policy by ID.
@@ -225,50 +553,76 @@ impl PolicyEngine {
     /// * `Ok(())` - If the policy was unloaded
     /// * `Err(Error)` - If the policy was not found
     pub async fn unload_policy(&self, policy_id: &str) -> Result<()> {
-        let mut policies = self.policies.write();
-        if policies.remove(policy_id).is_none() {
-            return Err(crate::Error::validation(format!(
-                "Policy not found: {}",
-                policy_id
-            )));
-        }
+        self.update(|current, generation| {
+            current.without_policy(generation, policy_id).ok_or_else(|| {
+                crate::Error::validation(format!("Policy not found: {}", policy_id))
+            })
+        })
+    }
+
+    /// Publish the next policy snapshot built from the current one.
+    fn update(
+        &self,
+        next: impl FnOnce(&PolicySet, u64) -> Result<PolicySet>,
+    ) -> Result<()> {
+        self.try_update(|current, generation| next(current, generation).map(Some))
+            .map(|_| ())
+    }
+
+    /// Publish the next policy snapshot built from the current one, unless
+    /// `next` leaves the current snapshot in place by returning `None`.
+    ///
+    /// Returns whether a snapshot was published.
+    fn try_update(
+        &self,
+        next: impl FnOnce(&PolicySet, u64) -> Result<Option<PolicySet>>,
+    ) -> Result<bool> {
+        let _guard = self.update_lock.lock();
+        let current = self.policies.load();
+        let Some(updated) = next(&current, current.generation() + 1)? else {
+            return Ok(false);
+        };
+        self.policies.store(Arc::new(updated));
 
         // Clear cache when policies change
         if let Some(ref cache) = self.cache {
             cache.clear();
         }
 
-        Ok(())
+        Ok(true)
     }
 
     /// Get a policy by ID.
     pub fn get_policy(&self, policy_id: &str) -> Option<Policy> {
-        self.policies.read().get(policy_id).cloned()
+        self.policies.load().get(policy_id).map(|p| Policy::clone(p.policy()))
     }
 
     /// List all loaded policy IDs.
     pub fn list_policies(&self) -> Vec<String> {
-        self.policies.read().keys().cloned().collect()
+        self.policies.load().ids().map(String::from).collect()
     }
 
     /// Get the number of loaded policies.
     pub fn policy_count(&self) -> usize {
-        self.policies.read().len()
+        self.policies.load().len()
+    }
+
+    /// Get the current policy snapshot.
+    pub fn policy_set(&self) -> Arc<PolicySet> {
+        self.policies.load_full()
     }
 
-    /// Get enabled policies sorted by priority.
-    fn get_enabled_policies(&self) -> Vec<Policy> {
-        let policies = self.policies.read();
-        let mut enabled: Vec<_> = policies.values().filter(|p| p.enabled).cloned().collect();
-        enabled.sort_by(|a, b| b.priority.cmp(&a.priority));
-        enabled
+    /// Get the generation of the current policy snapshot.
+    pub fn policy_generation(&self) -> u64 {
+        self.policies.load().generation()
     }
 
-    /// Clear the decision cache.
+    /// Clear the decision cache and cached context attributes.
     pub fn clear_cache(&self) {
         if let Some(ref cache) = self.cache {
             cache.clear();
         }
+        self.resolvers.clear();
     }
 
     /// Get cache statistics.
@@ -276,16 +630,96 @@ impl PolicyEngine {
         self.cache.as_ref().map(|c| c.stats())
     }
 
+    /// Get the telemetry instance, if enabled.
+    pub fn telemetry(&self) -> Option<&Telemetry> {
+        self.telemetry.as_ref()
+    }
+
+    /// Get the engine configuration.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
     /// Get engine metrics.
     pub fn metrics(&self) -> EngineMetrics {
         EngineMetrics {
             policy_count: self.policy_count(),
+            policy_generation: self.policy_generation(),
             cache_enabled: self.cache.is_some(),
             cache_stats: self.cache_stats(),
         }
     }
 }
 
+/// Validate and compile a policy for loading.
+fn compile(policy: Policy) -> Result<CompiledPolicy> {
+    policy.validate()?;
+    CompiledPolicy::compile(policy)
+}
+
+/// A timeout for a request step that did not finish within its budget.
+fn timed_out(step: &str, deadline: Deadline) -> Error {
+    let limit = deadline.limit().as_millis();
+    Error::timeout(format!("{} within {}ms", step, limit), limit as u64)
+}
+
+/// Per-request options for [`PolicyEngine::evaluate_with_options`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EvaluationOptions {
+    /// Only evaluate these policies (all enabled policies when empty)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policy_ids: Vec<String>,
+    /// Include an evaluation trace in the decision
+    #[serde(default)]
+    pub trace: bool,
+    /// Evaluate without caching the decision or recording metrics
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Only evaluate policies in this namespace and use its defaults
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Only evaluate policies that run in this phase
+    #[serde(default)]
+    pub phase: PolicyPhase,
+}
+
+impl EvaluationOptions {
+    /// Create default options (all policies, no trace, not a dry run).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restrict evaluation to the given policy IDs.
+    pub fn with_policy_ids(mut self, policy_ids: Vec<String>) -> Self {
+        self.policy_ids = policy_ids;
+        self
+    }
+
+    /// Enable or disable tracing.
+    pub fn with_trace(mut self, trace: bool) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    /// Set the evaluation phase.
+    pub fn with_phase(mut self, phase: PolicyPhase) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    /// Enable or disable dry-run mode.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Restrict evaluation to a policy namespace.
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+}
+
 /// Builder for creating a PolicyEngine.
 #[derive(Debug, Default)]
 pub struct PolicyEngineBuilder {
@@ -295,6 +729,13 @@ pub struct PolicyEngineBuilder {
     telemetry_enabled: bool,
     cache_enabled: Option<bool>,
     cache_size: Option<usize>,
+    combining_algorithm: Option<CombiningAlgorithm>,
+    default_decision: Option<DecisionType>,
+    fail_open: Option<bool>,
+    fulfiller: Fulfiller,
+    resolvers: Resolvers,
+    model_catalog: Option<ModelCatalog>,
+    clock: Option<Arc<dyn Clock>>,
 }
 
 impl PolicyEngineBuilder {
@@ -339,8 +780,63 @@ impl PolicyEngineBuilder {
         self
     }
 
+    /// Set how the results of different policies are combined.
+    pub fn with_combining_algorithm(mut self, algorithm: CombiningAlgorithm) -> Self {
+        self.combining_algorithm = Some(algorithm);
+        self
+    }
+
+    /// Set the decision returned when no policy applies.
+    pub fn with_default_decision(mut self, decision: DecisionType) -> Self {
+        self.default_decision = Some(decision);
+        self
+    }
+
+    /// Allow requests when evaluation fails instead of denying them.
+    pub fn with_fail_open(mut self, fail_open: bool) -> Self {
+        self.fail_open = Some(fail_open);
+        self
+    }
+
+    /// Register a handler for obligations the engine cannot fulfil itself.
+    pub fn with_obligation_handler(mut self, handler: Arc<dyn ObligationHandler>) -> Self {
+        self.fulfiller = self.fulfiller.with_handler(handler);
+        self
+    }
+
+    /// Limit the time an obligation handler gets to fulfil an obligation;
+    /// the default is five seconds.
+    pub fn with_obligation_timeout(mut self, timeout: Duration) -> Self {
+        self.fulfiller = self.fulfiller.with_timeout(timeout);
+        self
+    }
+
+    /// Register a resolver for a namespace of external context attributes.
+    pub fn with_context_resolver(mut self, resolver: Arc<dyn ContextResolver>) -> Self {
+        self.resolvers = self.resolvers.with_resolver(resolver);
+        self
+    }
+
+    /// Add models and prices to the built-in model catalog.
+    pub fn with_model_catalog(mut self, catalog: ModelCatalog) -> Self {
+        self.model_catalog = Some(catalog);
+        self
+    }
+
+    /// Read the current time for time conditions from the given clock
+    /// instead of the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
     /// Build the policy engine.
+    ///
+    /// Fails when two resolvers claim the same namespace, a resolver
+    /// claims a built-in context section, or the model catalog is invalid.
     pub async fn build(self) -> Result<PolicyEngine> {
+        self.resolvers.validate()?;
+
         let mut config = self.config.unwrap_or_default();
 
         // Apply builder overrides
@@ -350,8 +846,29 @@ impl PolicyEngineBuilder {
         if let Some(size) = self.cache_size {
             config.cache.l1_max_entries = size;
         }
+        if let Some(algorithm) = self.combining_algorithm {
+            config.evaluation.combining_algorithm = algorithm;
+        }
+        if let Some(decision) = self.default_decision {
+            config.evaluation.default_decision = decision;
+        }
+        if let Some(fail_open) = self.fail_open {
+            config.evaluation.fail_open = fail_open;
+        }
 
         let mut engine = PolicyEngine::new(config);
+        engine.fulfiller = self.fulfiller;
+        engine.resolvers = self.resolvers;
+        if let Some(path) = &engine.config.evaluation.model_catalog {
+            engine.catalog.extend(ModelCatalog::from_file(path)?);
+        }
+        if let Some(catalog) = self.model_catalog {
+            engine.catalog.extend(catalog);
+        }
+        engine.catalog.validate()?;
+        if let Some(clock) = self.clock {
+            engine.evaluator = engine.evaluator.with_clock(clock);
+        }
 
         // Enable telemetry if requested
         if self.telemetry_enabled {
@@ -392,6 +909,9 @@ use serde::{Deserialize, Serialize};
 pub struct EngineMetrics {
     /// Number of loaded policies
     pub policy_count: usize,
+    /// Generation of the current policy snapshot
+    #[serde(default)]
+    pub policy_generation: u64,
     /// Whether caching is enabled
     pub cache_enabled: bool,
     /// Cache statistics (if caching is enabled)
@@ -401,7 +921,11 @@ pub struct EngineMetrics {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::policy::{Action, Condition, PolicyRule};
+    use crate::api::ObligationStatus;
+    use crate::core::FixedClock;
+    use crate::policy::{Action, Condition, LogLevel, Modification, Obligation, PolicyRule};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
 
     fn sample_policy() -> Policy {
         Policy::builder("test-policy")
@@ -439,6 +963,52 @@ mod tests {
         assert!(engine.get_policy("test-policy").is_some());
     }
 
+    #[tokio::test]
+    async fn test_load_new_policy() {
+        let engine = Arc::new(PolicyEngine::builder().build().await.unwrap());
+
+        let loads = (0..8).map(|_| {
+            let engine = Arc::clone(&engine);
+            tokio::spawn(async move { engine.load_new_policy(sample_policy()).await.unwrap() })
+        });
+        let mut created = 0;
+        for load in loads {
+            if load.await.unwrap() {
+                created += 1;
+            }
+        }
+
+        assert_eq!(created, 1);
+        assert_eq!(engine.policy_count(), 1);
+        assert_eq!(engine.policy_generation(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_policy_generation() {
+        let engine = PolicyEngine::builder().build().await.unwrap();
+        assert_eq!(engine.policy_generation(), 0);
+
+        let before = engine.policy_set();
+        engine.load_policy(sample_policy()).await.unwrap();
+        assert_eq!(engine.policy_generation(), 1);
+
+        // Snapshots taken earlier are unaffected by later changes
+        assert!(before.is_empty());
+
+        let context = EvaluationContext::builder().with_user_id("u").build();
+        let decision = engine.evaluate(&context).await.unwrap();
+        assert_eq!(decision.policy_generation, 1);
+
+        assert!(engine.unload_policy("missing").await.is_err());
+        assert_eq!(engine.policy_generation(), 1);
+
+        engine.replace_policies(Vec::new()).await.unwrap();
+        assert_eq!(engine.policy_generation(), 2);
+        let decision = engine.evaluate(&context).await.unwrap();
+        assert!(!decision.cached);
+        assert_eq!(decision.policy_generation, 2);
+    }
+
     #[tokio::test]
     async fn test_unload_policy() {
         let engine = PolicyEngine::builder()
@@ -471,4 +1041,589 @@ mod tests {
         // Should allow because user is admin, not guest
         assert!(decision.allowed);
     }
+
+    #[tokio::test]
+    async fn test_default_decision_and_failure_modes() {
+        let mut config = Config::default();
+        config.evaluation.default_decision = DecisionType::Deny;
+        config.evaluation.namespaces.insert(
+            "sandbox".to_string(),
+            crate::config::NamespaceConfig {
+                default_decision: Some(DecisionType::Allow),
+                fail_open: Some(true),
+            },
+        );
+        let broken = Policy::builder("broken")
+            .namespace("sandbox")
+            .rule(PolicyRule::new(
+                "compare",
+                "Compare a string to a number",
+                Condition::expression("llm.model > 5"),
+                Action::deny("unreachable"),
+            ))
+            .build();
+        let engine = PolicyEngine::builder()
+            .with_config(config)
+            .with_policy(sample_policy())
+            .build()
+            .await
+            .unwrap();
+
+        let context = EvaluationContext::builder().with_model("gpt-4").build();
+        let decision = engine.evaluate(&context).await.unwrap();
+        assert_eq!(decision.decision, DecisionType::Deny);
+        assert_eq!(decision.metadata["default_decision"], true);
+
+        let sandbox = EvaluationOptions::new().with_namespace("sandbox");
+        let decision = engine.evaluate_with_options(&context, &sandbox).await.unwrap();
+        assert_eq!(decision.decision, DecisionType::Allow);
+
+        engine.load_policy(broken).await.unwrap();
+        let decision = engine.evaluate_with_options(&context, &sandbox).await.unwrap();
+        assert_eq!(decision.decision, DecisionType::Warn);
+        assert!(decision.metadata.contains_key("evaluation_error"));
+
+        let decision = engine.evaluate(&context).await.unwrap();
+        assert_eq!(decision.decision, DecisionType::Deny);
+        assert!(decision.reason.unwrap().contains("failing closed"));
+    }
+
+    #[tokio::test]
+    async fn test_evaluation_deadline() {
+        let mut config = Config::default();
+        config.cache.enabled = false;
+        config.performance.max_evaluation_time_ms = 0;
+        let engine = PolicyEngine::builder()
+            .with_config(config.clone())
+            .with_policy(sample_policy())
+            .build()
+            .await
+            .unwrap();
+
+        let context = EvaluationContext::builder().with_user_id("u").build();
+        let decision = engine.evaluate(&context).await.unwrap();
+        assert_eq!(decision.decision, DecisionType::Deny);
+        assert_eq!(decision.metadata["timed_out_policy"], "test-policy");
+
+        config.evaluation.on_timeout = TimeoutBehavior::Error;
+        let engine = PolicyEngine::builder()
+            .with_config(config)
+            .with_policy(sample_policy())
+            .build()
+            .await
+            .unwrap();
+        match engine.evaluate(&context).await {
+            Err(Error::Timeout { policy_id, .. }) => {
+                assert_eq!(policy_id.as_deref(), Some("test-policy"))
+            }
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_parallel_evaluations() {
+        let mut builder = PolicyEngine::builder().with_cache_enabled(false);
+        for index in 0..16 {
+            builder = builder.with_policy(
+                Policy::builder(format!("block-{}", index))
+                    .rule(PolicyRule::new(
+                        "user",
+                        "Blocked user",
+                        Condition::equals("user.id", format!("u{}", index)),
+                        Action::deny("Blocked"),
+                    ))
+                    .build(),
+            );
+        }
+        let mut engine = builder.build().await.unwrap();
+        // Use the shared pool however many cores the test machine has
+        engine.evaluator = engine.evaluator.with_parallelism(4);
+        let engine = Arc::new(engine);
+
+        let evaluations = (0..256).map(|request| {
+            let engine = engine.clone();
+            tokio::spawn(async move {
+                let user = format!("u{}", request % 32);
+                let context = EvaluationContext::builder().with_user_id(&user).build();
+                (request % 32 < 16, engine.evaluate(&context).await.unwrap())
+            })
+        });
+        for evaluation in futures::future::join_all(evaluations).await {
+            let (blocked, decision) = evaluation.unwrap();
+            assert_eq!(decision.decision == DecisionType::Deny, blocked);
+        }
+    }
+
+    struct Pager;
+
+    #[async_trait::async_trait]
+    impl ObligationHandler for Pager {
+        fn handles(&self, obligation: &Obligation) -> bool {
+            matches!(obligation, Obligation::Notify { channel, .. } if channel == "pager")
+        }
+
+        async fn fulfil(&self, _: &Obligation, _: &EvaluationContext) -> Result<()> {
+            Err(Error::integration("pager", "unreachable"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_obligations() {
+        let policy = Policy::builder("quota")
+            .rule(
+                PolicyRule::new(
+                    "per-user",
+                    "Two requests per user",
+                    Condition::equals("llm.model", "gpt-4"),
+                    Action::rate_limit(2, 60)
+                        .with_metadata("key", serde_json::json!("user.id"))
+                        .mandatory(),
+                )
+                .with_action(Action::log(LogLevel::Warning))
+                .with_action(Action::notify("security")),
+            )
+            .rule(
+                PolicyRule::new(
+                    "page",
+                    "Page on o1",
+                    Condition::equals("llm.model", "o1"),
+                    Action::notify("pager"),
+                )
+                .with_action(Action::notify("security").mandatory()),
+            )
+            .build();
+        let engine = PolicyEngine::builder()
+            .with_policy(policy)
+            .with_obligation_handler(Arc::new(Pager))
+            .build()
+            .await
+            .unwrap();
+
+        let context = EvaluationContext::builder()
+            .with_model("gpt-4")
+            .with_user_id("alice")
+            .build();
+        for _ in 0..2 {
+            let decision = engine.evaluate(&context).await.unwrap();
+            assert!(decision.allowed);
+            let statuses: Vec<_> = decision.obligations.iter().map(|o| o.status).collect();
+            assert_eq!(
+                statuses,
+                vec![
+                    ObligationStatus::Fulfilled,
+                    ObligationStatus::Fulfilled,
+                    ObligationStatus::Pending,
+                ]
+            );
+        }
+
+        // The cached decision still counts against the limit
+        let decision = engine.evaluate(&context).await.unwrap();
+        assert!(decision.cached);
+        assert_eq!(decision.decision, DecisionType::Deny);
+        assert_eq!(decision.metadata["rate_limited"], "quota");
+
+        let bob = EvaluationContext::builder()
+            .with_model("gpt-4")
+            .with_user_id("bob")
+            .build();
+        assert!(engine.evaluate(&bob).await.unwrap().allowed);
+
+        let anonymous = EvaluationContext::builder().with_model("gpt-4").build();
+        let decision = engine.evaluate(&anonymous).await.unwrap();
+        assert_eq!(decision.decision, DecisionType::Deny);
+        assert_eq!(decision.obligations[0].status, ObligationStatus::Failed);
+        assert!(decision.reason.unwrap().contains("Mandatory obligation"));
+
+        // A failed optional obligation is reported without denying, and
+        // mandatory obligations left to the caller stay pending
+        let paged = EvaluationContext::builder().with_model("o1").build();
+        let decision = engine.evaluate(&paged).await.unwrap();
+        assert!(decision.allowed);
+        assert_eq!(decision.obligations[0].status, ObligationStatus::Failed);
+        assert!(decision.obligations[1].mandatory && decision.obligations[1].is_pending());
+    }
+
+    struct Stuck;
+
+    #[async_trait::async_trait]
+    impl ObligationHandler for Stuck {
+        fn handles(&self, _: &Obligation) -> bool {
+            true
+        }
+
+        async fn fulfil(&self, _: &Obligation, _: &EvaluationContext) -> Result<()> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_obligation_handler_timeout() {
+        let policy = Policy::builder("audit")
+            .rule(PolicyRule::new(
+                "audit",
+                "Audit every request",
+                Condition::exists("llm.model"),
+                Action::notify("audit").mandatory(),
+            ))
+            .build();
+        let engine = PolicyEngine::builder()
+            .with_policy(policy)
+            .with_obligation_handler(Arc::new(Stuck))
+            .with_obligation_timeout(Duration::from_millis(20))
+            .build()
+            .await
+            .unwrap();
+
+        let context = EvaluationContext::builder().with_model("gpt-4").build();
+        let decision = engine.evaluate(&context).await.unwrap();
+        assert_eq!(decision.decision, DecisionType::Deny);
+        assert_eq!(decision.obligations[0].status, ObligationStatus::Failed);
+        assert!(decision.obligations[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("did not finish"));
+
+        // Fulfilment is part of the request's time budget
+        let mut config = Config::default();
+        config.performance.max_evaluation_time_ms = 50;
+        config.evaluation.on_timeout = TimeoutBehavior::Error;
+        let engine = PolicyEngine::builder()
+            .with_config(config)
+            .with_policy(engine.get_policy("audit").unwrap())
+            .with_obligation_handler(Arc::new(Stuck))
+            .build()
+            .await
+            .unwrap();
+        match engine.evaluate(&context).await {
+            Err(Error::Timeout { message, .. }) => {
+                assert!(message.contains("Obligations were not fulfilled"))
+            }
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deadline_spans_resolution_and_evaluation() {
+        let policy = Policy::builder("budget")
+            .rule(PolicyRule::new(
+                "exhausted",
+                "Budget exhausted",
+                Condition::less_than("budget.remaining", 10),
+                Action::deny("Budget exhausted"),
+            ))
+            .build();
+        let mut config = Config::default();
+        config.evaluation.on_timeout = TimeoutBehavior::Error;
+        let engine = PolicyEngine::builder()
+            .with_config(config)
+            .with_policy(policy)
+            .with_context_resolver(Attributes::new("budget", Duration::ZERO))
+            .build()
+            .await
+            .unwrap();
+
+        // Resolution succeeds but takes longer than the whole budget, which
+        // leaves no time to evaluate the policy
+        let context = EvaluationContext::builder().with_user_id("slow").build();
+        match engine.evaluate(&context).await {
+            Err(Error::Timeout { duration_ms, .. }) => assert_eq!(duration_ms, 100),
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+
+        let context = EvaluationContext::builder().with_user_id("alice").build();
+        assert_eq!(
+            engine.evaluate(&context).await.unwrap().decision,
+            DecisionType::Deny
+        );
+    }
+
+    struct Attributes {
+        namespace: &'static str,
+        ttl: Duration,
+        calls: AtomicUsize,
+    }
+
+    impl Attributes {
+        fn new(namespace: &'static str, ttl: Duration) -> Arc<Self> {
+            Arc::new(Self {
+                namespace,
+                ttl,
+                calls: AtomicUsize::new(0),
+            })
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ContextResolver for Attributes {
+        fn namespace(&self) -> &str {
+            self.namespace
+        }
+
+        fn ttl(&self) -> Duration {
+            self.ttl
+        }
+
+        async fn resolve(&self, context: &EvaluationContext) -> Result<serde_json::Value> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            match context.user.as_ref().map(|u| u.id.as_str()) {
+                Some("alice") => Ok(serde_json::json!({"remaining": 5, "department": "finance"})),
+                Some("mallory") => Err(Error::evaluation("user store unavailable")),
+                Some("hanging") => std::future::pending().await,
+                Some("slow") => {
+                    // Block instead of awaiting, so the resolver finishes on
+                    // its first poll and no timeout can preempt it
+                    std::thread::sleep(Duration::from_millis(150));
+                    Ok(serde_json::json!({"remaining": 50}))
+                }
+                _ => Ok(serde_json::json!({"remaining": 50, "department": "research"})),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_context_resolvers() {
+        let budget = Attributes::new("budget", Duration::from_secs(60));
+        let directory = Attributes::new("directory", Duration::ZERO);
+        let billing = Attributes::new("billing", Duration::ZERO);
+        let policy = Policy::builder("budget")
+            .rule(PolicyRule::new(
+                "exhausted",
+                "Budget exhausted",
+                Condition::less_than("budget.remaining", 10),
+                Action::deny("Budget exhausted"),
+            ))
+            .rule(PolicyRule::new(
+                "finance",
+                "Finance only",
+                Condition::expression("directory.department == 'finance'"),
+                Action::warn("Finance request"),
+            ))
+            .build();
+        let engine = PolicyEngine::builder()
+            .with_policy(policy)
+            .with_context_resolver(budget.clone())
+            .with_context_resolver(directory.clone())
+            .with_context_resolver(billing.clone())
+            .build()
+            .await
+            .unwrap();
+
+        let context = |user: &str| EvaluationContext::builder().with_user_id(user).build();
+        for _ in 0..2 {
+            let decision = engine.evaluate(&context("alice")).await.unwrap();
+            assert_eq!(decision.decision, DecisionType::Deny);
+        }
+        let decision = engine.evaluate(&context("bob")).await.unwrap();
+        assert!(decision.allowed);
+
+        // Budgets are cached per user, directory entries fetched on every
+        // request, and unreferenced namespaces never resolved
+        assert_eq!(budget.calls(), 2);
+        assert_eq!(directory.calls(), 3);
+        assert_eq!(billing.calls(), 0);
+
+        // Anonymous requests are never served another caller's attributes
+        let anonymous = EvaluationContext::default();
+        engine.evaluate(&anonymous).await.unwrap();
+        engine.evaluate(&anonymous).await.unwrap();
+        assert_eq!(budget.calls(), 4);
+
+        // Attributes the request already carries are not fetched again
+        let supplied = EvaluationContext::builder()
+            .with_user_id("carol")
+            .with_attributes("budget", serde_json::json!({"remaining": 1}))
+            .with_attributes("directory", serde_json::json!({}))
+            .build();
+        let decision = engine.evaluate(&supplied).await.unwrap();
+        assert_eq!(decision.decision, DecisionType::Deny);
+        assert_eq!((budget.calls(), directory.calls()), (4, 5));
+
+        // A failing resolver fails closed
+        let decision = engine.evaluate(&context("mallory")).await.unwrap();
+        assert_eq!(decision.decision, DecisionType::Deny);
+        assert!(decision.metadata["evaluation_error"]
+            .as_str()
+            .unwrap()
+            .contains("user store unavailable"));
+
+        // A resolver that never answers runs out of the evaluation budget
+        let decision = engine.evaluate(&context("hanging")).await.unwrap();
+        assert_eq!(decision.decision, DecisionType::Deny);
+        assert!(decision.metadata["evaluation_error"]
+            .as_str()
+            .unwrap()
+            .contains("not resolved within"));
+
+        for namespace in ["user", "budget"] {
+            let result = PolicyEngine::builder()
+                .with_context_resolver(budget.clone())
+                .with_context_resolver(Attributes::new(namespace, Duration::ZERO))
+                .build()
+                .await;
+            assert!(matches!(result, Err(Error::Config { .. })));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_response_phase() {
+        let model = Policy::builder("model")
+            .rule(PolicyRule::new(
+                "gpt-4",
+                "Warn on GPT-4",
+                Condition::equals("llm.model", "gpt-4"),
+                Action::warn("Expensive model"),
+            ))
+            .build();
+        let redact = Policy::builder("redact")
+            .phase(PolicyPhase::Response)
+            .rule(PolicyRule::new(
+                "keys",
+                "Redact API keys",
+                Condition::matches("response.content", r"sk-[a-z0-9]+"),
+                Action::modify(vec![Modification::mask_pattern(
+                    "response.content",
+                    r"sk-[a-z0-9]+",
+                )]),
+            ))
+            .build();
+        let length = Policy::builder("length")
+            .phase(PolicyPhase::Response)
+            .rule(PolicyRule::new(
+                "truncated",
+                "Truncated output",
+                Condition::equals("response.finish_reason", "length"),
+                Action::deny("Response was truncated"),
+            ))
+            .build();
+        let engine = PolicyEngine::builder()
+            .with_policy(model)
+            .with_policy(redact)
+            .with_policy(length)
+            .build()
+            .await
+            .unwrap();
+
+        // Request-phase policies do not run on responses and vice versa
+        let context = EvaluationContext::builder().with_model("gpt-4").build();
+        let decision = engine.evaluate(&context).await.unwrap();
+        assert_eq!(decision.matched_policies, vec!["model"]);
+
+        let response = ResponseContext::new("Your key is sk-abc123").with_finish_reason("stop");
+        let decision = engine.evaluate_response(&context, &response).await.unwrap();
+        assert_eq!(decision.decision, DecisionType::Modify);
+        assert_eq!(decision.matched_policies, vec!["redact"]);
+        let redacted = decision.apply_response(&response).unwrap();
+        assert!(!redacted.content.unwrap().contains("sk-abc123"));
+        assert_eq!(redacted.finish_reason.as_deref(), Some("stop"));
+
+        let response = ResponseContext::new("Once upon a").with_finish_reason("length");
+        let decision = engine.evaluate_response(&context, &response).await.unwrap();
+        assert_eq!(decision.decision, DecisionType::Deny);
+
+        // Request-phase policies cannot read the response
+        let invalid = Policy::builder("invalid")
+            .rule(PolicyRule::new(
+                "content",
+                "Reads response",
+                Condition::contains("response.content", "secret"),
+                Action::deny("Secret"),
+            ))
+            .build();
+        assert!(engine.load_policy(invalid).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cost_ceiling() {
+        let policy = Policy::builder("budget")
+            .rule(PolicyRule::new(
+                "ceiling",
+                "Cost ceiling",
+                Condition::greater_than("llm.estimated_cost_usd", 1.0),
+                Action::deny("Request may cost more than $1"),
+            ))
+            .rule(PolicyRule::new(
+                "small-window",
+                "Small context window",
+                Condition::less_than("model.context_window", 1000),
+                Action::warn("Model has a small context window"),
+            ))
+            .build();
+        let engine = PolicyEngine::builder()
+            .with_policy(policy)
+            .with_model_catalog(
+                ModelCatalog::from_yaml(
+                    "models:\n  premium:\n    context_window: 100\n    input_cost_per_million: 50000\n",
+                )
+                .unwrap(),
+            )
+            .build()
+            .await
+            .unwrap();
+
+        let request = |prompt_chars: usize| {
+            EvaluationContext::builder()
+                .with_model("premium")
+                .with_prompt("x".repeat(prompt_chars))
+                .build()
+        };
+        // 25 prompt tokens at $0.05 each
+        let decision = engine.evaluate(&request(100)).await.unwrap();
+        assert_eq!(decision.decision, DecisionType::Deny);
+        let decision = engine.evaluate(&request(60)).await.unwrap();
+        assert_eq!(decision.decision, DecisionType::Warn);
+        assert_eq!(decision.matched_rules, vec!["small-window"]);
+
+        let invalid = ModelCatalog::from_yaml("models:\n  m:\n    family: none\n").unwrap();
+        let result = PolicyEngine::builder().with_model_catalog(invalid).build().await;
+        assert!(matches!(result, Err(Error::Config { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_time_conditions() {
+        let policy = Policy::builder("hours")
+            .rule(PolicyRule::new(
+                "weekend",
+                "No requests on weekends",
+                Condition::day_of_week_in(["weekend"]).with_timezone("Europe/Berlin"),
+                Action::deny("Closed on weekends"),
+            ))
+            .rule(PolicyRule::new(
+                "after-hours",
+                "Outside office hours",
+                Condition::not(Condition::hour_between(8, "18:00").with_timezone("Europe/Berlin")),
+                Action::warn("Outside office hours"),
+            ))
+            .build();
+        // Saturday noon in Berlin
+        let saturday = chrono::DateTime::parse_from_rfc3339("2025-07-05T10:00:00Z").unwrap();
+        let engine = PolicyEngine::builder()
+            .with_policy(policy)
+            .with_cache_enabled(true)
+            .with_clock(Arc::new(FixedClock(saturday.to_utc())))
+            .build()
+            .await
+            .unwrap();
+
+        // Requests without a timestamp are evaluated at the clock's time
+        // and never cached
+        let context = EvaluationContext::builder().with_model("gpt-4").build();
+        for _ in 0..2 {
+            let decision = engine.evaluate(&context).await.unwrap();
+            assert_eq!(decision.decision, DecisionType::Deny);
+            assert!(!decision.cached);
+        }
+
+        // Monday 07:00 in Berlin
+        let mut context = EvaluationContext::builder().with_request("req-1").build();
+        context.request.as_mut().unwrap().timestamp = Some(1751864400);
+        let decision = engine.evaluate(&context).await.unwrap();
+        assert_eq!(decision.decision, DecisionType::Warn);
+        assert!(engine.evaluate(&context).await.unwrap().cached);
+    }
 }
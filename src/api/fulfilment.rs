@@ -0,0 +1,237 @@
+//! Fulfilment of decision obligations.
+
+use super::{DecisionObligation, EvaluationContext, ObligationStatus, PolicyDecision};
+use crate::policy::{DecisionType, LogLevel, Obligation};
+use crate::{Error, Result};
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+/// Time a handler gets to fulfil an obligation unless configured otherwise.
+const DEFAULT_HANDLER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Fulfils obligations the engine cannot fulfil itself, such as
+/// notifications.
+#[async_trait]
+pub trait ObligationHandler: Send + Sync {
+    /// Whether the handler fulfils this obligation.
+    fn handles(&self, obligation: &Obligation) -> bool;
+
+    /// Fulfil the obligation for a request.
+    async fn fulfil(&self, obligation: &Obligation, context: &EvaluationContext) -> Result<()>;
+}
+
+/// Fulfils the obligations of decisions.
+///
+/// Registered handlers take precedence; otherwise log obligations are
+/// written to the engine log and rate limits are enforced in memory. Other
+/// obligations stay pending for the caller. A decision is denied when a
+/// mandatory obligation fails or a rate limit is exceeded; a handler that
+/// does not finish in time has failed.
+#[derive(Default)]
+pub(crate) struct Fulfiller {
+    handlers: Vec<Arc<dyn ObligationHandler>>,
+    limiter: RateLimiter,
+    timeout: Option<Duration>,
+}
+
+impl Fulfiller {
+    /// Register a handler.
+    pub(crate) fn with_handler(mut self, handler: Arc<dyn ObligationHandler>) -> Self {
+        self.handlers.push(handler);
+        self
+    }
+
+    /// Limit the time a handler gets to fulfil an obligation.
+    pub(crate) fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Fulfil the pending obligations of a decision.
+    pub(crate) async fn fulfil(&self, decision: &mut PolicyDecision, context: &EvaluationContext) {
+        let mut limited = None;
+
+        for entry in &mut decision.obligations {
+            if entry.status != ObligationStatus::Pending {
+                continue;
+            }
+
+            let handler = self.handlers.iter().find(|h| h.handles(&entry.obligation));
+            let result = match (handler, &entry.obligation) {
+                (Some(handler), obligation) => {
+                    let timeout = self.timeout.unwrap_or(DEFAULT_HANDLER_TIMEOUT);
+                    match tokio::time::timeout(timeout, handler.fulfil(obligation, context)).await
+                    {
+                        Ok(result) => result.map(|()| true),
+                        Err(_) => Err(Error::timeout(
+                            format!(
+                                "Obligation handler did not finish within {}ms",
+                                timeout.as_millis()
+                            ),
+                            timeout.as_millis() as u64,
+                        )),
+                    }
+                }
+                (None, Obligation::Log { level, message }) => {
+                    log(entry, *level, message.as_deref());
+                    Ok(true)
+                }
+                (
+                    None,
+                    Obligation::RateLimit {
+                        key,
+                        limit,
+                        window_secs,
+                    },
+                ) => self
+                    .limiter
+                    .admit(entry, key.as_deref(), *limit, *window_secs, context),
+                (None, _) => continue,
+            };
+
+            match result {
+                Ok(admitted) => {
+                    entry.status = ObligationStatus::Fulfilled;
+                    if !admitted && limited.is_none() {
+                        limited = Some(entry.policy_id.clone());
+                    }
+                }
+                Err(e) => {
+                    entry.status = ObligationStatus::Failed;
+                    entry.error = Some(e.to_string());
+                }
+            }
+        }
+
+        let failed = decision
+            .obligations
+            .iter()
+            .find(|entry| entry.mandatory && entry.status == ObligationStatus::Failed)
+            .map(|entry| {
+                (
+                    entry.policy_id.clone(),
+                    entry.error.clone().unwrap_or_default(),
+                )
+            });
+
+        if let Some((policy_id, error)) = failed {
+            warn!(policy_id = %policy_id, error = %error, "Mandatory obligation failed");
+            deny(
+                decision,
+                format!("Mandatory obligation could not be fulfilled: {}", error),
+            );
+            decision.metadata.insert(
+                "failed_obligation".to_string(),
+                serde_json::json!(policy_id),
+            );
+        } else if let Some(policy_id) = limited {
+            deny(
+                decision,
+                format!("Rate limit exceeded for policy: {}", policy_id),
+            );
+            decision
+                .metadata
+                .insert("rate_limited".to_string(), serde_json::json!(policy_id));
+        }
+    }
+}
+
+impl fmt::Debug for Fulfiller {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Fulfiller")
+            .field("handlers", &self.handlers.len())
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Turn a decision into a denial.
+fn deny(decision: &mut PolicyDecision, reason: String) {
+    decision.decision = DecisionType::Deny;
+    decision.allowed = false;
+    decision.reason = Some(reason);
+}
+
+/// Write a log obligation to the engine log.
+fn log(entry: &DecisionObligation, level: LogLevel, message: Option<&str>) {
+    let message = message.unwrap_or("Policy rule matched");
+    let (policy_id, rule_id) = (&entry.policy_id, &entry.rule_id);
+    match level {
+        LogLevel::Debug => debug!(policy_id = %policy_id, rule_id = %rule_id, "{}", message),
+        LogLevel::Info => info!(policy_id = %policy_id, rule_id = %rule_id, "{}", message),
+        LogLevel::Warning => warn!(policy_id = %policy_id, rule_id = %rule_id, "{}", message),
+        LogLevel::Error => error!(policy_id = %policy_id, rule_id = %rule_id, "{}", message),
+    }
+}
+
+/// Entries kept before expired windows are swept.
+const MAX_RATE_LIMIT_ENTRIES: usize = 10_000;
+
+/// Fixed-window request counters keyed by policy, rule and key value.
+#[derive(Debug, Default)]
+struct RateLimiter {
+    windows: Mutex<HashMap<String, Window>>,
+}
+
+#[derive(Debug)]
+struct Window {
+    started: Instant,
+    length: Duration,
+    count: u64,
+}
+
+impl RateLimiter {
+    /// Count a request, returning whether it is within the limit.
+    fn admit(
+        &self,
+        entry: &DecisionObligation,
+        key: Option<&str>,
+        limit: u64,
+        window_secs: u64,
+        context: &EvaluationContext,
+    ) -> Result<bool> {
+        let mut bucket = format!("{}/{}", entry.policy_id, entry.rule_id);
+        if let Some(key) = key {
+            let value = context.get(key).ok_or_else(|| {
+                Error::evaluation(format!(
+                    "Rate limit key '{}' is missing from the request",
+                    key
+                ))
+            })?;
+            let value = match value {
+                serde_json::Value::String(value) => value,
+                value => value.to_string(),
+            };
+            bucket.push('/');
+            bucket.push_str(&value);
+        }
+
+        let now = Instant::now();
+        let length = Duration::from_secs(window_secs);
+        let mut windows = self.windows.lock();
+        if windows.len() >= MAX_RATE_LIMIT_ENTRIES {
+            windows.retain(|_, window| now.duration_since(window.started) < window.length);
+        }
+
+        let window = windows.entry(bucket).or_insert(Window {
+            started: now,
+            length,
+            count: 0,
+        });
+        if now.duration_since(window.started) >= window.length {
+            *window = Window {
+                started: now,
+                length,
+                count: 0,
+            };
+        }
+        window.count += 1;
+        Ok(window.count <= limit)
+    }
+}
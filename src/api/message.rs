@@ -0,0 +1,156 @@
+//! Chat messages of conversational requests.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// One message of a chat conversation.
+///
+/// Content given as a list of parts, as in multimodal chat APIs, is reduced
+/// to its text parts joined by newlines.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    /// Who wrote the message
+    pub role: MessageRole,
+    /// Message text
+    #[serde(default, deserialize_with = "decode_content")]
+    pub content: String,
+    /// Name of the participant or tool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Tool call a tool message answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+/// Role of a chat message author.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageRole {
+    /// Instructions for the model
+    #[serde(alias = "developer")]
+    System,
+    /// The end user
+    User,
+    /// The model
+    Assistant,
+    /// A tool result
+    #[serde(alias = "function")]
+    Tool,
+}
+
+impl ChatMessage {
+    /// Create a message.
+    pub fn new(role: MessageRole, content: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: content.into(),
+            name: None,
+            tool_call_id: None,
+        }
+    }
+
+    /// Create a system message.
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::new(MessageRole::System, content)
+    }
+
+    /// Create a user message.
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::new(MessageRole::User, content)
+    }
+
+    /// Create an assistant message.
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::new(MessageRole::Assistant, content)
+    }
+
+    /// Create a tool result answering a tool call.
+    pub fn tool(tool_call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id.into()),
+            ..Self::new(MessageRole::Tool, content)
+        }
+    }
+
+    /// Set the participant or tool name.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+}
+
+impl MessageRole {
+    /// Get the string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageRole::System => "system",
+            MessageRole::User => "user",
+            MessageRole::Assistant => "assistant",
+            MessageRole::Tool => "tool",
+        }
+    }
+}
+
+impl fmt::Display for MessageRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for MessageRole {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(Value::String(s.to_lowercase()))
+            .map_err(|_| crate::Error::parse(format!("Unknown message role: {}", s)))
+    }
+}
+
+/// Decode content given as a string or a list of content parts.
+fn decode_content<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(text) => text,
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| match part {
+                Value::String(text) => Some(text.as_str()),
+                part => part.get("text")?.as_str(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize() {
+        let messages: Vec<ChatMessage> = serde_json::from_value(serde_json::json!([
+            {"role": "developer", "content": "Be brief"},
+            {"role": "user", "content": [
+                {"type": "text", "text": "What is this?"},
+                {"type": "image_url", "image_url": {"url": "https://example.com/a.png"}},
+            ]},
+            {"role": "tool", "tool_call_id": "call-1", "content": "42"},
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            messages,
+            vec![
+                ChatMessage::system("Be brief"),
+                ChatMessage::user("What is this?"),
+                ChatMessage::tool("call-1", "42"),
+            ]
+        );
+        assert_eq!(
+            "Assistant".parse::<MessageRole>().unwrap(),
+            MessageRole::Assistant
+        );
+        assert!("narrator".parse::<MessageRole>().is_err());
+    }
+}
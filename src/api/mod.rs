@@ -3,10 +3,35 @@
 //! This module provides the main interface for interacting with the policy engine,
 //! including the `PolicyEngine` struct and evaluation context types.
 
+mod catalog;
 mod context;
 mod decision;
 mod engine;
+mod fulfilment;
+mod message;
+mod path;
+mod resolver;
+mod response;
+mod tool;
 
-pub use context::{EvaluationContext, EvaluationContextBuilder, LlmContext, RequestContext, UserContext};
-pub use decision::PolicyDecision;
-pub use engine::{PolicyEngine, PolicyEngineBuilder};
+pub use catalog::{ModelCatalog, ModelFamily, ModelInfo};
+pub use context::{
+    EvaluationContext, EvaluationContextBuilder, LlmContext, ProjectContext, RequestContext,
+    TeamContext, UserContext,
+};
+pub use decision::{
+    DecisionObligation, EvaluationTrace, FieldModification, ModificationConflict,
+    ObligationStatus, PolicyDecision, RuleAction, TraceStep, TraceStepType,
+};
+pub use engine::{
+    CacheStats, EngineMetrics, EvaluationOptions, PolicyEngine, PolicyEngineBuilder,
+};
+pub use fulfilment::ObligationHandler;
+pub use message::{ChatMessage, MessageRole};
+pub use path::{FieldPath, PathSegment};
+pub(crate) use context::canonical_field;
+pub(crate) use path::holds;
+pub use resolver::ContextResolver;
+pub use response::{ResponseContext, TokenUsage};
+pub use tool::{ToolCall, ToolDefinition};
+pub(crate) use tool::tool_name;
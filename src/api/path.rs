@@ -0,0 +1,299 @@
+//! Field paths into an evaluation context.
+
+use serde_json::Value;
+use std::fmt;
+
+/// A parsed field path such as `llm.model`, `metadata.messages[0].role`,
+/// `metadata.messages[*].content`, `llm.messages[role=user].content` or
+/// `user.roles.#`.
+///
+/// Segments are separated by dots. `[n]` (or a numeric segment) indexes an
+/// array, `[*]` (or `*`) selects every element and `[key=value]` the
+/// elements whose `key` holds `value`. A trailing `#` takes the length of an
+/// array, string or object. A path containing a wildcard or filter resolves
+/// to the list of values it selects, and a `#` after one counts them.
+///
+/// Paths are parsed once when a policy is compiled so evaluation does not
+/// re-split the same strings on every request.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FieldPath {
+    path: String,
+    segments: Vec<PathSegment>,
+}
+
+/// One step of a [`FieldPath`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// An object key; numeric keys also index arrays
+    Key(String),
+    /// An array index
+    Index(usize),
+    /// Every element of an array
+    Wildcard,
+    /// The elements of an array whose key holds a value
+    Filter {
+        /// Key read from each element
+        key: String,
+        /// Value the key must hold
+        value: String,
+    },
+    /// The length of the value
+    Length,
+}
+
+impl PathSegment {
+    /// Get the key, if the segment is one.
+    pub fn as_key(&self) -> Option<&str> {
+        match self {
+            Self::Key(key) => Some(key),
+            _ => None,
+        }
+    }
+}
+
+impl FieldPath {
+    /// Parse a path.
+    pub fn parse(path: &str) -> crate::Result<Self> {
+        let invalid = || {
+            crate::Error::validation_field(format!("Invalid field path: '{}'", path), "field")
+        };
+
+        let mut segments = Vec::new();
+        for part in path.split('.') {
+            let (key, mut rest) = match part.find('[') {
+                Some(open) => part.split_at(open),
+                None => (part, ""),
+            };
+            // Brackets must follow a key, e.g. `a[0]` but not `[0]`
+            match key {
+                "" => return Err(invalid()),
+                "*" => segments.push(PathSegment::Wildcard),
+                "#" => segments.push(PathSegment::Length),
+                key => segments.push(PathSegment::Key(key.to_string())),
+            }
+
+            while !rest.is_empty() {
+                let close = rest.find(']').ok_or_else(invalid)?;
+                let segment = match &rest[1..close] {
+                    "*" => PathSegment::Wildcard,
+                    filter if filter.contains('=') => {
+                        let (key, value) = filter.split_once('=').ok_or_else(invalid)?;
+                        let value = value.trim();
+                        let value = value
+                            .strip_prefix('\'')
+                            .and_then(|v| v.strip_suffix('\''))
+                            .unwrap_or(value);
+                        match key.trim() {
+                            "" => return Err(invalid()),
+                            key => PathSegment::Filter {
+                                key: key.to_string(),
+                                value: value.to_string(),
+                            },
+                        }
+                    }
+                    index => PathSegment::Index(index.parse().map_err(|_| invalid())?),
+                };
+                segments.push(segment);
+                rest = &rest[close + 1..];
+                if !rest.is_empty() && !rest.starts_with('[') {
+                    return Err(invalid());
+                }
+            }
+        }
+
+        let length = segments.iter().position(|s| *s == PathSegment::Length);
+        if matches!(length, Some(position) if position + 1 != segments.len() || position == 0) {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            path: path.to_string(),
+            segments,
+        })
+    }
+
+    /// Get the original path string.
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+
+    /// Get the path segments.
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Whether the path selects several elements of some array, through a
+    /// wildcard or filter.
+    pub fn has_wildcard(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|s| matches!(s, PathSegment::Wildcard | PathSegment::Filter { .. }))
+    }
+
+    /// Select the value reached by following the segments from `skip`
+    /// onwards, starting at `value`.
+    pub(crate) fn select(&self, value: &Value, skip: usize) -> Option<Value> {
+        let segments = &self.segments[skip.min(self.segments.len())..];
+        if !self.has_wildcard() {
+            return select_one(value, segments);
+        }
+
+        let mut selected = Vec::new();
+        if let Some((PathSegment::Length, segments)) = segments.split_last() {
+            select_all(value, segments, &mut selected);
+            return Some(Value::from(selected.len()));
+        }
+        select_all(value, segments, &mut selected);
+        (!selected.is_empty()).then_some(Value::Array(selected))
+    }
+}
+
+/// Follow a path without wildcards.
+fn select_one(mut value: &Value, segments: &[PathSegment]) -> Option<Value> {
+    for segment in segments {
+        if *segment == PathSegment::Length {
+            return length(value);
+        }
+        value = child(value, segment)?;
+    }
+    Some(value.clone())
+}
+
+/// Follow a path, collecting every value a wildcard reaches.
+fn select_all(value: &Value, segments: &[PathSegment], selected: &mut Vec<Value>) {
+    match segments.split_first() {
+        None => selected.push(value.clone()),
+        Some((PathSegment::Wildcard, rest)) => {
+            if let Value::Array(items) = value {
+                for item in items {
+                    select_all(item, rest, selected);
+                }
+            }
+        }
+        Some((PathSegment::Filter { key, value: expected }, rest)) => {
+            if let Value::Array(items) = value {
+                for item in items.iter().filter(|item| holds(item, key, expected)) {
+                    select_all(item, rest, selected);
+                }
+            }
+        }
+        Some((PathSegment::Length, _)) => selected.extend(length(value)),
+        Some((segment, rest)) => {
+            if let Some(child) = child(value, segment) {
+                select_all(child, rest, selected);
+            }
+        }
+    }
+}
+
+/// Get the child of a value named by a key or index segment.
+pub(crate) fn child<'v>(value: &'v Value, segment: &PathSegment) -> Option<&'v Value> {
+    match (value, segment) {
+        (Value::Object(map), PathSegment::Key(key)) => map.get(key),
+        (Value::Array(items), PathSegment::Key(key)) => items.get(key.parse::<usize>().ok()?),
+        (Value::Array(items), PathSegment::Index(index)) => items.get(*index),
+        _ => None,
+    }
+}
+
+/// Check whether an element's key holds a filter value.
+pub(crate) fn holds(item: &Value, key: &str, expected: &str) -> bool {
+    match item.get(key) {
+        Some(Value::String(actual)) => actual == expected,
+        Some(actual) => serde_json::from_str::<Value>(expected).is_ok_and(|e| e == *actual),
+        None => false,
+    }
+}
+
+/// Get the length of an array, string or object.
+fn length(value: &Value) -> Option<Value> {
+    let length = match value {
+        Value::Array(items) => items.len(),
+        Value::String(text) => text.chars().count(),
+        Value::Object(map) => map.len(),
+        _ => return None,
+    };
+    Some(Value::from(length))
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str) -> PathSegment {
+        PathSegment::Key(key.to_string())
+    }
+
+    #[test]
+    fn test_parse() {
+        let path = FieldPath::parse("llm.model").unwrap();
+        assert_eq!(path.segments(), [key("llm"), key("model")]);
+        assert_eq!(path.to_string(), "llm.model");
+
+        let path = FieldPath::parse("metadata.messages[*].parts[0].#").unwrap();
+        assert_eq!(
+            path.segments(),
+            [
+                key("metadata"),
+                key("messages"),
+                PathSegment::Wildcard,
+                key("parts"),
+                PathSegment::Index(0),
+                PathSegment::Length,
+            ]
+        );
+        assert!(path.has_wildcard());
+
+        let path = FieldPath::parse("llm.messages[role='user'].content").unwrap();
+        assert_eq!(
+            path.segments()[2],
+            PathSegment::Filter {
+                key: "role".to_string(),
+                value: "user".to_string(),
+            }
+        );
+        assert!(path.has_wildcard());
+
+        for invalid in [
+            "", "llm..model", "llm.", "[0]", "a[x]", "a[0", "a[0]b", "#", "a.#.b", "a[=x]",
+        ] {
+            assert!(FieldPath::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let value = serde_json::json!({
+            "messages": [
+                {"role": "system", "content": "be brief"},
+                {"role": "user", "content": "hello"},
+                {"role": "user"},
+            ],
+        });
+        let select = |path: &str| FieldPath::parse(path).unwrap().select(&value, 0);
+
+        assert_eq!(select("messages[1].role"), Some("user".into()));
+        assert_eq!(select("messages.0.content"), Some("be brief".into()));
+        assert_eq!(select("messages.#"), Some(3.into()));
+        assert_eq!(select("messages[0].content.#"), Some(8.into()));
+        assert_eq!(
+            select("messages[*].content"),
+            Some(serde_json::json!(["be brief", "hello"]))
+        );
+        assert_eq!(select("messages[*].name"), None);
+        assert_eq!(
+            select("messages[role=user].content"),
+            Some(serde_json::json!(["hello"]))
+        );
+        assert_eq!(select("messages[role=user].#"), Some(2.into()));
+        assert_eq!(select("messages[role=tool].#"), Some(0.into()));
+        assert_eq!(select("messages[role=tool]"), None);
+        assert_eq!(select("messages[5]"), None);
+    }
+}
@@ -0,0 +1,196 @@
+//! Lazy resolution of external context attributes.
+
+use super::catalog::MODEL_NAMESPACE;
+use super::EvaluationContext;
+use crate::{Error, Result};
+
+use async_trait::async_trait;
+use futures::future::try_join_all;
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Context sections and derived namespaces that cannot be claimed by a
+/// resolver.
+const RESERVED_NAMESPACES: [&str; 8] = [
+    "llm", "user", "team", "project", "request", "response", "metadata", MODEL_NAMESPACE,
+];
+
+/// Entries kept before expired attributes are swept.
+const MAX_CACHED_ATTRIBUTES: usize = 10_000;
+
+/// Fetches the attributes of one namespace from an external system, such
+/// as `budget.*` from a cost service or `directory.*` from a user store.
+///
+/// The engine only calls a resolver when a loaded policy reads its
+/// namespace and the request does not already carry its attributes. The
+/// result is stored in [`EvaluationContext::attributes`] for the rest of
+/// the request and may be cached across requests for [`ttl`](Self::ttl).
+#[async_trait]
+pub trait ContextResolver: Send + Sync {
+    /// The namespace the resolver provides, e.g. `budget`.
+    fn namespace(&self) -> &str;
+
+    /// How long resolved attributes may be reused; zero disables caching.
+    fn ttl(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    /// The key resolved attributes are cached under, or `None` to skip the
+    /// cache for this request.
+    ///
+    /// Defaults to the user, team and project IDs of the request; requests
+    /// with none of them are not cached, so anonymous callers never share
+    /// attributes.
+    fn cache_key(&self, context: &EvaluationContext) -> Option<String> {
+        let ids = [
+            context.user.as_ref().map_or("", |u| u.id.as_str()),
+            context.team.as_ref().map_or("", |t| t.id.as_str()),
+            context.project.as_ref().map_or("", |p| p.id.as_str()),
+        ];
+        ids.iter()
+            .any(|id| !id.is_empty())
+            .then(|| ids.join("/"))
+    }
+
+    /// Fetch the namespace's attributes for a request.
+    async fn resolve(&self, context: &EvaluationContext) -> Result<Value>;
+}
+
+/// The registered resolvers with a cache of their results.
+#[derive(Default)]
+pub(crate) struct Resolvers {
+    resolvers: Vec<Arc<dyn ContextResolver>>,
+    cache: Mutex<HashMap<(String, String), CachedAttributes>>,
+}
+
+struct CachedAttributes {
+    expires: Instant,
+    value: Value,
+}
+
+impl Resolvers {
+    /// Register a resolver.
+    pub(crate) fn with_resolver(mut self, resolver: Arc<dyn ContextResolver>) -> Self {
+        self.resolvers.push(resolver);
+        self
+    }
+
+    /// Check that every resolver claims its own, non-reserved namespace.
+    pub(crate) fn validate(&self) -> Result<()> {
+        let mut seen = BTreeSet::new();
+        for resolver in &self.resolvers {
+            let namespace = resolver.namespace();
+            if namespace.is_empty() || namespace.contains(['.', '[']) {
+                return Err(Error::config(format!(
+                    "Invalid resolver namespace: '{}'",
+                    namespace
+                )));
+            }
+            if RESERVED_NAMESPACES.contains(&namespace) {
+                return Err(Error::config(format!(
+                    "Resolver namespace '{}' is a built-in context section",
+                    namespace
+                )));
+            }
+            if !seen.insert(namespace) {
+                return Err(Error::config(format!(
+                    "More than one resolver for namespace '{}'",
+                    namespace
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve the given namespaces that the context does not carry yet.
+    ///
+    /// Returns the context with the resolved attributes added, or `None`
+    /// when nothing needed resolving. Resolvers run concurrently; the first
+    /// failure fails the whole resolution.
+    pub(crate) async fn enrich(
+        &self,
+        context: &EvaluationContext,
+        namespaces: &BTreeSet<String>,
+    ) -> Result<Option<EvaluationContext>> {
+        let now = Instant::now();
+        let mut resolved = Vec::new();
+        let mut pending = Vec::new();
+
+        for resolver in &self.resolvers {
+            let namespace = resolver.namespace();
+            if !namespaces.contains(namespace) || context.attributes.contains_key(namespace) {
+                continue;
+            }
+
+            let key = (!resolver.ttl().is_zero())
+                .then(|| resolver.cache_key(context))
+                .flatten();
+            let cached = key.as_ref().and_then(|key| {
+                let cache = self.cache.lock();
+                let entry = cache.get(&(namespace.to_string(), key.clone()))?;
+                (entry.expires > now).then(|| entry.value.clone())
+            });
+            match cached {
+                Some(value) => resolved.push((namespace.to_string(), value)),
+                None => pending.push(self.resolve(resolver, context, key)),
+            }
+        }
+
+        if resolved.is_empty() && pending.is_empty() {
+            return Ok(None);
+        }
+        resolved.extend(try_join_all(pending).await?);
+
+        let mut enriched = context.clone();
+        enriched.attributes.extend(resolved);
+        Ok(Some(enriched))
+    }
+
+    /// Run one resolver, caching its result under `key`.
+    async fn resolve(
+        &self,
+        resolver: &Arc<dyn ContextResolver>,
+        context: &EvaluationContext,
+        key: Option<String>,
+    ) -> Result<(String, Value)> {
+        let namespace = resolver.namespace().to_string();
+        let value = resolver.resolve(context).await.map_err(|e| match e {
+            Error::Integration { .. } => e,
+            e => Error::integration(namespace.as_str(), e.to_string()),
+        })?;
+
+        if let Some(key) = key {
+            let now = Instant::now();
+            let mut cache = self.cache.lock();
+            if cache.len() >= MAX_CACHED_ATTRIBUTES {
+                cache.retain(|_, entry| entry.expires > now);
+            }
+            cache.insert(
+                (namespace.clone(), key),
+                CachedAttributes {
+                    expires: now + resolver.ttl(),
+                    value: value.clone(),
+                },
+            );
+        }
+        Ok((namespace, value))
+    }
+
+    /// Drop every cached result.
+    pub(crate) fn clear(&self) {
+        self.cache.lock().clear();
+    }
+}
+
+impl fmt::Debug for Resolvers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let namespaces: Vec<_> = self.resolvers.iter().map(|r| r.namespace()).collect();
+        f.debug_struct("Resolvers")
+            .field("namespaces", &namespaces)
+            .finish_non_exhaustive()
+    }
+}
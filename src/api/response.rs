@@ -0,0 +1,79 @@
+//! Model response context for response-phase evaluation.
+
+use super::ToolCall;
+use serde::{Deserialize, Serialize};
+
+/// What the model returned for a request.
+///
+/// Response-phase policies read it through the `response` context section,
+/// e.g. `response.content` or `response.usage.completion_tokens`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResponseContext {
+    /// Completion text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// Why generation stopped (e.g., "stop", "length", "content_filter")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<String>,
+    /// Tokens actually used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+    /// Model that produced the response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Tool calls the model requested
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+}
+
+/// Token usage reported for a response.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    /// Tokens in the prompt
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    /// Tokens in the completion
+    #[serde(default)]
+    pub completion_tokens: u64,
+    /// Prompt and completion tokens together
+    #[serde(default)]
+    pub total_tokens: u64,
+}
+
+impl ResponseContext {
+    /// Create a response with the given completion text.
+    pub fn new(content: impl Into<String>) -> Self {
+        Self {
+            content: Some(content.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Set the finish reason.
+    pub fn with_finish_reason(mut self, finish_reason: impl Into<String>) -> Self {
+        self.finish_reason = Some(finish_reason.into());
+        self
+    }
+
+    /// Set the token usage; the total is the sum of both counts.
+    pub fn with_usage(mut self, prompt_tokens: u64, completion_tokens: u64) -> Self {
+        self.usage = Some(TokenUsage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        });
+        self
+    }
+
+    /// Set the model.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Add a tool call requested by the model.
+    pub fn with_tool_call(mut self, call: ToolCall) -> Self {
+        self.tool_calls.push(call);
+        self
+    }
+}
@@ -0,0 +1,162 @@
+//! Tool definitions and tool calls of agentic requests.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+/// A tool (function) offered to the model.
+///
+/// Both the plain `{name, description, parameters}` shape and the OpenAI
+/// `{type: function, function: {...}}` wrapper are accepted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "ToolDefinitionRepr")]
+pub struct ToolDefinition {
+    /// Tool name
+    pub name: String,
+    /// What the tool does
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// JSON schema of the tool's arguments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ToolDefinitionRepr {
+    Wrapped { function: ToolFields },
+    Plain(ToolFields),
+}
+
+#[derive(Deserialize)]
+struct ToolFields {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    parameters: Option<Value>,
+}
+
+impl From<ToolDefinitionRepr> for ToolDefinition {
+    fn from(repr: ToolDefinitionRepr) -> Self {
+        let (ToolDefinitionRepr::Wrapped { function: fields } | ToolDefinitionRepr::Plain(fields)) =
+            repr;
+        Self {
+            name: fields.name,
+            description: fields.description,
+            parameters: fields.parameters,
+        }
+    }
+}
+
+impl ToolDefinition {
+    /// Create a tool definition.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            parameters: None,
+        }
+    }
+
+    /// Set the description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set the JSON schema of the arguments.
+    pub fn with_parameters(mut self, parameters: Value) -> Self {
+        self.parameters = Some(parameters);
+        self
+    }
+}
+
+/// A call the model made to a tool.
+///
+/// Arguments sent as a JSON-encoded string, as OpenAI does, are decoded so
+/// conditions can read them by path.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// Call identifier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Name of the tool called
+    pub name: String,
+    /// Arguments of the call
+    #[serde(default, deserialize_with = "decode_arguments")]
+    pub arguments: Value,
+}
+
+impl ToolCall {
+    /// Create a tool call.
+    pub fn new(name: impl Into<String>, arguments: Value) -> Self {
+        Self {
+            id: None,
+            name: name.into(),
+            arguments,
+        }
+    }
+
+    /// Set the call identifier.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+}
+
+/// Decode arguments given as a JSON string, keeping other strings as is.
+fn decode_arguments<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(text) => serde_json::from_str(&text).unwrap_or(Value::String(text)),
+        value => value,
+    })
+}
+
+/// Get the tool name of a value: a string is the name itself, an object
+/// has a `name` (or `function.name`) key.
+pub(crate) fn tool_name(value: &Value) -> Option<&str> {
+    match value {
+        Value::String(name) => Some(name),
+        Value::Object(map) => map
+            .get("name")
+            .or_else(|| map.get("function")?.get("name"))
+            .and_then(Value::as_str),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize() {
+        let tools: Vec<ToolDefinition> = serde_json::from_value(serde_json::json!([
+            {"name": "search", "description": "Search the web"},
+            {"type": "function", "function": {"name": "shell", "parameters": {"type": "object"}}},
+        ]))
+        .unwrap();
+        assert_eq!(
+            tools,
+            vec![
+                ToolDefinition::new("search").with_description("Search the web"),
+                ToolDefinition::new("shell").with_parameters(serde_json::json!({"type": "object"})),
+            ]
+        );
+
+        let call: ToolCall = serde_json::from_value(serde_json::json!({
+            "id": "call-1",
+            "name": "shell",
+            "arguments": "{\"command\": \"ls\"}",
+        }))
+        .unwrap();
+        assert_eq!(
+            call,
+            ToolCall::new("shell", serde_json::json!({"command": "ls"})).with_id("call-1")
+        );
+        assert_eq!(
+            tool_name(&serde_json::to_value(&call).unwrap()),
+            Some("shell")
+        );
+    }
+}
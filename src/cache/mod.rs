@@ -1,10 +1,10 @@
 //! Caching layer for policy decisions.
 //!
 //! This module provides multi-layer caching for policy decisions to improve
-//! evaluation performance.
+//! evaluation performance. Entries remember the policy generation that
+//! produced them and are only served for that generation.
 
-use crate::api::{EvaluationContext, PolicyDecision};
-use crate::api::engine::CacheStats;
+use crate::api::{CacheStats, EvaluationContext, PolicyDecision};
 
 use lru::LruCache;
 use parking_lot::Mutex;
@@ -42,17 +42,19 @@ impl DecisionCache {
         }
     }
 
-    /// Get a cached decision for the given context.
-    pub fn get(&self, context: &EvaluationContext) -> Option<PolicyDecision> {
+    /// Get a cached decision for the given context and policy generation.
+    pub fn get(&self, context: &EvaluationContext, generation: u64) -> Option<PolicyDecision> {
         let key = self.compute_key(context);
         let mut cache = self.l1.lock();
 
         if let Some(cached) = cache.get(&key) {
-            if cached.expires_at > Instant::now() {
+            if cached.expires_at > Instant::now()
+                && cached.decision.policy_generation == generation
+            {
                 self.hits.fetch_add(1, Ordering::Relaxed);
                 return Some(cached.decision.clone());
             } else {
-                // Entry expired, remove it
+                // Entry expired or stale, remove it
                 cache.pop(&key);
             }
         }
@@ -62,6 +64,8 @@ impl DecisionCache {
     }
 
     /// Cache a decision for the given context.
+    ///
+    /// The entry is tied to the decision's `policy_generation`.
     pub fn put(&self, context: &EvaluationContext, decision: &PolicyDecision) {
         let key = self.compute_key(context);
         let cached = CachedDecision {
@@ -125,7 +129,7 @@ mod tests {
         let decision = PolicyDecision::allow();
         cache.put(&context, &decision);
 
-        let cached = cache.get(&context);
+        let cached = cache.get(&context, 0);
         assert!(cached.is_some());
         assert_eq!(cached.unwrap().decision, DecisionType::Allow);
     }
@@ -138,7 +142,7 @@ mod tests {
             .with_user_id("user-123")
             .build();
 
-        let cached = cache.get(&context);
+        let cached = cache.get(&context, 0);
         assert!(cached.is_none());
 
         let stats = cache.stats();
@@ -158,13 +162,30 @@ mod tests {
         cache.put(&context, &decision);
 
         // Should find it immediately
-        assert!(cache.get(&context).is_some());
+        assert!(cache.get(&context, 0).is_some());
 
         // Wait for expiration
         thread::sleep(Duration::from_millis(100));
 
         // Should be expired now
-        assert!(cache.get(&context).is_none());
+        assert!(cache.get(&context, 0).is_none());
+    }
+
+    #[test]
+    fn test_cache_stale_generation() {
+        let cache = DecisionCache::new(100, Duration::from_secs(60));
+
+        let context = EvaluationContext::builder()
+            .with_user_id("user-123")
+            .build();
+
+        let mut decision = PolicyDecision::allow();
+        decision.policy_generation = 3;
+        cache.put(&context, &decision);
+
+        assert!(cache.get(&context, 4).is_none());
+        // The stale entry is evicted rather than kept around
+        assert!(cache.get(&context, 3).is_none());
     }
 
     #[test]
@@ -180,7 +201,7 @@ mod tests {
 
         cache.clear();
 
-        assert!(cache.get(&context).is_none());
+        assert!(cache.get(&context, 0).is_none());
     }
 
     #[test]
@@ -198,9 +219,9 @@ mod tests {
         cache.put(&context1, &decision);
 
         // Hit
-        cache.get(&context1);
+        cache.get(&context1, 0);
         // Miss
-        cache.get(&context2);
+        cache.get(&context2, 0);
 
         let stats = cache.stats();
         assert_eq!(stats.hits, 1);
@@ -2,13 +2,20 @@
 //!
 //! This module provides hierarchical configuration support with environment
 //! variable overrides, following the LLM Dev Ops platform configuration patterns.
+//! See [`ConfigLoader`] for how files, environment variables and flags are layered.
 
+pub mod loader;
+
+pub use loader::{ConfigLoader, ConfigSource, LoadedConfig};
+
+use crate::policy::{CombiningAlgorithm, DecisionType};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Duration;
 
 /// Main configuration structure for the policy engine.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(default)]
+#[serde(default, deny_unknown_fields)]
 pub struct Config {
     /// Server configuration
     pub server: ServerConfig,
@@ -20,6 +27,8 @@ pub struct Config {
     pub integrations: IntegrationsConfig,
     /// Performance tuning configuration
     pub performance: PerformanceConfig,
+    /// Policy evaluation semantics
+    pub evaluation: EvaluationConfig,
     /// Security configuration
     pub security: SecurityConfig,
 }
@@ -32,6 +41,7 @@ impl Default for Config {
             telemetry: TelemetryConfig::default(),
             integrations: IntegrationsConfig::default(),
             performance: PerformanceConfig::default(),
+            evaluation: EvaluationConfig::default(),
             security: SecurityConfig::default(),
         }
     }
@@ -39,7 +49,7 @@ impl Default for Config {
 
 /// Server configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(default)]
+#[serde(default, deny_unknown_fields)]
 pub struct ServerConfig {
     /// HTTP server host
     pub host: String,
@@ -71,7 +81,7 @@ impl ServerConfig {
 
 /// Cache configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(default)]
+#[serde(default, deny_unknown_fields)]
 pub struct CacheConfig {
     /// Whether caching is enabled
     pub enabled: bool,
@@ -117,7 +127,7 @@ impl CacheConfig {
 
 /// Telemetry and observability configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(default)]
+#[serde(default, deny_unknown_fields)]
 pub struct TelemetryConfig {
     /// Whether telemetry is enabled
     pub enabled: bool,
@@ -154,7 +164,7 @@ impl Default for TelemetryConfig {
 
 /// External service integration configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(default)]
+#[serde(default, deny_unknown_fields)]
 pub struct IntegrationsConfig {
     /// LLM Shield service URL
     pub shield_url: Option<String>,
@@ -211,7 +221,7 @@ impl IntegrationsConfig {
 
 /// Performance tuning configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(default)]
+#[serde(default, deny_unknown_fields)]
 pub struct PerformanceConfig {
     /// Maximum policy size in MB
     pub max_policy_size_mb: usize,
@@ -223,8 +233,10 @@ pub struct PerformanceConfig {
     pub max_concurrent_evaluations: usize,
     /// WASM sandbox memory limit in MB
     pub wasm_memory_limit_mb: usize,
-    /// CEL expression evaluation timeout in milliseconds
-    pub cel_timeout_ms: u64,
+    /// Time budget of a CEL expression in milliseconds. This is not a
+    /// timeout: expressions run to completion, and one that took longer
+    /// fails with a timeout error
+    pub cel_budget_ms: u64,
 }
 
 impl Default for PerformanceConfig {
@@ -235,7 +247,7 @@ impl Default for PerformanceConfig {
             parallel_evaluation: true,
             max_concurrent_evaluations: 1000,
             wasm_memory_limit_mb: 64,
-            cel_timeout_ms: 50,
+            cel_budget_ms: 50,
         }
     }
 }
@@ -246,15 +258,96 @@ impl PerformanceConfig {
         Duration::from_millis(self.max_evaluation_time_ms)
     }
 
-    /// Get CEL timeout as Duration.
-    pub fn cel_timeout(&self) -> Duration {
-        Duration::from_millis(self.cel_timeout_ms)
+    /// Get the number of threads shared by parallel policy evaluations.
+    pub fn evaluation_threads(&self) -> usize {
+        if self.parallel_evaluation {
+            std::thread::available_parallelism().map_or(1, |threads| threads.get())
+        } else {
+            1
+        }
+    }
+
+    /// Get the CEL expression time budget as Duration.
+    pub fn cel_budget(&self) -> Duration {
+        Duration::from_millis(self.cel_budget_ms)
     }
 }
 
+/// Policy evaluation semantics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EvaluationConfig {
+    /// How the results of different policies are combined
+    pub combining_algorithm: CombiningAlgorithm,
+    /// Decision returned when no policy applies
+    pub default_decision: DecisionType,
+    /// Whether evaluation errors allow the request instead of denying it
+    pub fail_open: bool,
+    /// What an evaluation that exceeds `max_evaluation_time_ms` returns
+    pub on_timeout: TimeoutBehavior,
+    /// Per-namespace overrides
+    pub namespaces: HashMap<String, NamespaceConfig>,
+    /// YAML model catalog extending the built-in one
+    pub model_catalog: Option<String>,
+}
+
+impl Default for EvaluationConfig {
+    fn default() -> Self {
+        Self {
+            combining_algorithm: CombiningAlgorithm::default(),
+            default_decision: DecisionType::NotApplicable,
+            fail_open: false,
+            on_timeout: TimeoutBehavior::default(),
+            namespaces: HashMap::new(),
+            model_catalog: None,
+        }
+    }
+}
+
+impl EvaluationConfig {
+    /// Get the decision returned when no policy in the namespace applies.
+    pub fn default_decision_for(&self, namespace: Option<&str>) -> DecisionType {
+        self.namespace(namespace)
+            .and_then(|ns| ns.default_decision)
+            .unwrap_or(self.default_decision)
+    }
+
+    /// Check whether evaluation errors in the namespace fail open.
+    pub fn fail_open_for(&self, namespace: Option<&str>) -> bool {
+        self.namespace(namespace)
+            .and_then(|ns| ns.fail_open)
+            .unwrap_or(self.fail_open)
+    }
+
+    fn namespace(&self, namespace: Option<&str>) -> Option<&NamespaceConfig> {
+        namespace.and_then(|ns| self.namespaces.get(ns))
+    }
+}
+
+/// Outcome of an evaluation that runs out of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeoutBehavior {
+    /// Resolve to the fail-open or fail-closed decision like other errors
+    #[default]
+    Fail,
+    /// Return the timeout error to the caller
+    Error,
+}
+
+/// Evaluation overrides for a policy namespace.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NamespaceConfig {
+    /// Decision returned when no policy in the namespace applies
+    pub default_decision: Option<DecisionType>,
+    /// Whether evaluation errors allow the request instead of denying it
+    pub fail_open: Option<bool>,
+}
+
 /// Security configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(default)]
+#[serde(default, deny_unknown_fields)]
 pub struct SecurityConfig {
     /// Whether authentication is required
     pub auth_enabled: bool,
@@ -292,79 +385,15 @@ impl Default for SecurityConfig {
 impl Config {
     /// Load configuration from environment variables.
     pub fn from_env() -> crate::Result<Self> {
-        let mut config = Self::default();
-
-        // Server config
-        if let Ok(port) = std::env::var("PORT") {
-            config.server.port = port.parse().unwrap_or(3000);
-        }
-        if let Ok(grpc_port) = std::env::var("GRPC_PORT") {
-            config.server.grpc_port = grpc_port.parse().unwrap_or(50051);
-        }
-        if let Ok(host) = std::env::var("HOST") {
-            config.server.host = host;
-        }
-
-        // Cache config
-        if let Ok(enabled) = std::env::var("CACHE_ENABLED") {
-            config.cache.enabled = enabled.parse().unwrap_or(true);
-        }
-        if let Ok(redis_url) = std::env::var("REDIS_URL") {
-            config.cache.redis_url = Some(redis_url);
-            config.cache.l2_enabled = true;
-        }
-
-        // Telemetry config
-        if let Ok(enabled) = std::env::var("TELEMETRY_ENABLED") {
-            config.telemetry.enabled = enabled.parse().unwrap_or(true);
-        }
-        if let Ok(endpoint) = std::env::var("OTLP_ENDPOINT") {
-            config.telemetry.otlp_endpoint = Some(endpoint);
-        }
-        if let Ok(level) = std::env::var("LOG_LEVEL") {
-            config.telemetry.log_level = level;
-        }
-
-        // Integration URLs
-        if let Ok(url) = std::env::var("LLM_SHIELD_URL") {
-            config.integrations.shield_url = Some(url);
-        }
-        if let Ok(url) = std::env::var("LLM_COSTOPS_URL") {
-            config.integrations.costops_url = Some(url);
-        }
-        if let Ok(url) = std::env::var("LLM_GOVERNANCE_URL") {
-            config.integrations.governance_url = Some(url);
-        }
-        if let Ok(url) = std::env::var("LLM_EDGE_AGENT_URL") {
-            config.integrations.edge_agent_url = Some(url);
-        }
-        if let Ok(url) = std::env::var("INCIDENT_MANAGER_URL") {
-            config.integrations.incident_manager_url = Some(url);
-        }
-        if let Ok(url) = std::env::var("SENTINEL_URL") {
-            config.integrations.sentinel_url = Some(url);
-        }
-
-        // Phase 2B: Upstream consumption adapter URLs
-        if let Ok(url) = std::env::var("LLM_SCHEMA_REGISTRY_URL") {
-            config.integrations.schema_registry_url = Some(url);
-        }
-        if let Ok(url) = std::env::var("LLM_CONFIG_MANAGER_URL") {
-            config.integrations.config_manager_url = Some(url);
-        }
-        if let Ok(url) = std::env::var("LLM_OBSERVATORY_URL") {
-            config.integrations.observatory_url = Some(url);
-        }
-
-        // Security config
-        if let Ok(secret) = std::env::var("JWT_SECRET") {
-            config.security.jwt_secret = Some(secret);
-        }
-        if let Ok(enabled) = std::env::var("AUTH_ENABLED") {
-            config.security.auth_enabled = enabled.parse().unwrap_or(false);
-        }
+        ConfigLoader::new().load().map(|loaded| loaded.config)
+    }
 
-        Ok(config)
+    /// Load configuration from a file, with environment variable overrides.
+    pub fn from_file(path: impl Into<std::path::PathBuf>) -> crate::Result<Self> {
+        ConfigLoader::new()
+            .with_file(path)
+            .load()
+            .map(|loaded| loaded.config)
     }
 
     /// Validate the configuration.
@@ -390,6 +419,12 @@ impl Config {
             ));
         }
 
+        if self.performance.max_concurrent_evaluations == 0 {
+            return Err(crate::Error::config(
+                "max_concurrent_evaluations must be greater than 0",
+            ));
+        }
+
         Ok(())
     }
 }
@@ -426,4 +461,17 @@ mod tests {
         assert_eq!(config.l1_ttl(), Duration::from_secs(300));
         assert_eq!(config.l2_ttl(), Duration::from_secs(600));
     }
+
+    #[test]
+    fn test_namespace_overrides() {
+        let config: EvaluationConfig = serde_yaml::from_str(
+            "default_decision: deny\nnamespaces:\n  sandbox:\n    default_decision: allow\n    fail_open: true\n",
+        )
+        .unwrap();
+        assert_eq!(config.default_decision_for(None), DecisionType::Deny);
+        assert_eq!(config.default_decision_for(Some("billing")), DecisionType::Deny);
+        assert_eq!(config.default_decision_for(Some("sandbox")), DecisionType::Allow);
+        assert!(!config.fail_open_for(Some("billing")));
+        assert!(config.fail_open_for(Some("sandbox")));
+    }
 }
@@ -0,0 +1,181 @@
+//! IP ranges of CIDR conditions, compiled into prefix tries.
+
+use crate::{Error, Result};
+
+use std::net::IpAddr;
+
+/// A set of IPv4 and IPv6 ranges.
+///
+/// Ranges are stored in a binary trie per address family, so a lookup
+/// takes at most one step per address bit however many ranges the set
+/// holds. IPv4-mapped IPv6 addresses (`::ffff:10.0.0.1`) are looked up as
+/// IPv4 addresses.
+#[derive(Debug, Clone)]
+pub struct CidrSet {
+    /// Ranges as written in the policy
+    ranges: Vec<String>,
+    v4: PrefixTrie,
+    v6: PrefixTrie,
+}
+
+/// A binary trie of address prefixes.
+#[derive(Debug, Clone)]
+struct PrefixTrie {
+    /// Nodes with the root first; a child index of zero means no child
+    nodes: Vec<TrieNode>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct TrieNode {
+    children: [u32; 2],
+    /// Whether a range ends here, covering every address below
+    terminal: bool,
+}
+
+impl CidrSet {
+    /// Compile ranges in CIDR notation (`10.0.0.0/8`, `2001:db8::/32`);
+    /// plain addresses are single-address ranges.
+    pub fn compile(ranges: &[String]) -> Result<Self> {
+        let mut set = Self {
+            ranges: ranges.to_vec(),
+            v4: PrefixTrie::new(),
+            v6: PrefixTrie::new(),
+        };
+        for range in ranges {
+            let (address, length) = parse_range(range)?;
+            match address {
+                IpAddr::V4(address) => set.v4.insert(u32::from(address).into(), 32, length),
+                IpAddr::V6(address) => set.v6.insert(u128::from(address), 128, length),
+            }
+        }
+        Ok(set)
+    }
+
+    /// Get the ranges as written in the policy.
+    pub fn ranges(&self) -> &[String] {
+        &self.ranges
+    }
+
+    /// Check whether an address is in one of the ranges.
+    pub fn contains(&self, address: IpAddr) -> bool {
+        match address.to_canonical() {
+            IpAddr::V4(address) => self.v4.contains(u32::from(address).into(), 32),
+            IpAddr::V6(address) => self.v6.contains(u128::from(address), 128),
+        }
+    }
+}
+
+impl PrefixTrie {
+    fn new() -> Self {
+        Self {
+            nodes: vec![TrieNode::default()],
+        }
+    }
+
+    /// Add the first `length` of the `width` bits of an address.
+    fn insert(&mut self, address: u128, width: u8, length: u8) {
+        let mut node = 0;
+        for bit in 0..length {
+            if self.nodes[node].terminal {
+                return; // Covered by a shorter range
+            }
+            let branch = bit_at(address, width, bit);
+            node = match self.nodes[node].children[branch] {
+                0 => {
+                    self.nodes.push(TrieNode::default());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children[branch] = child as u32;
+                    child
+                }
+                child => child as usize,
+            };
+        }
+        self.nodes[node] = TrieNode {
+            children: [0; 2],
+            terminal: true,
+        };
+    }
+
+    fn contains(&self, address: u128, width: u8) -> bool {
+        let mut node = 0;
+        for bit in 0..width {
+            if self.nodes[node].terminal {
+                return true;
+            }
+            node = match self.nodes[node].children[bit_at(address, width, bit)] {
+                0 => return false,
+                child => child as usize,
+            };
+        }
+        self.nodes[node].terminal
+    }
+}
+
+/// Get a bit of an address, counting from the most significant.
+fn bit_at(address: u128, width: u8, bit: u8) -> usize {
+    ((address >> (width - 1 - bit)) & 1) as usize
+}
+
+/// Parse a range into its address and prefix length.
+fn parse_range(range: &str) -> Result<(IpAddr, u8)> {
+    let invalid = || Error::validation(format!("Invalid CIDR range: '{}'", range));
+    let (address, length) = match range.split_once('/') {
+        Some((address, length)) => (address, Some(length)),
+        None => (range, None),
+    };
+    let address: IpAddr = address.trim().parse().map_err(|_| invalid())?;
+    let width = if address.is_ipv4() { 32 } else { 128 };
+    let length = match length {
+        Some(length) => length.trim().parse().map_err(|_| invalid())?,
+        None => width,
+    };
+    if length > width {
+        return Err(invalid());
+    }
+    Ok((address, length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[&str]) -> CidrSet {
+        let ranges: Vec<_> = ranges.iter().map(|r| r.to_string()).collect();
+        CidrSet::compile(&ranges).unwrap()
+    }
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    #[test]
+    fn test_ranges() {
+        let corporate = set(&["10.0.0.0/8", "192.168.1.7", "10.1.0.0/16", "2001:db8::/32"]);
+        assert!(corporate.contains(ip("10.200.3.4")));
+        assert!(corporate.contains(ip("10.1.2.3")));
+        assert!(corporate.contains(ip("192.168.1.7")));
+        assert!(!corporate.contains(ip("192.168.1.8")));
+        assert!(!corporate.contains(ip("11.0.0.1")));
+        assert!(corporate.contains(ip("::ffff:10.0.0.1")));
+        assert!(corporate.contains(ip("2001:db8:ffff::1")));
+        assert!(!corporate.contains(ip("2001:db9::1")));
+
+        let everything = set(&["0.0.0.0/0", "::/0"]);
+        assert!(everything.contains(ip("203.0.113.9")));
+        assert!(everything.contains(ip("fe80::1")));
+        assert!(!set(&[]).contains(ip("127.0.0.1")));
+    }
+
+    #[test]
+    fn test_invalid_ranges() {
+        for range in [
+            "10.0.0.0/33",
+            "2001:db8::/129",
+            "10.0.0/8",
+            "10.0.0.0/x",
+            "localhost",
+        ] {
+            assert!(CidrSet::compile(&[range.to_string()]).is_err(), "{}", range);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Combining of rule and policy results.
+
+use crate::api::PolicyDecision;
+use crate::policy::{CombiningAlgorithm, DecisionType};
+use crate::{Error, Result};
+
+/// Accumulates applicable results under a combining algorithm.
+pub(crate) struct Combiner {
+    algorithm: CombiningAlgorithm,
+    deny: Option<(String, PolicyDecision)>,
+    permit: Option<PolicyDecision>,
+    permit_ids: Vec<String>,
+    decided: bool,
+}
+
+impl Combiner {
+    /// Create a combiner.
+    pub(crate) fn new(algorithm: CombiningAlgorithm) -> Self {
+        Self {
+            algorithm,
+            deny: None,
+            permit: None,
+            permit_ids: Vec::new(),
+            decided: false,
+        }
+    }
+
+    /// Whether a result settles the outcome on its own, so that later
+    /// results can no longer change it.
+    pub(crate) fn decides(algorithm: CombiningAlgorithm, decision: &PolicyDecision) -> bool {
+        match decision.decision {
+            DecisionType::NotApplicable => false,
+            DecisionType::Deny => matches!(
+                algorithm,
+                CombiningAlgorithm::DenyOverrides | CombiningAlgorithm::FirstApplicable
+            ),
+            _ => algorithm == CombiningAlgorithm::FirstApplicable,
+        }
+    }
+
+    /// Whether further results can no longer change the outcome.
+    pub(crate) fn is_decided(&self) -> bool {
+        self.decided
+    }
+
+    /// Add the result of a rule or policy; not-applicable results are ignored.
+    pub(crate) fn add(&mut self, id: &str, decision: PolicyDecision) -> Result<()> {
+        if decision.decision == DecisionType::NotApplicable {
+            return Ok(());
+        }
+
+        if self.algorithm == CombiningAlgorithm::OnlyOneApplicable {
+            let previous = self
+                .deny
+                .as_ref()
+                .map(|(previous, _)| previous)
+                .or(self.permit_ids.first());
+            if let Some(previous) = previous {
+                return Err(Error::evaluation(format!(
+                    "Both '{}' and '{}' apply under the {} algorithm",
+                    previous, id, self.algorithm
+                )));
+            }
+        }
+
+        self.decided |= Self::decides(self.algorithm, &decision);
+        if decision.decision == DecisionType::Deny {
+            if self.deny.is_none() {
+                self.deny = Some((id.to_string(), decision));
+            }
+        } else {
+            self.permit_ids.push(id.to_string());
+            match &mut self.permit {
+                Some(permit) => merge_permit(permit, decision),
+                None => self.permit = Some(decision),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the combined decision and the IDs that produced it, or `None`
+    /// when nothing applied.
+    pub(crate) fn finish(self) -> Option<(PolicyDecision, Vec<String>)> {
+        let deny = self.deny.map(|(id, decision)| (decision, vec![id]));
+        let permit = self.permit.map(|decision| (decision, self.permit_ids));
+
+        match self.algorithm {
+            CombiningAlgorithm::PermitOverrides => permit.or(deny),
+            _ => deny.or(permit),
+        }
+    }
+}
+
+/// Merge a permitting result into the permits combined so far.
+///
+/// A warning replaces a plain allow, modifications upgrade the decision to
+/// modify, and later modifications of the same field win.
+fn merge_permit(permit: &mut PolicyDecision, next: PolicyDecision) {
+    match next.decision {
+        DecisionType::Warn if permit.decision == DecisionType::Allow => {
+            *permit = next;
+            return;
+        }
+        DecisionType::Modify => {
+            permit.decision = DecisionType::Modify;
+            permit.modifications.extend(next.modifications);
+            permit.field_modifications.extend(next.field_modifications);
+        }
+        _ => {}
+    }
+
+    if permit.reason.is_none() {
+        permit.reason = next.reason;
+    }
+    for (key, value) in next.metadata {
+        permit.metadata.entry(key).or_insert(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn results() -> Vec<(&'static str, PolicyDecision)> {
+        vec![
+            ("warn", PolicyDecision::warn("careful")),
+            ("deny", PolicyDecision::deny("no")),
+            (
+                "modify",
+                PolicyDecision::modify(HashMap::from([(
+                    "llm.maxTokens".to_string(),
+                    serde_json::json!(100),
+                )])),
+            ),
+        ]
+    }
+
+    fn combine(algorithm: CombiningAlgorithm) -> Result<Option<(PolicyDecision, Vec<String>)>> {
+        let mut combiner = Combiner::new(algorithm);
+        for (id, decision) in results() {
+            if combiner.is_decided() {
+                break;
+            }
+            combiner.add(id, decision)?;
+        }
+        Ok(combiner.finish())
+    }
+
+    #[test]
+    fn test_algorithms() {
+        let (decision, ids) = combine(CombiningAlgorithm::DenyOverrides).unwrap().unwrap();
+        assert_eq!(decision.decision, DecisionType::Deny);
+        assert_eq!(ids, vec!["deny"]);
+
+        let (decision, ids) = combine(CombiningAlgorithm::PermitOverrides)
+            .unwrap()
+            .unwrap();
+        assert_eq!(decision.decision, DecisionType::Modify);
+        assert_eq!(decision.reason.as_deref(), Some("careful"));
+        assert_eq!(ids, vec!["warn", "modify"]);
+
+        let (decision, ids) = combine(CombiningAlgorithm::FirstApplicable)
+            .unwrap()
+            .unwrap();
+        assert_eq!(decision.decision, DecisionType::Warn);
+        assert_eq!(ids, vec!["warn"]);
+
+        assert!(combine(CombiningAlgorithm::OnlyOneApplicable).is_err());
+    }
+
+    #[test]
+    fn test_nothing_applicable() {
+        assert!(Combiner::new(CombiningAlgorithm::DenyOverrides)
+            .finish()
+            .is_none());
+    }
+}
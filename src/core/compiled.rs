@@ -0,0 +1,1105 @@
+//! Executable form of policies.
+//!
+//! Policies are compiled once when they are loaded: field paths are parsed,
+//! regexes, CEL programs and IP range tries are built, operator/value
+//! combinations are type checked and rules are sorted by priority. Targets
+//! are compiled into field paths and value sets. Problems are reported at
+//! load time instead of on the first request that reaches the offending
+//! condition.
+
+use super::time::{time_zone, timestamp, TimeWindow};
+use super::{CelExpression, CidrSet, EvaluationScope};
+use crate::api::{tool_name, EvaluationContext, EvaluationTrace, FieldPath, TraceStep};
+use crate::policy::{
+    Condition, ConditionOperator, ConditionValue, Policy, PolicyPhase, PolicyRule, PolicyTarget,
+};
+use crate::{Error, Result};
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use regex::{Regex, RegexBuilder};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Limit on the compiled size of a condition regex.
+///
+/// Matching time grows with the compiled size, so patterns that would be
+/// slow to match on every request are rejected when the policy is loaded.
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+/// Field read by time conditions that do not name one.
+const TIMESTAMP_FIELD: &str = "request.timestamp";
+
+/// A policy compiled for evaluation.
+#[derive(Debug, Clone)]
+pub struct CompiledPolicy {
+    policy: Arc<Policy>,
+    target: Option<CompiledTarget>,
+    rules: Vec<CompiledRule>,
+    namespaces: BTreeSet<String>,
+    reads_clock: bool,
+}
+
+/// The compiled target of a [`CompiledPolicy`].
+#[derive(Debug, Clone)]
+pub struct CompiledTarget {
+    selectors: Vec<(FieldPath, Vec<String>)>,
+}
+
+/// The first target selector a request does not satisfy.
+#[derive(Debug, Clone)]
+pub struct TargetMismatch<'a> {
+    /// Field the selector reads
+    pub field: &'a FieldPath,
+    /// Value of the field in the request, if present
+    pub actual: Option<Value>,
+    /// Values the selector accepts
+    pub expected: &'a [String],
+}
+
+/// A rule of a [`CompiledPolicy`].
+#[derive(Debug, Clone)]
+pub struct CompiledRule {
+    index: usize,
+    condition: CompiledCondition,
+}
+
+impl CompiledPolicy {
+    /// Compile a policy.
+    pub fn compile(policy: impl Into<Arc<Policy>>) -> Result<Self> {
+        let policy = policy.into();
+
+        let mut rules = policy
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.enabled)
+            .map(|(index, rule)| {
+                let condition = CompiledCondition::compile(&rule.condition).map_err(|e| {
+                    Error::validation(format!("Policy '{}' rule '{}': {}", policy.id, rule.id, e))
+                })?;
+                Ok(CompiledRule { index, condition })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Stable sort keeps declaration order for equal priorities
+        rules.sort_by_key(|r| std::cmp::Reverse(policy.rules[r.index].priority));
+
+        let target = match &policy.target {
+            Some(target) if !target.is_empty() => Some(CompiledTarget::compile(target).map_err(
+                |e| Error::validation(format!("Policy '{}' target: {}", policy.id, e)),
+            )?),
+            _ => None,
+        };
+
+        let mut namespaces = BTreeSet::new();
+        for rule in &rules {
+            rule.condition.collect_namespaces(&mut namespaces);
+        }
+        let reads_clock = rules.iter().any(|rule| rule.condition.reads_clock());
+        for (field, _) in target.iter().flat_map(|t| t.selectors()) {
+            namespaces.extend(namespace(field));
+        }
+        if policy.phase == PolicyPhase::Request && namespaces.contains("response") {
+            return Err(Error::validation(format!(
+                "Policy '{}' reads the response but only runs in the request phase",
+                policy.id
+            )));
+        }
+
+        Ok(Self {
+            policy,
+            target,
+            rules,
+            namespaces,
+            reads_clock,
+        })
+    }
+
+    /// Get the top-level context sections the target and rules read, such
+    /// as `llm` or an attribute namespace like `budget`.
+    pub fn namespaces(&self) -> &BTreeSet<String> {
+        &self.namespaces
+    }
+
+    /// Check whether a rule has a time condition, whose outcome depends on
+    /// the current time when the request carries no timestamp.
+    pub fn reads_clock(&self) -> bool {
+        self.reads_clock
+    }
+
+    /// Get the compiled target, if the policy has one.
+    pub fn target(&self) -> Option<&CompiledTarget> {
+        self.target.as_ref()
+    }
+
+    /// Get the source policy.
+    pub fn policy(&self) -> &Arc<Policy> {
+        &self.policy
+    }
+
+    /// Get the enabled rules in evaluation order with their compiled conditions.
+    pub fn rules(&self) -> impl Iterator<Item = (&PolicyRule, &CompiledCondition)> {
+        self.rules
+            .iter()
+            .map(|r| (&self.policy.rules[r.index], &r.condition))
+    }
+}
+
+impl std::ops::Deref for CompiledPolicy {
+    type Target = Policy;
+
+    fn deref(&self) -> &Policy {
+        &self.policy
+    }
+}
+
+impl CompiledTarget {
+    /// Compile a target.
+    pub fn compile(target: &PolicyTarget) -> Result<Self> {
+        let selectors = target
+            .selectors()
+            .map(|(field, values)| {
+                let mut values = values.to_vec();
+                values.sort();
+                values.dedup();
+                Ok((FieldPath::parse(field)?, values))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { selectors })
+    }
+
+    /// Get the selected fields with their accepted values, sorted and deduplicated.
+    pub fn selectors(&self) -> &[(FieldPath, Vec<String>)] {
+        &self.selectors
+    }
+
+    /// Find the first selector the context does not satisfy, if any.
+    pub fn mismatch(&self, context: &EvaluationContext) -> Option<TargetMismatch<'_>> {
+        self.selectors.iter().find_map(|(field, expected)| {
+            let actual = context.resolve(field);
+            let matched = actual.as_ref().is_some_and(|actual| {
+                target_keys(actual)
+                    .iter()
+                    .any(|key| expected.binary_search(key).is_ok())
+            });
+            (!matched).then_some(TargetMismatch {
+                field,
+                actual,
+                expected,
+            })
+        })
+    }
+}
+
+/// Get the time a time condition tests, with the field value it was read
+/// from; requests without the field are tested at the current time.
+fn time_in(
+    scope: &EvaluationScope<'_>,
+    field: &FieldPath,
+) -> Result<(Option<Value>, DateTime<Utc>)> {
+    match scope.context().resolve(field) {
+        Some(actual) => match timestamp(&actual) {
+            Some(time) => Ok((Some(actual), time)),
+            None => Err(Error::evaluation(format!(
+                "Field '{}' is not a Unix or RFC 3339 timestamp",
+                field.as_str()
+            ))),
+        },
+        None => Ok((None, scope.now())),
+    }
+}
+
+/// Get the top-level section a path reads.
+fn namespace(field: &FieldPath) -> Option<String> {
+    field.segments().first()?.as_key().map(String::from)
+}
+
+/// Get the keys a context value is matched against target values with.
+///
+/// Scalars match their string form; lists match any of their elements.
+pub(crate) fn target_keys(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(s) => vec![s.clone()],
+        Value::Number(_) | Value::Bool(_) => vec![value.to_string()],
+        Value::Array(items) => items
+            .iter()
+            .filter(|item| !item.is_array())
+            .flat_map(target_keys)
+            .collect(),
+        Value::Null | Value::Object(_) => Vec::new(),
+    }
+}
+
+/// A condition compiled for evaluation.
+#[derive(Debug, Clone)]
+pub enum CompiledCondition {
+    /// All nested conditions hold
+    And(Vec<CompiledCondition>),
+    /// Any nested condition holds
+    Or(Vec<CompiledCondition>),
+    /// The nested condition does not hold
+    Not(Box<CompiledCondition>),
+    /// The field is present
+    Exists(FieldPath),
+    /// The field is absent
+    NotExists(FieldPath),
+    /// The field is present and its value satisfies the matcher
+    Compare {
+        /// Field to read from the context
+        field: FieldPath,
+        /// Test applied to the field value
+        matcher: Matcher,
+    },
+    /// The time in the field, or the current time when it is absent, falls
+    /// in the window
+    Time {
+        /// Field holding the time, in Unix seconds or RFC 3339
+        field: FieldPath,
+        /// Time zone the window is in
+        zone: Tz,
+        /// Local times the condition holds at
+        window: TimeWindow,
+    },
+    /// The CEL expression evaluates to true
+    Expression(CelExpression),
+}
+
+/// A comparison against a field value.
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    /// Value equals the expected value
+    Equals(ConditionValue),
+    /// Value does not equal the expected value
+    NotEquals(ConditionValue),
+    /// Numeric value is greater than the bound
+    GreaterThan(f64),
+    /// Numeric value is greater than or equal to the bound
+    GreaterThanOrEquals(f64),
+    /// Numeric value is less than the bound
+    LessThan(f64),
+    /// Numeric value is less than or equal to the bound
+    LessThanOrEquals(f64),
+    /// Value is one of the listed values
+    In(Vec<ConditionValue>),
+    /// Value is none of the listed values
+    NotIn(Vec<ConditionValue>),
+    /// String contains a substring, or array contains an element
+    Contains(ConditionValue, Value),
+    /// String starts with the prefix
+    StartsWith(String),
+    /// String ends with the suffix
+    EndsWith(String),
+    /// String matches the regex
+    Matches(Regex),
+    /// String equals one of the templates
+    MatchesTemplate {
+        /// Templates as written in the policy
+        templates: Vec<String>,
+        /// Anchored regex matching any of the templates
+        regex: Regex,
+    },
+    /// A named tool is one of the listed tools
+    ToolIn(Vec<String>),
+    /// A named tool is not one of the listed tools
+    ToolNotIn(Vec<String>),
+    /// String is an IP address in one of the ranges
+    InCidr(CidrSet),
+    /// String is not an IP address in any of the ranges
+    NotInCidr(CidrSet),
+    /// An argument of a matching tool call satisfies the matcher
+    ArgumentMatches {
+        /// Tool whose calls are tested; any tool when unset
+        tool: Option<String>,
+        /// Path of the argument within the call arguments
+        argument: FieldPath,
+        /// Test applied to the argument
+        matcher: Box<Matcher>,
+    },
+}
+
+impl CompiledCondition {
+    /// Add the top-level context sections the condition reads.
+    pub fn collect_namespaces(&self, namespaces: &mut BTreeSet<String>) {
+        match self {
+            Self::And(conditions) | Self::Or(conditions) => conditions
+                .iter()
+                .for_each(|c| c.collect_namespaces(namespaces)),
+            Self::Not(inner) => inner.collect_namespaces(namespaces),
+            Self::Exists(field)
+            | Self::NotExists(field)
+            | Self::Compare { field, .. }
+            | Self::Time { field, .. } => namespaces.extend(namespace(field)),
+            Self::Expression(expression) => namespaces.extend(expression.variables()),
+        }
+    }
+
+    /// Check whether the condition has a time condition.
+    pub fn reads_clock(&self) -> bool {
+        match self {
+            Self::And(conditions) | Self::Or(conditions) => {
+                conditions.iter().any(Self::reads_clock)
+            }
+            Self::Not(inner) => inner.reads_clock(),
+            Self::Time { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Compile a condition, checking that operators and values fit together.
+    pub fn compile(condition: &Condition) -> Result<Self> {
+        let nested = || {
+            condition
+                .conditions
+                .iter()
+                .map(Self::compile)
+                .collect::<Result<Vec<_>>>()
+        };
+
+        let time_operator = matches!(
+            condition.operator,
+            ConditionOperator::HourBetween
+                | ConditionOperator::DayOfWeekIn
+                | ConditionOperator::DateBetween
+        );
+        if condition.timezone.is_some() && !time_operator {
+            return Err(Error::validation(format!(
+                "{} operator does not take a time zone",
+                condition.operator
+            )));
+        }
+
+        match condition.operator {
+            ConditionOperator::And => Ok(Self::And(nested()?)),
+            ConditionOperator::Or => Ok(Self::Or(nested()?)),
+            ConditionOperator::Not => match condition.conditions.as_slice() {
+                [inner] => Ok(Self::Not(Box::new(Self::compile(inner)?))),
+                _ => Err(Error::validation(
+                    "NOT operator requires exactly one nested condition",
+                )),
+            },
+            ConditionOperator::Exists => Ok(Self::Exists(field(condition)?)),
+            ConditionOperator::NotExists => Ok(Self::NotExists(field(condition)?)),
+            ConditionOperator::ArgumentMatches => {
+                let invalid = || {
+                    Error::validation(
+                        "argument_matches operator requires a '<tool>.<argument>' field and a regex",
+                    )
+                };
+                let (tool, argument) = condition
+                    .field
+                    .as_deref()
+                    .and_then(|field| field.split_once('.'))
+                    .ok_or_else(invalid)?;
+                let Some(ConditionValue::String(pattern)) = &condition.value else {
+                    return Err(invalid());
+                };
+                Ok(Self::Compare {
+                    field: FieldPath::parse("llm.tool_calls")?,
+                    matcher: Matcher::ArgumentMatches {
+                        tool: (tool != "*").then(|| tool.to_string()),
+                        argument: FieldPath::parse(argument)?,
+                        matcher: Box::new(Matcher::Matches(regex(pattern)?)),
+                    },
+                })
+            }
+            operator if time_operator => {
+                let value = condition.value.as_ref().ok_or_else(|| {
+                    Error::validation(format!("{} operator requires a value", operator))
+                })?;
+                Ok(Self::Time {
+                    field: FieldPath::parse(condition.field.as_deref().unwrap_or(TIMESTAMP_FIELD))?,
+                    zone: condition.timezone.as_deref().map_or(Ok(Tz::UTC), time_zone)?,
+                    window: TimeWindow::compile(operator, value)?,
+                })
+            }
+            ConditionOperator::Expression => match &condition.value {
+                Some(ConditionValue::String(source)) => {
+                    Ok(Self::Expression(CelExpression::compile(source)?))
+                }
+                _ => Err(Error::validation(
+                    "expression operator requires a CEL expression string",
+                )),
+            },
+            operator => {
+                let field = field(condition)?;
+                let value = condition.value.as_ref().ok_or_else(|| {
+                    Error::validation(format!("{} operator requires a value", operator))
+                })?;
+                let matcher = Matcher::compile(operator, value)?;
+                Ok(Self::Compare { field, matcher })
+            }
+        }
+    }
+
+    /// Evaluate the condition within a request scope.
+    pub fn evaluate(&self, scope: &EvaluationScope<'_>) -> Result<bool> {
+        match self {
+            Self::And(conditions) => {
+                for condition in conditions {
+                    if !condition.evaluate(scope)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            Self::Or(conditions) => {
+                for condition in conditions {
+                    if condition.evaluate(scope)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            Self::Not(condition) => Ok(!condition.evaluate(scope)?),
+            Self::Exists(field) => Ok(scope.context().resolve(field).is_some()),
+            Self::NotExists(field) => Ok(scope.context().resolve(field).is_none()),
+            Self::Compare { field, matcher } => match scope.context().resolve(field) {
+                Some(actual) => matcher.matches_field(field, &actual),
+                None => Ok(false), // Field doesn't exist, comparison fails
+            },
+            Self::Time { field, zone, window } => {
+                let (_, time) = time_in(scope, field)?;
+                Ok(window.contains(&time.with_timezone(zone)))
+            }
+            Self::Expression(expression) => expression.evaluate(scope),
+        }
+    }
+
+    /// Evaluate the condition, recording a trace step for it and for every
+    /// nested condition that was reached.
+    ///
+    /// Nested steps are identified by their index path below `id`, e.g.
+    /// `rule-1/0/1`. Steps are recorded after the conditions they contain.
+    pub fn evaluate_traced(
+        &self,
+        scope: &EvaluationScope<'_>,
+        id: &str,
+        trace: &mut EvaluationTrace,
+    ) -> Result<bool> {
+        let start = Instant::now();
+        let nested_id = |index: usize| format!("{}/{}", id, index);
+        let mut step = TraceStep::condition(id, "", Duration::ZERO).with_operator(self.operator().as_str());
+
+        let outcome = match self {
+            Self::And(conditions) => {
+                let mut outcome = Ok(true);
+                for (index, condition) in conditions.iter().enumerate() {
+                    outcome = condition.evaluate_traced(scope, &nested_id(index), trace);
+                    if !matches!(outcome, Ok(true)) {
+                        break;
+                    }
+                }
+                outcome
+            }
+            Self::Or(conditions) => {
+                let mut outcome = Ok(false);
+                for (index, condition) in conditions.iter().enumerate() {
+                    outcome = condition.evaluate_traced(scope, &nested_id(index), trace);
+                    if !matches!(outcome, Ok(false)) {
+                        break;
+                    }
+                }
+                outcome
+            }
+            Self::Not(condition) => condition
+                .evaluate_traced(scope, &nested_id(0), trace)
+                .map(|matched| !matched),
+            Self::Exists(field) | Self::NotExists(field) => {
+                let actual = scope.context().resolve(field);
+                let outcome = actual.is_some() == matches!(self, Self::Exists(_));
+                step = step.with_field(field.as_str()).with_actual(actual);
+                Ok(outcome)
+            }
+            Self::Compare { field, matcher } => {
+                let actual = scope.context().resolve(field);
+                let outcome = match &actual {
+                    Some(actual) => matcher.matches_field(field, actual),
+                    None => Ok(false),
+                };
+                step = step
+                    .with_field(field.as_str())
+                    .with_actual(actual)
+                    .with_expected(matcher.expected());
+                outcome
+            }
+            Self::Time { field, zone, window } => {
+                let read = time_in(scope, field);
+                let actual = match &read {
+                    Ok((actual, time)) => actual
+                        .clone()
+                        .or_else(|| Some(Value::String(time.to_rfc3339()))),
+                    Err(_) => scope.context().resolve(field),
+                };
+                step = step
+                    .with_field(field.as_str())
+                    .with_actual(actual)
+                    .with_expected(serde_json::json!({
+                        "window": window.expected(),
+                        "timezone": zone.name(),
+                    }));
+                read.map(|(_, time)| window.contains(&time.with_timezone(zone)))
+            }
+            Self::Expression(expression) => {
+                step = step.with_expected(Value::String(expression.source().to_string()));
+                expression.evaluate(scope)
+            }
+        };
+
+        step.duration_us = start.elapsed().as_micros() as u64;
+        trace.add_step(step.with_outcome(&outcome));
+        outcome
+    }
+
+    /// Get the operator of the condition.
+    pub fn operator(&self) -> ConditionOperator {
+        match self {
+            Self::And(_) => ConditionOperator::And,
+            Self::Or(_) => ConditionOperator::Or,
+            Self::Not(_) => ConditionOperator::Not,
+            Self::Exists(_) => ConditionOperator::Exists,
+            Self::NotExists(_) => ConditionOperator::NotExists,
+            Self::Compare { matcher, .. } => matcher.operator(),
+            Self::Time { window, .. } => window.operator(),
+            Self::Expression(_) => ConditionOperator::Expression,
+        }
+    }
+}
+
+impl Matcher {
+    /// Compile an operator and its expected value.
+    pub fn compile(operator: ConditionOperator, value: &ConditionValue) -> Result<Self> {
+        let string = |value: &ConditionValue| match value {
+            ConditionValue::String(s) => Ok(s.clone()),
+            other => Err(Error::validation(format!(
+                "{} operator requires a string value, got {:?}",
+                operator, other
+            ))),
+        };
+        let number = |value: &ConditionValue| match value {
+            ConditionValue::Integer(n) => Ok(*n as f64),
+            ConditionValue::Float(n) => Ok(*n),
+            other => Err(Error::validation(format!(
+                "{} operator requires a numeric value, got {:?}",
+                operator, other
+            ))),
+        };
+        let names = |value: &ConditionValue| match value {
+            ConditionValue::Array(values) => values.iter().map(string).collect(),
+            value => Ok(vec![string(value)?]),
+        };
+        let list = |value: &ConditionValue| match value {
+            ConditionValue::Array(values) => Ok(values.clone()),
+            other => Err(Error::validation(format!(
+                "{} operator requires a list value, got {:?}",
+                operator, other
+            ))),
+        };
+
+        Ok(match operator {
+            ConditionOperator::Equals => Self::Equals(value.clone()),
+            ConditionOperator::NotEquals => Self::NotEquals(value.clone()),
+            ConditionOperator::GreaterThan => Self::GreaterThan(number(value)?),
+            ConditionOperator::GreaterThanOrEquals => Self::GreaterThanOrEquals(number(value)?),
+            ConditionOperator::LessThan => Self::LessThan(number(value)?),
+            ConditionOperator::LessThanOrEquals => Self::LessThanOrEquals(number(value)?),
+            ConditionOperator::In => Self::In(list(value)?),
+            ConditionOperator::NotIn => Self::NotIn(list(value)?),
+            ConditionOperator::Contains => {
+                Self::Contains(value.clone(), condition_value_to_json(value))
+            }
+            ConditionOperator::StartsWith => Self::StartsWith(string(value)?),
+            ConditionOperator::EndsWith => Self::EndsWith(string(value)?),
+            ConditionOperator::Matches => Self::Matches(regex(&string(value)?)?),
+            ConditionOperator::MatchesTemplate => {
+                let templates = names(value)?;
+                let alternatives: Vec<_> = templates.iter().map(|t| template(t)).collect();
+                let regex = regex(&format!("(?s)^(?:{})$", alternatives.join("|")))?;
+                Self::MatchesTemplate { templates, regex }
+            }
+            ConditionOperator::ToolIn => Self::ToolIn(names(value)?),
+            ConditionOperator::ToolNotIn => Self::ToolNotIn(names(value)?),
+            ConditionOperator::InCidr => Self::InCidr(CidrSet::compile(&names(value)?)?),
+            ConditionOperator::NotInCidr => Self::NotInCidr(CidrSet::compile(&names(value)?)?),
+            other => {
+                return Err(Error::validation(format!(
+                    "{} is not a comparison operator",
+                    other
+                )))
+            }
+        })
+    }
+
+    /// Get the operator the matcher was compiled from.
+    pub fn operator(&self) -> ConditionOperator {
+        match self {
+            Self::Equals(_) => ConditionOperator::Equals,
+            Self::NotEquals(_) => ConditionOperator::NotEquals,
+            Self::GreaterThan(_) => ConditionOperator::GreaterThan,
+            Self::GreaterThanOrEquals(_) => ConditionOperator::GreaterThanOrEquals,
+            Self::LessThan(_) => ConditionOperator::LessThan,
+            Self::LessThanOrEquals(_) => ConditionOperator::LessThanOrEquals,
+            Self::In(_) => ConditionOperator::In,
+            Self::NotIn(_) => ConditionOperator::NotIn,
+            Self::Contains(..) => ConditionOperator::Contains,
+            Self::StartsWith(_) => ConditionOperator::StartsWith,
+            Self::EndsWith(_) => ConditionOperator::EndsWith,
+            Self::Matches(_) => ConditionOperator::Matches,
+            Self::MatchesTemplate { .. } => ConditionOperator::MatchesTemplate,
+            Self::ToolIn(_) => ConditionOperator::ToolIn,
+            Self::ToolNotIn(_) => ConditionOperator::ToolNotIn,
+            Self::InCidr(_) => ConditionOperator::InCidr,
+            Self::NotInCidr(_) => ConditionOperator::NotInCidr,
+            Self::ArgumentMatches { .. } => ConditionOperator::ArgumentMatches,
+        }
+    }
+
+    /// Get the expected value as JSON.
+    pub fn expected(&self) -> Value {
+        match self {
+            Self::Equals(value) | Self::NotEquals(value) => condition_value_to_json(value),
+            Self::GreaterThan(bound)
+            | Self::GreaterThanOrEquals(bound)
+            | Self::LessThan(bound)
+            | Self::LessThanOrEquals(bound) => serde_json::json!(bound),
+            Self::In(values) | Self::NotIn(values) => {
+                Value::Array(values.iter().map(condition_value_to_json).collect())
+            }
+            Self::Contains(_, expected) => expected.clone(),
+            Self::StartsWith(s) | Self::EndsWith(s) => Value::String(s.clone()),
+            Self::Matches(regex) => Value::String(regex.as_str().to_string()),
+            Self::MatchesTemplate { templates, .. } => serde_json::json!(templates),
+            Self::ToolIn(names) | Self::ToolNotIn(names) => serde_json::json!(names),
+            Self::InCidr(ranges) | Self::NotInCidr(ranges) => serde_json::json!(ranges.ranges()),
+            Self::ArgumentMatches {
+                tool,
+                argument,
+                matcher,
+            } => serde_json::json!({
+                "tool": tool.as_deref().unwrap_or("*"),
+                "argument": argument.as_str(),
+                "expected": matcher.expected(),
+            }),
+        }
+    }
+
+    /// Test the value resolved for a field.
+    ///
+    /// A field with a wildcard resolves to the list of values it selects:
+    /// negated operators (`not_equals`, `not_in`, `not_in_cidr`) require
+    /// every value to match and all other operators any value.
+    pub fn matches_field(&self, field: &FieldPath, actual: &Value) -> Result<bool> {
+        let items = match actual {
+            Value::Array(items) if field.has_wildcard() => items,
+            actual => return self.matches(actual),
+        };
+        if matches!(self, Self::NotEquals(_) | Self::NotIn(_) | Self::NotInCidr(_)) {
+            for item in items {
+                if !self.matches(item)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        } else {
+            for item in items {
+                if self.matches(item)? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+    }
+
+    /// Test a field value.
+    pub fn matches(&self, actual: &Value) -> Result<bool> {
+        Ok(match self {
+            Self::Equals(expected) => values_equal(actual, expected),
+            Self::NotEquals(expected) => !values_equal(actual, expected),
+            Self::GreaterThan(bound) => numeric(actual)? > *bound,
+            Self::GreaterThanOrEquals(bound) => numeric(actual)? >= *bound,
+            Self::LessThan(bound) => numeric(actual)? < *bound,
+            Self::LessThanOrEquals(bound) => numeric(actual)? <= *bound,
+            Self::In(values) => values.iter().any(|v| values_equal(actual, v)),
+            Self::NotIn(values) => !values.iter().any(|v| values_equal(actual, v)),
+            Self::Contains(expected, expected_json) => match (actual, expected) {
+                (Value::String(s), ConditionValue::String(needle)) => s.contains(needle.as_str()),
+                (Value::Array(items), _) => items.contains(expected_json),
+                _ => false,
+            },
+            Self::StartsWith(prefix) => actual
+                .as_str()
+                .is_some_and(|s| s.starts_with(prefix.as_str())),
+            Self::EndsWith(suffix) => actual
+                .as_str()
+                .is_some_and(|s| s.ends_with(suffix.as_str())),
+            Self::Matches(regex) => actual.as_str().is_some_and(|s| regex.is_match(s)),
+            Self::MatchesTemplate { regex, .. } => {
+                actual.as_str().is_some_and(|s| regex.is_match(s))
+            }
+            Self::ToolIn(names) => tools(actual).any(|tool| listed(names, tool)),
+            Self::ToolNotIn(names) => tools(actual).any(|tool| !listed(names, tool)),
+            // Values that are not addresses are in no range
+            Self::InCidr(ranges) => address(actual).is_some_and(|ip| ranges.contains(ip)),
+            Self::NotInCidr(ranges) => !address(actual).is_some_and(|ip| ranges.contains(ip)),
+            Self::ArgumentMatches {
+                tool,
+                argument,
+                matcher,
+            } => {
+                for call in items(actual) {
+                    if tool.is_some() && tool_name(call) != tool.as_deref() {
+                        continue;
+                    }
+                    let value = call.get("arguments").and_then(|a| argument.select(a, 0));
+                    if let Some(value) = value {
+                        if matcher.matches_field(argument, &value)? {
+                            return Ok(true);
+                        }
+                    }
+                }
+                false
+            }
+        })
+    }
+}
+
+/// Get the elements of a list value, or the value itself.
+fn items(value: &Value) -> &[Value] {
+    match value {
+        Value::Array(items) => items,
+        value => std::slice::from_ref(value),
+    }
+}
+
+/// Parse an IP address value.
+fn address(value: &Value) -> Option<IpAddr> {
+    value.as_str()?.trim().parse().ok()
+}
+
+/// Get the tool names of a tool list, tool call list or name.
+fn tools(value: &Value) -> impl Iterator<Item = Option<&str>> {
+    items(value).iter().map(tool_name)
+}
+
+/// Check whether a tool name is one of the listed names.
+fn listed(names: &[String], tool: Option<&str>) -> bool {
+    tool.is_some_and(|tool| names.iter().any(|name| name == tool))
+}
+
+/// Translate a template into a regex: text is literal and each
+/// `{placeholder}` matches any text.
+fn template(template: &str) -> String {
+    let mut pattern = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        match rest[open..].find('}') {
+            Some(close) => {
+                pattern.push_str(&regex::escape(&rest[..open]));
+                pattern.push_str(".*?");
+                rest = &rest[open + close + 1..];
+            }
+            None => break,
+        }
+    }
+    pattern.push_str(&regex::escape(rest));
+    pattern
+}
+
+/// Compile a condition regex.
+pub(super) fn regex(pattern: &str) -> Result<Regex> {
+    RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|e| Error::expression_with_expr(format!("Invalid regex: {}", e), pattern))
+}
+
+fn field(condition: &Condition) -> Result<FieldPath> {
+    let field = condition.field.as_deref().ok_or_else(|| {
+        Error::validation(format!("{} operator requires a field", condition.operator))
+    })?;
+    FieldPath::parse(field)
+}
+
+/// Read a context value as a number.
+fn numeric(actual: &Value) -> Result<f64> {
+    actual
+        .as_f64()
+        .ok_or_else(|| Error::evaluation("Expected numeric value for comparison"))
+}
+
+/// Check if a JSON value equals a condition value.
+fn values_equal(actual: &Value, expected: &ConditionValue) -> bool {
+    match (actual, expected) {
+        (Value::String(a), ConditionValue::String(e)) => a == e,
+        (Value::Number(a), ConditionValue::Integer(e)) => {
+            a.as_i64().map(|n| n == *e).unwrap_or(false)
+        }
+        (Value::Number(a), ConditionValue::Float(e)) => a
+            .as_f64()
+            .map(|n| (n - e).abs() < f64::EPSILON)
+            .unwrap_or(false),
+        (Value::Bool(a), ConditionValue::Boolean(e)) => a == e,
+        (Value::Null, ConditionValue::Null) => true,
+        (Value::Array(actual_arr), ConditionValue::Array(expected_arr)) => {
+            if actual_arr.len() != expected_arr.len() {
+                return false;
+            }
+            actual_arr
+                .iter()
+                .zip(expected_arr.iter())
+                .all(|(a, e)| values_equal(a, e))
+        }
+        _ => false,
+    }
+}
+
+/// Convert a ConditionValue to a JSON value.
+fn condition_value_to_json(value: &ConditionValue) -> Value {
+    match value {
+        ConditionValue::String(s) => Value::String(s.clone()),
+        ConditionValue::Integer(n) => serde_json::json!(n),
+        ConditionValue::Float(n) => serde_json::json!(n),
+        ConditionValue::Boolean(b) => Value::Bool(*b),
+        ConditionValue::Array(arr) => {
+            Value::Array(arr.iter().map(condition_value_to_json).collect())
+        }
+        ConditionValue::Null => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{ChatMessage, EvaluationContext, ToolCall, ToolDefinition};
+    use crate::policy::Action;
+
+    #[test]
+    fn test_rules_sorted_and_filtered() {
+        let policy = Policy::builder("p")
+            .rule(
+                PolicyRule::new(
+                    "low",
+                    "Low",
+                    Condition::exists("llm.model"),
+                    Action::allow(),
+                )
+                .with_priority(1),
+            )
+            .rule(
+                PolicyRule::new(
+                    "high",
+                    "High",
+                    Condition::exists("llm.model"),
+                    Action::allow(),
+                )
+                .with_priority(10),
+            )
+            .rule(
+                PolicyRule::new(
+                    "tie",
+                    "Tie",
+                    Condition::exists("llm.model"),
+                    Action::allow(),
+                )
+                .with_priority(1),
+            )
+            .rule(
+                PolicyRule::new(
+                    "off",
+                    "Off",
+                    Condition::exists("llm.model"),
+                    Action::allow(),
+                )
+                .with_enabled(false),
+            )
+            .build();
+
+        let compiled = CompiledPolicy::compile(policy).unwrap();
+        let ids: Vec<_> = compiled.rules().map(|(rule, _)| rule.id.as_str()).collect();
+        assert_eq!(ids, vec!["high", "low", "tie"]);
+    }
+
+    #[test]
+    fn test_invalid_regex_rejected_at_compile() {
+        let policy = Policy::builder("p")
+            .rule(PolicyRule::new(
+                "bad-regex",
+                "Bad",
+                Condition::matches("llm.prompt", "(unclosed"),
+                Action::deny("no"),
+            ))
+            .build();
+
+        let err = CompiledPolicy::compile(policy).unwrap_err();
+        assert!(err.to_string().contains("bad-regex"));
+        assert!(err.to_string().contains("Invalid regex"));
+    }
+
+    #[test]
+    fn test_type_errors_rejected_at_compile() {
+        assert!(
+            CompiledCondition::compile(&Condition::greater_than("llm.maxTokens", "many")).is_err()
+        );
+        assert!(CompiledCondition::compile(&Condition {
+            operator: ConditionOperator::In,
+            field: Some("llm.model".into()),
+            value: Some("gpt-4".into()),
+            conditions: vec![],
+            timezone: None,
+        })
+        .is_err());
+        assert!(CompiledCondition::compile(&Condition::equals("llm..model", "gpt-4")).is_err());
+        assert!(CompiledCondition::compile(&Condition::not_equals("llm.model", "gpt-4")).is_ok());
+        assert!(CompiledCondition::compile(&Condition::expression("llm.model ==")).is_err());
+    }
+
+    #[test]
+    fn test_compiled_regex_evaluation() {
+        let condition =
+            CompiledCondition::compile(&Condition::matches("llm.model", "^gpt-4")).unwrap();
+        let context = EvaluationContext::builder()
+            .with_model("gpt-4-turbo")
+            .build();
+        assert!(condition.evaluate(&EvaluationScope::new(&context)).unwrap());
+
+        let context = EvaluationContext::builder().with_model("claude-3").build();
+        assert!(!condition.evaluate(&EvaluationScope::new(&context)).unwrap());
+    }
+
+    #[test]
+    fn test_nested_paths() {
+        let context = EvaluationContext::builder()
+            .with_user("u", None, vec!["admin".to_string(), "dev".to_string()])
+            .with_metadata(
+                "messages",
+                serde_json::json!([
+                    {"role": "system", "content": "be brief"},
+                    {"role": "user", "content": "my password is hunter2"},
+                ]),
+            )
+            .build();
+        let scope = EvaluationScope::new(&context);
+        let holds = |condition: Condition| {
+            CompiledCondition::compile(&condition)
+                .unwrap()
+                .evaluate(&scope)
+                .unwrap()
+        };
+
+        assert!(holds(Condition::equals("metadata.messages[1].role", "user")));
+        assert!(holds(Condition::equals("user.roles.#", 2)));
+        assert!(holds(Condition::greater_than("metadata.messages.#", 1)));
+        assert!(holds(Condition::matches("metadata.messages[*].content", "password")));
+        assert!(holds(Condition::is_in(
+            "metadata.messages[*].role",
+            vec!["tool".into(), "system".into()],
+        )));
+        assert!(!holds(Condition::not_equals("metadata.messages[*].role", "system")));
+        assert!(holds(Condition::exists("metadata.messages[0].content")));
+        assert!(!holds(Condition::exists("metadata.messages[*].name")));
+    }
+
+    #[test]
+    fn test_message_conditions() {
+        let context = EvaluationContext::builder()
+            .with_message(ChatMessage::system("You are Ada, a helpful assistant."))
+            .with_message(ChatMessage::user("hello"))
+            .with_message(ChatMessage::assistant("Hi! How can I help?"))
+            .with_message(ChatMessage::user("my password is hunter2"))
+            .build();
+        let scope = EvaluationScope::new(&context);
+        let holds = |condition: Condition| {
+            CompiledCondition::compile(&condition)
+                .unwrap()
+                .evaluate(&scope)
+                .unwrap()
+        };
+
+        assert!(holds(Condition::matches("llm.messages[role=user].content", "password")));
+        assert!(!holds(Condition::matches("llm.messages[role=assistant].content", "password")));
+        assert!(holds(Condition::matches_template(
+            "llm.system_prompt",
+            ["You are {name}, a helpful assistant.", "Be brief."],
+        )));
+        assert!(!holds(Condition::matches_template(
+            "llm.messages[role=system].content",
+            ["You are {name}. Answer in {language}."],
+        )));
+        assert!(holds(Condition::greater_than("llm.turns", 1)));
+        assert!(holds(Condition::equals("llm.messages[role=user].#", 2)));
+        assert!(holds(Condition::equals("llm.messages.#", 4)));
+    }
+
+    #[test]
+    fn test_tool_operators() {
+        let context = EvaluationContext::builder()
+            .with_tool(ToolDefinition::new("search"))
+            .with_tool(ToolDefinition::new("shell"))
+            .with_tool_call(ToolCall::new("search", serde_json::json!({"query": "rust"})))
+            .with_tool_call(ToolCall::new(
+                "shell",
+                serde_json::json!({"command": "rm -rf /", "env": ["PATH=/bin"]}),
+            ))
+            .build();
+        let scope = EvaluationScope::new(&context);
+        let holds = |condition: Condition| {
+            CompiledCondition::compile(&condition)
+                .unwrap()
+                .evaluate(&scope)
+                .unwrap()
+        };
+
+        assert!(holds(Condition::tool_in("llm.tools", ["shell", "exec"])));
+        assert!(!holds(Condition::tool_in("llm.tool_calls", ["exec"])));
+        assert!(holds(Condition::tool_not_in("llm.tools", ["search"])));
+        assert!(!holds(Condition::tool_not_in("llm.tool_calls", ["search", "shell"])));
+        assert!(holds(Condition::tool_in("llm.tool_calls[0]", ["search"])));
+        assert!(holds(Condition::tool_not_in("llm.tool_calls[*].name", ["search"])));
+
+        assert!(holds(Condition::argument_matches("shell", "command", "^rm ")));
+        assert!(holds(Condition::argument_matches("*", "env[*]", "^PATH=")));
+        assert!(!holds(Condition::argument_matches("search", "command", "rm")));
+        assert!(!holds(Condition::argument_matches("shell", "query", ".")));
+
+        // Requests without tools never match
+        let empty = EvaluationContext::default();
+        let scope = EvaluationScope::new(&empty);
+        let condition = CompiledCondition::compile(&Condition::tool_not_in("llm.tools", ["x"]));
+        assert!(!condition.unwrap().evaluate(&scope).unwrap());
+        assert!(CompiledCondition::compile(&Condition {
+            field: Some("shell".to_string()),
+            ..Condition::argument_matches("shell", "command", "rm")
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_cidr_operators() {
+        let context = EvaluationContext::builder()
+            .with_request_details("req-1", Some("10.20.30.40".to_string()), None)
+            .build();
+        let scope = EvaluationScope::new(&context);
+        let holds = |condition: Condition| {
+            CompiledCondition::compile(&condition)
+                .unwrap()
+                .evaluate(&scope)
+                .unwrap()
+        };
+
+        let corporate = ["192.168.0.0/16", "10.0.0.0/8", "fd00::/8"];
+        assert!(holds(Condition::in_cidr("request.ip_address", corporate)));
+        assert!(holds(Condition::in_cidr("request.ipAddress", ["10.20.30.40"])));
+        assert!(!holds(Condition::not_in_cidr("request.ip_address", corporate)));
+        assert!(holds(Condition::not_in_cidr("request.ip_address", ["10.20.30.0/31"])));
+        // Values that are not addresses are outside every range
+        assert!(holds(Condition::not_in_cidr("request.id", corporate)));
+        assert!(!holds(Condition::in_cidr("request.id", ["0.0.0.0/0", "::/0"])));
+
+        assert!(CompiledCondition::compile(&Condition::in_cidr("request.ip_address", ["10/8"]))
+            .is_err());
+    }
+}
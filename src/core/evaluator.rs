@@ -1,17 +1,42 @@
 //! Policy evaluator implementation.
 
-use crate::api::{EvaluationContext, PolicyDecision};
+use crate::api::{
+    DecisionObligation, EvaluationContext, EvaluationTrace, FieldModification, ModificationConflict,
+    PolicyDecision, RuleAction, TraceStep,
+};
+use super::combiner::Combiner;
+use super::{Clock, CompiledCondition, CompiledPolicy, Deadline, EvaluationScope, SystemClock};
 use crate::policy::{
-    Condition, ConditionOperator, ConditionValue, DecisionType, Policy, PolicyRule,
+    Action, CombiningAlgorithm, Condition, DecisionType, Obligation, Policy, PolicyRule,
 };
 use crate::Result;
 
-use std::time::Instant;
+use parking_lot::Mutex;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
 
 /// The policy evaluator that processes policies against contexts.
+#[derive(Debug, Clone)]
 pub struct Evaluator {
     /// Whether to include trace information in decisions
     enable_tracing: bool,
+    /// Time budget of a single CEL expression
+    cel_budget: Option<Duration>,
+    /// How the results of different policies are combined
+    combining_algorithm: CombiningAlgorithm,
+    /// Time limit for a whole evaluation
+    max_evaluation_time: Option<Duration>,
+    /// Number of threads evaluating policies of one request
+    parallelism: usize,
+    /// Threads shared by the parallel evaluations of all requests
+    pool: Option<Arc<ThreadPool>>,
+    /// Current time for time conditions on requests without a timestamp
+    clock: Arc<dyn Clock>,
 }
 
 impl Evaluator {
@@ -19,6 +44,12 @@ impl Evaluator {
     pub fn new() -> Self {
         Self {
             enable_tracing: false,
+            cel_budget: None,
+            combining_algorithm: CombiningAlgorithm::default(),
+            max_evaluation_time: None,
+            parallelism: 1,
+            pool: None,
+            clock: Arc::new(SystemClock),
         }
     }
 
@@ -28,337 +59,532 @@ impl Evaluator {
         self
     }
 
+    /// Check whether every evaluation records a trace.
+    pub fn is_tracing(&self) -> bool {
+        self.enable_tracing
+    }
+
+    /// Set the time budget of a single CEL expression.
+    ///
+    /// The budget is checked once an expression finishes, so it does not
+    /// interrupt a running expression; see
+    /// [`CelExpression::evaluate`](super::CelExpression::evaluate).
+    pub fn with_cel_budget(mut self, budget: Duration) -> Self {
+        self.cel_budget = Some(budget);
+        self
+    }
+
+    /// Limit the time a whole evaluation may take.
+    ///
+    /// The deadline is checked before every policy and rule and after every
+    /// rule condition; an evaluation that passes it fails with
+    /// [`Error::Timeout`](crate::Error::Timeout) naming the policy that was
+    /// being evaluated.
+    pub fn with_max_evaluation_time(mut self, limit: Duration) -> Self {
+        self.max_evaluation_time = Some(limit);
+        self
+    }
+
+    /// Evaluate the policies of a request on up to `threads` threads.
+    ///
+    /// The threads are started once and shared by every evaluation of this
+    /// evaluator and its clones, so concurrent requests never run on more
+    /// than `threads` threads between them. A pool that cannot be started
+    /// leaves evaluation sequential.
+    pub fn with_parallelism(mut self, threads: usize) -> Self {
+        self.parallelism = threads.max(1);
+        self.pool = (self.parallelism > 1)
+            .then(|| {
+                ThreadPoolBuilder::new()
+                    .num_threads(self.parallelism)
+                    .thread_name(|index| format!("policy-eval-{}", index))
+                    .build()
+                    .map_err(|e| warn!("Evaluating sequentially, no worker threads: {}", e))
+                    .ok()
+            })
+            .flatten()
+            .map(Arc::new);
+        self
+    }
+
+    /// Read the current time for time conditions from the given clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Set how the results of different policies are combined.
+    pub fn with_combining_algorithm(mut self, algorithm: CombiningAlgorithm) -> Self {
+        self.combining_algorithm = algorithm;
+        self
+    }
+
     /// Evaluate policies against the given context.
     ///
     /// Policies are evaluated in priority order (highest first).
     /// Rules within each policy are also evaluated in priority order.
-    /// The first deny decision takes precedence.
-    pub fn evaluate(&self, policies: &[Policy], context: &EvaluationContext) -> Result<PolicyDecision> {
-        let start = Instant::now();
-        let mut result = PolicyDecision::allow();
-        let mut matched_policies = Vec::new();
-        let mut matched_rules = Vec::new();
+    /// Results are combined with the evaluator's combining algorithm across
+    /// policies and each policy's own algorithm across its rules; by
+    /// default the first deny decision takes precedence.
+    pub fn evaluate<P: Borrow<Policy>>(
+        &self,
+        policies: &[P],
+        context: &EvaluationContext,
+    ) -> Result<PolicyDecision> {
+        self.evaluate_with_tracing(policies, context, self.enable_tracing)
+    }
 
-        for policy in policies {
-            if !policy.enabled {
-                continue;
-            }
+    /// Evaluate policies, recording an evaluation trace when `tracing` is set.
+    ///
+    /// This enables tracing for a single call on an evaluator that does not
+    /// trace every evaluation. Policies are compiled first; use
+    /// [`Evaluator::evaluate_compiled`] to evaluate policies compiled ahead
+    /// of time.
+    pub fn evaluate_with_tracing<P: Borrow<Policy>>(
+        &self,
+        policies: &[P],
+        context: &EvaluationContext,
+        tracing: bool,
+    ) -> Result<PolicyDecision> {
+        let compiled = policies
+            .iter()
+            .map(|p| CompiledPolicy::compile(p.borrow().clone()))
+            .collect::<Result<Vec<_>>>()?;
+        self.evaluate_compiled(&compiled, context, tracing)
+    }
 
-            let policy_result = self.evaluate_policy(policy, context)?;
+    /// Evaluate compiled policies in the given order.
+    ///
+    /// A trace is recorded when `tracing` is set or the evaluator was
+    /// created [`with_tracing`](Evaluator::with_tracing).
+    ///
+    /// With [`parallelism`](Evaluator::with_parallelism) above one, large
+    /// policy sets are evaluated on several threads. Results are merged in
+    /// the given order and evaluation stops where sequential evaluation
+    /// would, so the decision is the same either way.
+    pub fn evaluate_compiled<P: Borrow<CompiledPolicy>>(
+        &self,
+        policies: &[P],
+        context: &EvaluationContext,
+        tracing: bool,
+    ) -> Result<PolicyDecision> {
+        let deadline = self.max_evaluation_time.map(Deadline::after);
+        self.evaluate_compiled_until(policies, context, tracing, deadline)
+    }
 
-            if policy_result.decision == DecisionType::Deny {
-                // Deny takes precedence
-                result = policy_result;
-                result.matched_policies = vec![policy.id.clone()];
-                break;
+    /// Evaluate compiled policies, failing with
+    /// [`Error::Timeout`](crate::Error::Timeout) once `deadline` passes.
+    ///
+    /// This lets a caller that spends part of a request's time budget
+    /// elsewhere, such as resolving attributes, give the evaluation only
+    /// what is left of it. The evaluator's own
+    /// [`max_evaluation_time`](Evaluator::with_max_evaluation_time) is not
+    /// applied.
+    pub fn evaluate_compiled_until<P: Borrow<CompiledPolicy>>(
+        &self,
+        policies: &[P],
+        context: &EvaluationContext,
+        tracing: bool,
+        deadline: Option<Deadline>,
+    ) -> Result<PolicyDecision> {
+        let start = Instant::now();
+        let tracing = tracing || self.enable_tracing;
+        let policies: Vec<&CompiledPolicy> = policies
+            .iter()
+            .map(Borrow::borrow)
+            .filter(|policy| policy.enabled)
+            .collect();
+        // Read the clock once, so policies on different workers agree on it
+        let now = policies
+            .iter()
+            .any(|policy| policy.reads_clock())
+            .then(|| self.clock.now());
+        let new_scope = || {
+            let scope = EvaluationScope::new(context)
+                .with_cel_budget(self.cel_budget)
+                .with_deadline(deadline);
+            match now {
+                Some(now) => scope.with_now(now),
+                None => scope,
             }
-
-            if policy_result.decision == DecisionType::Warn {
-                // Collect warnings
-                if result.decision == DecisionType::Allow {
-                    result = policy_result;
+        };
+
+        let mut merger = Merger::new(self.combining_algorithm, tracing);
+        let pool = self
+            .pool
+            .as_deref()
+            .filter(|_| policies.len() >= MIN_PARALLEL_POLICIES);
+        if let Some(pool) = pool {
+            for outcome in self.evaluate_parallel(pool, &policies, &new_scope, tracing) {
+                // Policies after the one that settled the decision may be skipped
+                let Some(outcome) = outcome else { break };
+                if !merger.add(outcome)? {
+                    break;
                 }
-                matched_policies.push(policy.id.clone());
             }
-
-            if policy_result.decision == DecisionType::Modify {
-                // Merge modifications
-                result.decision = DecisionType::Modify;
-                for (key, value) in policy_result.modifications {
-                    result.modifications.insert(key, value);
+        } else {
+            let scope = new_scope();
+            for policy in policies {
+                if !merger.add(self.evaluate_outcome(policy, &scope, tracing))? {
+                    break;
                 }
-                matched_policies.push(policy.id.clone());
             }
-
-            matched_rules.extend(policy_result.matched_rules);
-        }
-
-        if !matched_policies.is_empty() {
-            result.matched_policies = matched_policies;
-        }
-        if !matched_rules.is_empty() {
-            result.matched_rules = matched_rules;
         }
 
+        let mut result = merger.finish(self.combining_algorithm);
         result.evaluation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
         Ok(result)
     }
 
-    /// Evaluate a single policy.
-    fn evaluate_policy(&self, policy: &Policy, context: &EvaluationContext) -> Result<PolicyDecision> {
-        let mut result = PolicyDecision::allow();
-        let mut matched_rules = Vec::new();
-
-        // Get enabled rules sorted by priority
-        let mut rules: Vec<_> = policy.enabled_rules().collect();
-        rules.sort_by(|a, b| b.priority.cmp(&a.priority));
-
-        for rule in rules {
-            let rule_matched = self.evaluate_condition(&rule.condition, context)?;
-
-            if rule_matched {
-                matched_rules.push(rule.id.clone());
-
-                // Apply the rule's action
-                let decision = match rule.action.decision {
-                    DecisionType::Allow => PolicyDecision::allow(),
-                    DecisionType::Deny => {
-                        let mut d = PolicyDecision::deny(
-                            rule.action.reason.clone().unwrap_or_else(|| {
-                                format!("Denied by rule: {}", rule.name)
-                            }),
-                        );
-                        d.metadata = rule
-                            .action
-                            .metadata
-                            .iter()
-                            .map(|(k, v)| (k.clone(), v.clone()))
-                            .collect();
-                        d
-                    }
-                    DecisionType::Warn => {
-                        let mut d = PolicyDecision::warn(
-                            rule.action.reason.clone().unwrap_or_else(|| {
-                                format!("Warning from rule: {}", rule.name)
-                            }),
-                        );
-                        d.metadata = rule
-                            .action
-                            .metadata
-                            .iter()
-                            .map(|(k, v)| (k.clone(), v.clone()))
-                            .collect();
-                        d
-                    }
-                    DecisionType::Modify => {
-                        let mut modifications = std::collections::HashMap::new();
-                        for modification in &rule.action.modifications {
-                            if let Some(value) = &modification.value {
-                                modifications.insert(modification.field.clone(), value.clone());
+    /// Evaluate policies on the worker pool.
+    ///
+    /// The calling thread waits for the workers. Workers take policies in
+    /// order and skip those after a policy whose result settles the
+    /// decision on its own. The outcomes are returned in policy order, with
+    /// `None` for skipped policies.
+    fn evaluate_parallel<'p, 'c>(
+        &self,
+        pool: &ThreadPool,
+        policies: &[&'p CompiledPolicy],
+        new_scope: &(dyn Fn() -> EvaluationScope<'c> + Sync),
+        tracing: bool,
+    ) -> Vec<Option<PolicyOutcome<'p>>> {
+        let next = AtomicUsize::new(0);
+        let settled_at = AtomicUsize::new(usize::MAX);
+        let workers = self.parallelism.min(policies.len());
+
+        let mut outcomes: Vec<Option<PolicyOutcome<'p>>> = std::iter::repeat_with(|| None)
+            .take(policies.len())
+            .collect();
+        let results = Mutex::new(Vec::with_capacity(policies.len()));
+        pool.scope(|workers_scope| {
+            for _ in 0..workers {
+                workers_scope.spawn(|_| {
+                    let scope = new_scope();
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        if index >= policies.len() || index > settled_at.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        let outcome = self.evaluate_outcome(policies[index], &scope, tracing);
+                        if let Ok(Some(decision)) = &outcome.result {
+                            if Combiner::decides(self.combining_algorithm, decision) {
+                                settled_at.fetch_min(index, Ordering::Relaxed);
                             }
                         }
-                        PolicyDecision::modify(modifications)
+                        done.push((index, outcome));
                     }
-                };
-
-                // Deny takes immediate precedence
-                if decision.decision == DecisionType::Deny {
-                    result = decision;
-                    result.matched_rules = matched_rules;
-                    return Ok(result);
-                }
-
-                // Update result for non-deny decisions
-                if result.decision == DecisionType::Allow {
-                    result = decision;
-                } else if result.decision == DecisionType::Modify && decision.decision == DecisionType::Modify {
-                    // Merge modifications
-                    for (key, value) in decision.modifications {
-                        result.modifications.insert(key, value);
-                    }
-                }
+                    results.lock().extend(done);
+                });
             }
-        }
+        });
 
-        result.matched_rules = matched_rules;
-        Ok(result)
+        for (index, outcome) in results.into_inner() {
+            outcomes[index] = Some(outcome);
+        }
+        outcomes
     }
 
-    /// Evaluate a condition against the context.
-    pub fn evaluate_condition(&self, condition: &Condition, context: &EvaluationContext) -> Result<bool> {
-        match condition.operator {
-            ConditionOperator::And => {
-                for nested in &condition.conditions {
-                    if !self.evaluate_condition(nested, context)? {
-                        return Ok(false);
-                    }
-                }
-                Ok(true)
-            }
-            ConditionOperator::Or => {
-                for nested in &condition.conditions {
-                    if self.evaluate_condition(nested, context)? {
-                        return Ok(true);
-                    }
-                }
-                Ok(false)
-            }
-            ConditionOperator::Not => {
-                if condition.conditions.is_empty() {
-                    return Err(crate::Error::evaluation("NOT condition requires a nested condition"));
-                }
-                Ok(!self.evaluate_condition(&condition.conditions[0], context)?)
+    /// Evaluate a policy, capturing its result and trace.
+    fn evaluate_outcome<'p>(
+        &self,
+        policy: &'p CompiledPolicy,
+        scope: &EvaluationScope<'_>,
+        tracing: bool,
+    ) -> PolicyOutcome<'p> {
+        let policy_start = Instant::now();
+        let mut trace = tracing.then(EvaluationTrace::new);
+
+        if let Some(mismatch) = policy.target().and_then(|t| t.mismatch(scope.context())) {
+            if let Some(trace) = trace.as_mut() {
+                trace.policies_skipped += 1;
+                trace.add_step(
+                    TraceStep::policy(&policy.id, "skipped", policy_start.elapsed())
+                        .with_field(mismatch.field.as_str())
+                        .with_actual(mismatch.actual)
+                        .with_expected(serde_json::json!(mismatch.expected)),
+                );
             }
-            _ => self.evaluate_comparison(condition, context),
+            return PolicyOutcome {
+                policy,
+                result: Ok(None),
+                trace,
+            };
         }
-    }
-
-    /// Evaluate a comparison condition.
-    fn evaluate_comparison(&self, condition: &Condition, context: &EvaluationContext) -> Result<bool> {
-        let field = condition.field.as_ref().ok_or_else(|| {
-            crate::Error::evaluation("Condition requires a field")
-        })?;
 
-        let context_value = context.get(field);
-
-        match condition.operator {
-            ConditionOperator::Exists => Ok(context_value.is_some()),
-            ConditionOperator::NotExists => Ok(context_value.is_none()),
-            _ => {
-                let expected = condition.value.as_ref().ok_or_else(|| {
-                    crate::Error::evaluation("Condition requires a value")
-                })?;
+        let result = scope
+            .check_deadline(&policy.id)
+            .and_then(|()| self.evaluate_policy(policy, scope, trace.as_mut()));
+
+        if let (Ok(result), Some(trace)) = (&result, trace.as_mut()) {
+            trace.policies_evaluated += 1;
+            trace.add_step(TraceStep::policy(
+                &policy.id,
+                result
+                    .as_ref()
+                    .map_or("not_applicable", |result| result.decision.as_str()),
+                policy_start.elapsed(),
+            ));
+        }
 
-                match context_value {
-                    Some(actual) => self.compare_values(&condition.operator, &actual, expected),
-                    None => Ok(false), // Field doesn't exist, comparison fails
-                }
-            }
+        PolicyOutcome {
+            policy,
+            result,
+            trace,
         }
     }
 
-    /// Compare two values using the given operator.
-    fn compare_values(
+    /// Evaluate a single policy, returning `None` when it does not apply.
+    fn evaluate_policy(
         &self,
-        operator: &ConditionOperator,
-        actual: &serde_json::Value,
-        expected: &ConditionValue,
-    ) -> Result<bool> {
-        match operator {
-            ConditionOperator::Equals => Ok(values_equal(actual, expected)),
-            ConditionOperator::NotEquals => Ok(!values_equal(actual, expected)),
-            ConditionOperator::GreaterThan => compare_numeric(actual, expected, |a, b| a > b),
-            ConditionOperator::GreaterThanOrEquals => compare_numeric(actual, expected, |a, b| a >= b),
-            ConditionOperator::LessThan => compare_numeric(actual, expected, |a, b| a < b),
-            ConditionOperator::LessThanOrEquals => compare_numeric(actual, expected, |a, b| a <= b),
-            ConditionOperator::In => {
-                if let ConditionValue::Array(arr) = expected {
-                    Ok(arr.iter().any(|v| values_equal(actual, v)))
-                } else {
-                    Ok(false)
-                }
-            }
-            ConditionOperator::NotIn => {
-                if let ConditionValue::Array(arr) = expected {
-                    Ok(!arr.iter().any(|v| values_equal(actual, v)))
-                } else {
-                    Ok(true)
-                }
-            }
-            ConditionOperator::Contains => {
-                if let (serde_json::Value::String(actual_str), ConditionValue::String(expected_str)) =
-                    (actual, expected)
-                {
-                    Ok(actual_str.contains(expected_str))
-                } else if let serde_json::Value::Array(arr) = actual {
-                    let expected_json = condition_value_to_json(expected);
-                    Ok(arr.contains(&expected_json))
-                } else {
-                    Ok(false)
-                }
-            }
-            ConditionOperator::StartsWith => {
-                if let (serde_json::Value::String(actual_str), ConditionValue::String(expected_str)) =
-                    (actual, expected)
-                {
-                    Ok(actual_str.starts_with(expected_str))
-                } else {
-                    Ok(false)
-                }
+        policy: &CompiledPolicy,
+        scope: &EvaluationScope<'_>,
+        mut trace: Option<&mut EvaluationTrace>,
+    ) -> Result<Option<PolicyDecision>> {
+        let mut combiner = Combiner::new(policy.combining_algorithm.unwrap_or_default());
+        let mut matched_rules = Vec::new();
+        let mut actions = Vec::new();
+        let mut obligations = Vec::new();
+
+        // Rules are pre-sorted by priority at compile time
+        for (rule, condition) in policy.rules() {
+            if combiner.is_decided() {
+                break;
             }
-            ConditionOperator::EndsWith => {
-                if let (serde_json::Value::String(actual_str), ConditionValue::String(expected_str)) =
-                    (actual, expected)
-                {
-                    Ok(actual_str.ends_with(expected_str))
-                } else {
-                    Ok(false)
-                }
+            scope.check_deadline(&policy.id)?;
+
+            let rule_start = Instant::now();
+            let outcome = match trace.as_deref_mut() {
+                Some(trace) => condition.evaluate_traced(scope, &rule.id, trace),
+                None => condition.evaluate(scope),
+            };
+            scope.check_deadline(&policy.id)?;
+
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.rules_evaluated += 1;
+                trace.add_step(
+                    TraceStep::rule(&rule.id, "", rule_start.elapsed()).with_outcome(&outcome),
+                );
             }
-            ConditionOperator::Matches => {
-                if let (serde_json::Value::String(actual_str), ConditionValue::String(pattern)) =
-                    (actual, expected)
-                {
-                    let regex = regex::Regex::new(pattern).map_err(|e| {
-                        crate::Error::expression_with_expr(
-                            format!("Invalid regex: {}", e),
-                            pattern.clone(),
-                        )
-                    })?;
-                    Ok(regex.is_match(actual_str))
-                } else {
-                    Ok(false)
-                }
+
+            let rule_matched = match outcome {
+                Ok(matched) => matched,
+                Err(e) => match &policy.fallback {
+                    Some(fallback) => {
+                        let mut decision = action_decision(&policy.id, rule, fallback);
+                        decision
+                            .metadata
+                            .insert("evaluation_error".to_string(), serde_json::json!(e.to_string()));
+                        push_obligations(&mut obligations, &policy.id, &rule.id, [fallback])?;
+                        decision.matched_rules = matched_rules;
+                        decision.actions = actions;
+                        decision.obligations = obligations;
+                        return Ok(Some(decision));
+                    }
+                    None => return Err(e),
+                },
+            };
+
+            if rule_matched {
+                matched_rules.push(rule.id.clone());
+                actions.extend(
+                    rule.actions
+                        .iter()
+                        .map(|action| RuleAction::new(&policy.id, &rule.id, action)),
+                );
+                push_obligations(
+                    &mut obligations,
+                    &policy.id,
+                    &rule.id,
+                    std::iter::once(&rule.action).chain(&rule.actions),
+                )?;
+                combiner.add(&rule.id, action_decision(&policy.id, rule, &rule.action))?;
             }
-            _ => Ok(false),
         }
+
+        let mut result = match (combiner.finish(), policy.default_decision) {
+            (Some((decision, _)), _) => decision,
+            (None, Some(DecisionType::NotApplicable)) | (None, None) => return Ok(None),
+            (None, Some(default)) => PolicyDecision::from_decision_type(
+                default,
+                format!("No rule matched in policy: {}", policy.id),
+            ),
+        };
+
+        result.matched_rules = matched_rules;
+        result.actions = actions;
+        result.obligations = obligations;
+        Ok(Some(result))
     }
-}
 
-impl Default for Evaluator {
-    fn default() -> Self {
-        Self::new()
+    /// Evaluate a condition against the context.
+    ///
+    /// The condition is compiled on every call; compiled policies evaluate
+    /// their conditions directly.
+    pub fn evaluate_condition(&self, condition: &Condition, context: &EvaluationContext) -> Result<bool> {
+        let scope = EvaluationScope::new(context)
+            .with_cel_budget(self.cel_budget)
+            .with_now(self.clock.now());
+        CompiledCondition::compile(condition)?.evaluate(&scope)
     }
 }
 
-/// Check if a JSON value equals a condition value.
-fn values_equal(actual: &serde_json::Value, expected: &ConditionValue) -> bool {
-    match (actual, expected) {
-        (serde_json::Value::String(a), ConditionValue::String(e)) => a == e,
-        (serde_json::Value::Number(a), ConditionValue::Integer(e)) => {
-            a.as_i64().map(|n| n == *e).unwrap_or(false)
+/// Build the decision produced by a rule action.
+fn action_decision(policy_id: &str, rule: &PolicyRule, action: &Action) -> PolicyDecision {
+    let metadata = || {
+        action
+            .metadata
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    };
+
+    match action.decision {
+        DecisionType::Allow => PolicyDecision::allow(),
+        DecisionType::NotApplicable => PolicyDecision::not_applicable(),
+        DecisionType::Deny => {
+            let mut d = PolicyDecision::deny(
+                action
+                    .reason
+                    .clone()
+                    .unwrap_or_else(|| format!("Denied by rule: {}", rule.name)),
+            );
+            d.metadata = metadata();
+            d
         }
-        (serde_json::Value::Number(a), ConditionValue::Float(e)) => {
-            a.as_f64().map(|n| (n - e).abs() < f64::EPSILON).unwrap_or(false)
+        DecisionType::Warn => {
+            let mut d = PolicyDecision::warn(
+                action
+                    .reason
+                    .clone()
+                    .unwrap_or_else(|| format!("Warning from rule: {}", rule.name)),
+            );
+            d.metadata = metadata();
+            d
         }
-        (serde_json::Value::Bool(a), ConditionValue::Boolean(e)) => a == e,
-        (serde_json::Value::Null, ConditionValue::Null) => true,
-        (serde_json::Value::Array(actual_arr), ConditionValue::Array(expected_arr)) => {
-            if actual_arr.len() != expected_arr.len() {
-                return false;
+        DecisionType::Modify => {
+            let mut modifications = HashMap::new();
+            for modification in &action.modifications {
+                if let Some(value) = &modification.value {
+                    modifications.insert(modification.field.clone(), value.clone());
+                }
             }
-            actual_arr
+            let mut d = PolicyDecision::modify(modifications);
+            d.field_modifications = action
+                .modifications
                 .iter()
-                .zip(expected_arr.iter())
-                .all(|(a, e)| values_equal(a, e))
+                .map(|m| FieldModification::new(policy_id, &rule.id, m.clone()))
+                .collect();
+            d
         }
-        _ => false,
     }
 }
 
-/// Compare numeric values using a comparison function.
-fn compare_numeric<F>(actual: &serde_json::Value, expected: &ConditionValue, cmp: F) -> Result<bool>
-where
-    F: Fn(f64, f64) -> bool,
-{
-    let actual_num = actual.as_f64().ok_or_else(|| {
-        crate::Error::evaluation("Expected numeric value for comparison")
-    })?;
-
-    let expected_num = match expected {
-        ConditionValue::Integer(n) => *n as f64,
-        ConditionValue::Float(n) => *n,
-        _ => {
-            return Err(crate::Error::evaluation(
-                "Expected numeric value for comparison",
-            ))
+/// Collect the obligations requested by a matched rule's actions.
+fn push_obligations<'a>(
+    obligations: &mut Vec<DecisionObligation>,
+    policy_id: &str,
+    rule_id: &str,
+    actions: impl IntoIterator<Item = &'a Action>,
+) -> Result<()> {
+    for action in actions {
+        if let Some(obligation) = Obligation::from_action(action)? {
+            obligations.push(DecisionObligation::new(
+                policy_id,
+                rule_id,
+                obligation,
+                Obligation::is_mandatory(action),
+            ));
         }
-    };
+    }
+    Ok(())
+}
+
+/// Policy sets smaller than this are evaluated sequentially; starting
+/// workers costs more than it saves.
+const MIN_PARALLEL_POLICIES: usize = 8;
 
-    Ok(cmp(actual_num, expected_num))
+/// The result of evaluating one policy, with its part of the trace.
+struct PolicyOutcome<'p> {
+    policy: &'p CompiledPolicy,
+    result: Result<Option<PolicyDecision>>,
+    trace: Option<EvaluationTrace>,
 }
 
-/// Convert a ConditionValue to a JSON value.
-fn condition_value_to_json(value: &ConditionValue) -> serde_json::Value {
-    match value {
-        ConditionValue::String(s) => serde_json::Value::String(s.clone()),
-        ConditionValue::Integer(n) => serde_json::json!(n),
-        ConditionValue::Float(n) => serde_json::json!(n),
-        ConditionValue::Boolean(b) => serde_json::Value::Bool(*b),
-        ConditionValue::Array(arr) => {
-            serde_json::Value::Array(arr.iter().map(condition_value_to_json).collect())
+/// Merges policy outcomes in priority order.
+struct Merger {
+    combiner: Combiner,
+    rule_algorithms: HashMap<String, CombiningAlgorithm>,
+    matched_rules: Vec<String>,
+    actions: Vec<RuleAction>,
+    obligations: Vec<DecisionObligation>,
+    trace: Option<EvaluationTrace>,
+}
+
+impl Merger {
+    fn new(algorithm: CombiningAlgorithm, tracing: bool) -> Self {
+        Self {
+            combiner: Combiner::new(algorithm),
+            rule_algorithms: HashMap::new(),
+            matched_rules: Vec::new(),
+            actions: Vec::new(),
+            obligations: Vec::new(),
+            trace: tracing.then(EvaluationTrace::new),
+        }
+    }
+
+    /// Add the next outcome, returning whether later policies still matter.
+    fn add(&mut self, outcome: PolicyOutcome<'_>) -> Result<bool> {
+        let PolicyOutcome {
+            policy,
+            result,
+            trace,
+        } = outcome;
+
+        if let (Some(trace), Some(part)) = (self.trace.as_mut(), trace) {
+            trace.policies_evaluated += part.policies_evaluated;
+            trace.policies_skipped += part.policies_skipped;
+            trace.rules_evaluated += part.rules_evaluated;
+            trace.steps.extend(part.steps);
         }
-        ConditionValue::Null => serde_json::Value::Null,
+
+        if let Some(mut decision) = result? {
+            self.matched_rules.append(&mut decision.matched_rules);
+            self.actions.append(&mut decision.actions);
+            self.obligations.append(&mut decision.obligations);
+            self.rule_algorithms.insert(
+                policy.id.clone(),
+                policy.combining_algorithm.unwrap_or_default(),
+            );
+            self.combiner.add(&policy.id, decision)?;
+        }
+        Ok(!self.combiner.is_decided())
+    }
+
+    fn finish(mut self, algorithm: CombiningAlgorithm) -> PolicyDecision {
+        let mut result = match self.combiner.finish() {
+            Some((mut decision, policy_ids)) => {
+                self.rule_algorithms.retain(|id, _| policy_ids.contains(id));
+                decision.matched_policies = policy_ids;
+                decision
+            }
+            None => PolicyDecision::not_applicable(),
+        };
+        result.matched_rules = self.matched_rules;
+        result.actions = self.actions;
+        result.obligations = self.obligations;
+        result.combining_algorithm = Some(algorithm);
+        result.rule_combining_algorithms = self.rule_algorithms;
+        result.modification_conflicts = ModificationConflict::detect(&result.field_modifications);
+        result.trace = self.trace;
+        result
+    }
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -366,7 +592,7 @@ fn condition_value_to_json(value: &ConditionValue) -> serde_json::Value {
 mod tests {
     use super::*;
     use crate::api::EvaluationContext;
-    use crate::policy::{Action, PolicyRule};
+    use crate::policy::{ActionType, PolicyTarget};
 
     fn sample_policy() -> Policy {
         Policy::builder("test-policy")
@@ -380,10 +606,7 @@ mod tests {
             .rule(PolicyRule::new(
                 "allow-admins",
                 "Allow admin users",
-                Condition::is_in(
-                    "user.roles",
-                    vec!["admin".into(), "superadmin".into()],
-                ),
+                Condition::contains("user.roles", "admin"),
                 Action::allow(),
             ))
             .build()
@@ -403,6 +626,18 @@ mod tests {
         assert_eq!(result.decision, DecisionType::Allow);
     }
 
+    #[test]
+    fn test_evaluator_not_applicable() {
+        let context = EvaluationContext::builder()
+            .with_user("user-123", None, vec!["viewer".to_string()])
+            .build();
+
+        let result = Evaluator::new().evaluate(&[sample_policy()], &context).unwrap();
+        assert_eq!(result.decision, DecisionType::NotApplicable);
+        assert!(result.allowed);
+        assert!(result.matched_policies.is_empty());
+    }
+
     #[test]
     fn test_condition_equals() {
         let evaluator = Evaluator::new();
@@ -479,4 +714,292 @@ mod tests {
         let condition = Condition::exists("user.id");
         assert!(!evaluator.evaluate_condition(&condition, &context).unwrap());
     }
+
+    #[test]
+    fn test_rule_actions_reported() {
+        let evaluator = Evaluator::new();
+        let policy = Policy::builder("budget")
+            .rule(
+                PolicyRule::new(
+                    "over-budget",
+                    "Over budget",
+                    Condition::greater_than("llm.maxTokens", 1000i64),
+                    Action::deny("Over budget"),
+                )
+                .with_action(Action {
+                    action_type: ActionType::Audit,
+                    ..Action::allow()
+                }),
+            )
+            .build();
+        let context = EvaluationContext::builder().with_max_tokens(2000).build();
+
+        let decision = evaluator.evaluate(&[policy], &context).unwrap();
+        assert_eq!(decision.decision, DecisionType::Deny);
+        assert_eq!(decision.actions.len(), 1);
+        assert_eq!(decision.actions[0].action_type, ActionType::Audit);
+        assert_eq!(decision.actions[0].rule_id, "over-budget");
+    }
+
+    #[test]
+    fn test_default_decision_and_fallback() {
+        let evaluator = Evaluator::new();
+        let context = EvaluationContext::builder().with_model("gpt-4").build();
+
+        let policy = Policy::builder("closed")
+            .rule(PolicyRule::new(
+                "admins",
+                "Admins",
+                Condition::equals("user.id", "admin"),
+                Action::allow(),
+            ))
+            .default_decision(DecisionType::Deny)
+            .build();
+        let decision = evaluator.evaluate(&[policy], &context).unwrap();
+        assert_eq!(decision.decision, DecisionType::Deny);
+
+        let failing = PolicyRule::new(
+            "failing",
+            "Failing",
+            Condition::greater_than("llm.model", 1i64),
+            Action::deny("unreachable"),
+        );
+        let policy = Policy::builder("strict").rule(failing.clone()).build();
+        assert!(evaluator.evaluate(&[policy], &context).is_err());
+
+        let policy = Policy::builder("lenient")
+            .rule(failing)
+            .fallback(Action::warn("failing open"))
+            .build();
+        let decision = evaluator.evaluate(&[policy], &context).unwrap();
+        assert_eq!(decision.decision, DecisionType::Warn);
+        assert!(decision.metadata.contains_key("evaluation_error"));
+    }
+
+    #[test]
+    fn test_combining_algorithms() {
+        let context = EvaluationContext::builder().with_model("gpt-4").build();
+        let deny = Policy::builder("deny-gpt4")
+            .priority(10)
+            .rule(PolicyRule::new(
+                "deny",
+                "Deny",
+                Condition::equals("llm.model", "gpt-4"),
+                Action::deny("No GPT-4"),
+            ))
+            .build();
+        let allow = Policy::builder("allow-gpt4")
+            .combining_algorithm(CombiningAlgorithm::FirstApplicable)
+            .rule(
+                PolicyRule::new(
+                    "allow",
+                    "Allow",
+                    Condition::equals("llm.model", "gpt-4"),
+                    Action::allow(),
+                )
+                .with_priority(1),
+            )
+            .rule(PolicyRule::new(
+                "deny-later",
+                "Deny later",
+                Condition::exists("llm.model"),
+                Action::deny("unreachable"),
+            ))
+            .build();
+        let policies = [deny, allow];
+
+        let decision = Evaluator::new().evaluate(&policies, &context).unwrap();
+        assert_eq!(decision.decision, DecisionType::Deny);
+        assert_eq!(decision.combining_algorithm, Some(CombiningAlgorithm::DenyOverrides));
+        assert_eq!(decision.matched_policies, vec!["deny-gpt4"]);
+
+        let decision = Evaluator::new()
+            .with_combining_algorithm(CombiningAlgorithm::PermitOverrides)
+            .evaluate(&policies, &context)
+            .unwrap();
+        assert_eq!(decision.decision, DecisionType::Allow);
+        assert_eq!(decision.matched_policies, vec!["allow-gpt4"]);
+        assert_eq!(decision.matched_rules, vec!["deny", "allow"]);
+        assert_eq!(
+            decision.rule_combining_algorithms.get("allow-gpt4"),
+            Some(&CombiningAlgorithm::FirstApplicable)
+        );
+
+        let result = Evaluator::new()
+            .with_combining_algorithm(CombiningAlgorithm::OnlyOneApplicable)
+            .evaluate(&policies, &context);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_evaluation_trace() {
+        let policy = Policy::builder("limits")
+            .rule(PolicyRule::new(
+                "large-gpt4",
+                "Large GPT-4 requests",
+                Condition::and(vec![
+                    Condition::equals("llm.model", "gpt-4"),
+                    Condition::greater_than("llm.max_tokens", 1000),
+                ]),
+                Action::deny("Too many tokens"),
+            ))
+            .build();
+        let context = EvaluationContext::builder()
+            .with_model("gpt-4")
+            .with_max_tokens(500)
+            .build();
+
+        let policies = [policy];
+        let untraced = Evaluator::new().evaluate(&policies, &context).unwrap();
+        assert!(untraced.trace.is_none());
+
+        let decision = Evaluator::new()
+            .with_tracing(true)
+            .evaluate(&policies, &context)
+            .unwrap();
+        let trace = decision.trace.unwrap();
+        assert_eq!(trace.policies_evaluated, 1);
+        assert_eq!(trace.rules_evaluated, 1);
+
+        let ids: Vec<_> = trace.steps.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["large-gpt4/0", "large-gpt4/1", "large-gpt4", "large-gpt4", "limits"]);
+
+        let model = &trace.steps[0];
+        assert_eq!(model.step_type, crate::api::TraceStepType::ConditionEvaluated);
+        assert_eq!(model.field.as_deref(), Some("llm.model"));
+        assert_eq!(model.actual, Some(serde_json::json!("gpt-4")));
+        assert_eq!(model.outcome, Some(true));
+
+        let tokens = &trace.steps[1];
+        assert_eq!(tokens.operator.as_deref(), Some("greater_than"));
+        assert_eq!(tokens.actual, Some(serde_json::json!(500)));
+        assert_eq!(tokens.expected, Some(serde_json::json!(1000.0)));
+        assert_eq!(tokens.outcome, Some(false));
+
+        assert_eq!(trace.steps[2].operator.as_deref(), Some("and"));
+        assert_eq!(trace.steps[3].step_type, crate::api::TraceStepType::RuleEvaluated);
+        assert_eq!(trace.steps[3].result, "not_matched");
+        assert_eq!(trace.steps[4].result, "not_applicable");
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let policies: Vec<_> = (0..40)
+            .map(|i| {
+                let action = match i % 7 {
+                    0 => Action::deny(format!("deny {}", i)),
+                    1 => Action::warn(format!("warn {}", i)),
+                    _ => Action::allow(),
+                };
+                Policy::builder(format!("policy-{:02}", i))
+                    .priority(100 - i)
+                    .rule(PolicyRule::new(
+                        "rule",
+                        "Rule",
+                        Condition::equals("llm.model", if i % 3 == 0 { "gpt-4" } else { "other" }),
+                        action,
+                    ))
+                    .build()
+            })
+            .collect();
+        let context = EvaluationContext::builder().with_model("gpt-4").build();
+
+        for algorithm in [
+            CombiningAlgorithm::DenyOverrides,
+            CombiningAlgorithm::PermitOverrides,
+            CombiningAlgorithm::FirstApplicable,
+        ] {
+            let sequential = Evaluator::new()
+                .with_combining_algorithm(algorithm)
+                .with_tracing(true)
+                .evaluate(&policies, &context)
+                .unwrap();
+            let parallel = Evaluator::new()
+                .with_combining_algorithm(algorithm)
+                .with_tracing(true)
+                .with_parallelism(4)
+                .evaluate(&policies, &context)
+                .unwrap();
+
+            assert_eq!(parallel.decision, sequential.decision);
+            assert_eq!(parallel.reason, sequential.reason);
+            assert_eq!(parallel.matched_policies, sequential.matched_policies);
+            assert_eq!(parallel.matched_rules, sequential.matched_rules);
+            let steps = |d: &PolicyDecision| {
+                d.trace.as_ref().unwrap().steps.iter().map(|s| s.id.clone()).collect::<Vec<_>>()
+            };
+            assert_eq!(steps(&parallel), steps(&sequential));
+        }
+    }
+
+    /// A clock that moves an hour forward on every read.
+    #[derive(Debug, Default)]
+    struct AdvancingClock(AtomicUsize);
+
+    impl Clock for AdvancingClock {
+        fn now(&self) -> chrono::DateTime<chrono::Utc> {
+            let reads = self.0.fetch_add(1, Ordering::SeqCst) as i64;
+            // 09:30 UTC on the first read
+            chrono::DateTime::from_timestamp(1_751_794_200 + reads * 3600, 0).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_clock_read_once_per_evaluation() {
+        let policies: Vec<_> = (0..MIN_PARALLEL_POLICIES * 2)
+            .map(|i| {
+                Policy::builder(format!("policy-{:02}", i))
+                    .rule(PolicyRule::new(
+                        "morning",
+                        "Between nine and ten",
+                        Condition::hour_between(9, 10),
+                        Action::warn("Morning"),
+                    ))
+                    .build()
+            })
+            .collect();
+        let clock = Arc::new(AdvancingClock::default());
+        let evaluator = Evaluator::new()
+            .with_parallelism(4)
+            .with_clock(clock.clone());
+
+        let decision = evaluator
+            .evaluate(&policies, &EvaluationContext::default())
+            .unwrap();
+        assert_eq!(decision.decision, DecisionType::Warn);
+        assert_eq!(decision.matched_rules.len(), policies.len());
+        assert_eq!(clock.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_target_skips_policy() {
+        let policy = Policy::builder("openai-only")
+            .target(PolicyTarget::new().with("llm.provider", ["openai"]))
+            .rule(PolicyRule::new(
+                "deny-all",
+                "Deny all",
+                Condition::exists("llm.model"),
+                Action::deny("No"),
+            ))
+            .build();
+        let context = EvaluationContext::builder()
+            .with_provider("anthropic")
+            .with_model("claude")
+            .build();
+
+        let decision = Evaluator::new()
+            .with_tracing(true)
+            .evaluate(&[policy], &context)
+            .unwrap();
+        assert_eq!(decision.decision, DecisionType::NotApplicable);
+
+        let trace = decision.trace.unwrap();
+        assert_eq!(trace.policies_skipped, 1);
+        assert_eq!(trace.rules_evaluated, 0);
+        let step = &trace.steps[0];
+        assert_eq!(step.result, "skipped");
+        assert_eq!(step.field.as_deref(), Some("llm.provider"));
+        assert_eq!(step.actual, Some(serde_json::json!("anthropic")));
+        assert_eq!(step.expected, Some(serde_json::json!(["openai"])));
+    }
 }
@@ -0,0 +1,166 @@
+//! CEL expression conditions.
+
+use super::EvaluationScope;
+use crate::{Error, Result};
+
+use cel_interpreter::{ExecutionError, Program, Value};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// A CEL expression compiled when its policy is loaded.
+///
+/// The expression sees the context sections (`llm`, `user`, `team`,
+/// `project`, `request`, `response`, `metadata`) and the request's attribute
+/// namespaces as variables and must evaluate to a boolean. Reading a key
+/// that is not present makes the condition false, matching how comparison
+/// operators treat missing fields.
+#[derive(Clone)]
+pub struct CelExpression {
+    source: String,
+    program: Arc<Program>,
+}
+
+impl CelExpression {
+    /// Compile an expression.
+    pub fn compile(source: &str) -> Result<Self> {
+        let program = Program::compile(source).map_err(|e| {
+            Error::expression_with_expr(format!("Invalid CEL expression: {}", e), source)
+        })?;
+
+        Ok(Self {
+            source: source.to_string(),
+            program: Arc::new(program),
+        })
+    }
+
+    /// Get the expression source.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Get the top-level variables the expression reads.
+    pub fn variables(&self) -> Vec<String> {
+        let references = self.program.references();
+        references
+            .variables()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    /// Evaluate the expression.
+    ///
+    /// The scope's CEL budget is a post-hoc check, not a timeout: the
+    /// interpreter cannot be interrupted, so the expression always runs to
+    /// completion and a result produced after the budget ran out is
+    /// discarded and reported as [`Error::Timeout`]. CEL has no unbounded
+    /// loops, so the running time is bounded by the size of the expression
+    /// and the context; the evaluation deadline is checked again after
+    /// every rule condition.
+    pub fn evaluate(&self, scope: &EvaluationScope<'_>) -> Result<bool> {
+        let cel_context = scope.cel_context()?;
+        let start = Instant::now();
+        let result = self.program.execute(cel_context);
+
+        if let Some(budget) = scope.cel_budget() {
+            if start.elapsed() > budget {
+                return Err(Error::timeout(
+                    format!("CEL expression '{}' exceeded its time budget", self.source),
+                    budget.as_millis() as u64,
+                ));
+            }
+        }
+
+        match result {
+            Ok(Value::Bool(matched)) => Ok(matched),
+            Ok(other) => Err(Error::expression_with_expr(
+                format!("CEL expression must evaluate to a boolean, got {:?}", other),
+                self.source.clone(),
+            )),
+            Err(ExecutionError::NoSuchKey(_)) => Ok(false),
+            Err(e) => Err(Error::expression_with_expr(
+                e.to_string(),
+                self.source.clone(),
+            )),
+        }
+    }
+}
+
+impl fmt::Debug for CelExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CelExpression").field(&self.source).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::EvaluationContext;
+    use std::time::Duration;
+
+    fn context() -> EvaluationContext {
+        EvaluationContext::builder()
+            .with_model("gpt-4")
+            .with_max_tokens(2000)
+            .with_user_id("user-1")
+            .with_metadata("monthly_spent", serde_json::json!(120.5))
+            .with_metadata("monthly_limit", serde_json::json!(100.0))
+            .build()
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let context = context();
+        let scope = EvaluationScope::new(&context);
+
+        let expression = CelExpression::compile(
+            "llm.model == 'gpt-4' && metadata.monthly_spent >= metadata.monthly_limit",
+        )
+        .unwrap();
+        assert!(expression.evaluate(&scope).unwrap());
+
+        let expression = CelExpression::compile("llm.max_tokens > 4000").unwrap();
+        assert!(!expression.evaluate(&scope).unwrap());
+    }
+
+    #[test]
+    fn test_missing_key_is_false() {
+        let context = context();
+        let scope = EvaluationScope::new(&context);
+
+        let expression = CelExpression::compile("team.id == 'research'").unwrap();
+        assert!(!expression.evaluate(&scope).unwrap());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(CelExpression::compile("llm.model ==").is_err());
+
+        let context = context();
+        let scope = EvaluationScope::new(&context);
+        let expression = CelExpression::compile("llm.model").unwrap();
+        assert!(matches!(
+            expression.evaluate(&scope),
+            Err(Error::Expression { .. })
+        ));
+    }
+
+    #[test]
+    fn test_budget() {
+        let context = context();
+        let source = vec!["llm.model == 'gpt-4'"; 200].join(" && ");
+        let expression = CelExpression::compile(&source).unwrap();
+
+        // Two hundred lookups and comparisons take well over a microsecond
+        let scope =
+            EvaluationScope::new(&context).with_cel_budget(Some(Duration::from_micros(1)));
+        assert!(matches!(
+            expression.evaluate(&scope),
+            Err(Error::Timeout { .. })
+        ));
+
+        let scope = EvaluationScope::new(&context).with_cel_budget(Some(Duration::from_secs(5)));
+        assert!(expression.evaluate(&scope).unwrap());
+    }
+}
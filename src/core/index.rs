@@ -0,0 +1,82 @@
+//! Inverted index from target values to policies.
+
+use super::compiled::target_keys;
+use super::CompiledPolicy;
+use crate::api::{EvaluationContext, FieldPath};
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Finds the policies whose targets select a request.
+///
+/// Every (field, value) pair named by a target maps to the positions of the
+/// policies accepting it. A request resolves each indexed field once, and a
+/// policy applies when all of its selected fields hit; policies without a
+/// target always apply.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TargetIndex {
+    fields: Vec<FieldPath>,
+    postings: HashMap<(usize, String), Vec<usize>>,
+    /// Number of selectors of the policy at each position
+    required: Vec<usize>,
+}
+
+impl TargetIndex {
+    /// Index policies by their position in `policies`.
+    pub(crate) fn build(policies: &[Arc<CompiledPolicy>]) -> Self {
+        let mut index = Self::default();
+
+        for (position, policy) in policies.iter().enumerate() {
+            let selectors = policy.target().map_or(&[][..], |target| target.selectors());
+            index.required.push(selectors.len());
+
+            for (field, values) in selectors {
+                let field = match index.fields.iter().position(|f| f == field) {
+                    Some(field) => field,
+                    None => {
+                        index.fields.push(field.clone());
+                        index.fields.len() - 1
+                    }
+                };
+                for value in values {
+                    index
+                        .postings
+                        .entry((field, value.clone()))
+                        .or_default()
+                        .push(position);
+                }
+            }
+        }
+
+        index
+    }
+
+    /// Get the positions of the policies that apply to the context, in order.
+    pub(crate) fn applicable(&self, context: &EvaluationContext) -> Vec<usize> {
+        let mut hits = vec![0; self.required.len()];
+        // Field that last counted a hit for each position, so a list value
+        // with several accepted elements counts once per field
+        let mut counted = vec![usize::MAX; self.required.len()];
+
+        for (field_index, field) in self.fields.iter().enumerate() {
+            let Some(value) = context.resolve(field) else {
+                continue;
+            };
+            for key in target_keys(&value) {
+                let Some(positions) = self.postings.get(&(field_index, key)) else {
+                    continue;
+                };
+                for &position in positions {
+                    if counted[position] != field_index {
+                        counted[position] = field_index;
+                        hits[position] += 1;
+                    }
+                }
+            }
+        }
+
+        (0..self.required.len())
+            .filter(|&position| hits[position] == self.required[position])
+            .collect()
+    }
+}
@@ -1,5 +1,23 @@
 //! Core evaluation logic for the policy engine.
 
+mod cidr;
+mod combiner;
+mod compiled;
 mod evaluator;
+mod expression;
+mod index;
+mod policy_set;
+mod scope;
+mod stream;
+mod time;
 
+pub use cidr::CidrSet;
+pub use compiled::{
+    CompiledCondition, CompiledPolicy, CompiledRule, CompiledTarget, Matcher, TargetMismatch,
+};
 pub use evaluator::Evaluator;
+pub use expression::CelExpression;
+pub use policy_set::PolicySet;
+pub use scope::{Deadline, EvaluationScope};
+pub use stream::{ResponseStreamEvaluator, StreamOutput};
+pub use time::{Clock, FixedClock, SystemClock, TimeWindow};
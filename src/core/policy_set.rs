@@ -0,0 +1,265 @@
+//! Immutable policy set snapshots.
+
+use super::index::TargetIndex;
+use super::CompiledPolicy;
+use crate::api::EvaluationContext;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+/// An immutable snapshot of the loaded, compiled policies.
+///
+/// Snapshots are shared through `Arc` and never modified; changes produce a
+/// new snapshot with a higher generation. Enabled policies are sorted once
+/// when the snapshot is built, by descending priority and then by ID, and
+/// indexed by their targets.
+#[derive(Debug, Clone, Default)]
+pub struct PolicySet {
+    generation: u64,
+    by_id: HashMap<String, Arc<CompiledPolicy>>,
+    enabled: Vec<Arc<CompiledPolicy>>,
+    index: TargetIndex,
+    namespaces: BTreeSet<String>,
+    reads_clock: bool,
+}
+
+impl PolicySet {
+    /// Create a snapshot from the given policies.
+    ///
+    /// Later policies replace earlier ones with the same ID.
+    pub fn new(generation: u64, policies: impl IntoIterator<Item = Arc<CompiledPolicy>>) -> Self {
+        let by_id: HashMap<_, _> = policies.into_iter().map(|p| (p.id.clone(), p)).collect();
+
+        let mut enabled: Vec<_> = by_id.values().filter(|p| p.enabled).cloned().collect();
+        enabled.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.id.cmp(&b.id)));
+
+        let index = TargetIndex::build(&enabled);
+        let namespaces = enabled
+            .iter()
+            .flat_map(|p| p.namespaces().iter().cloned())
+            .collect();
+        let reads_clock = enabled.iter().any(|p| p.reads_clock());
+
+        Self {
+            generation,
+            by_id,
+            enabled,
+            index,
+            namespaces,
+            reads_clock,
+        }
+    }
+
+    /// Get the generation of this snapshot.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Get a policy by ID.
+    pub fn get(&self, id: &str) -> Option<&Arc<CompiledPolicy>> {
+        self.by_id.get(id)
+    }
+
+    /// Check whether a policy is present.
+    pub fn contains(&self, id: &str) -> bool {
+        self.by_id.contains_key(id)
+    }
+
+    /// Get the number of policies, enabled or not.
+    pub fn len(&self) -> usize {
+        self.by_id.len()
+    }
+
+    /// Check whether the snapshot is empty.
+    pub fn is_empty(&self) -> bool {
+        self.by_id.is_empty()
+    }
+
+    /// Iterate over all policy IDs.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.by_id.keys().map(String::as_str)
+    }
+
+    /// Iterate over all policies in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<CompiledPolicy>> {
+        self.by_id.values()
+    }
+
+    /// Get the enabled policies in evaluation order.
+    pub fn enabled(&self) -> &[Arc<CompiledPolicy>] {
+        &self.enabled
+    }
+
+    /// Get the top-level context sections read by the enabled policies.
+    pub fn namespaces(&self) -> &BTreeSet<String> {
+        &self.namespaces
+    }
+
+    /// Check whether an enabled policy has a time condition.
+    pub fn reads_clock(&self) -> bool {
+        self.reads_clock
+    }
+
+    /// Get the enabled policies whose targets select the context, in
+    /// evaluation order.
+    pub fn applicable(&self, context: &EvaluationContext) -> Vec<Arc<CompiledPolicy>> {
+        self.index
+            .applicable(context)
+            .into_iter()
+            .map(|position| self.enabled[position].clone())
+            .collect()
+    }
+
+    /// Get the requested enabled policies in evaluation order.
+    ///
+    /// Unknown IDs are an error; disabled policies are skipped.
+    pub fn select(&self, ids: &[String]) -> crate::Result<Vec<Arc<CompiledPolicy>>> {
+        for id in ids {
+            if !self.contains(id) {
+                return Err(crate::Error::validation_field(
+                    format!("Policy not found: {}", id),
+                    "policy_ids",
+                ));
+            }
+        }
+
+        Ok(self
+            .enabled
+            .iter()
+            .filter(|p| ids.contains(&p.id))
+            .cloned()
+            .collect())
+    }
+
+    /// Build the next snapshot with the given policies added or replaced.
+    pub fn with_policies(
+        &self,
+        generation: u64,
+        policies: impl IntoIterator<Item = CompiledPolicy>,
+    ) -> Self {
+        let existing = self.by_id.values().cloned();
+        Self::new(
+            generation,
+            existing.chain(policies.into_iter().map(Arc::new)),
+        )
+    }
+
+    /// Build the next snapshot without the given policy, if it is present.
+    pub fn without_policy(&self, generation: u64, id: &str) -> Option<Self> {
+        if !self.contains(id) {
+            return None;
+        }
+        let remaining = self.by_id.values().filter(|p| p.id != id).cloned();
+        Some(Self::new(generation, remaining))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::{Policy, PolicyTarget};
+
+    fn policy(id: &str, priority: i32, enabled: bool) -> CompiledPolicy {
+        let policy = Policy::builder(id)
+            .priority(priority)
+            .enabled(enabled)
+            .build();
+        CompiledPolicy::compile(policy).unwrap()
+    }
+
+    #[test]
+    fn test_enabled_sorted_by_priority_then_id() {
+        let set = PolicySet::new(
+            1,
+            [
+                policy("b", 10, true),
+                policy("a", 10, true),
+                policy("c", 50, true),
+                policy("d", 100, false),
+            ]
+            .into_iter()
+            .map(Arc::new),
+        );
+
+        let order: Vec<_> = set.enabled().iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(order, vec!["c", "a", "b"]);
+        assert_eq!(set.len(), 4);
+        assert_eq!(set.generation(), 1);
+    }
+
+    #[test]
+    fn test_next_generation_shares_policies() {
+        let first = PolicySet::new(1, [Arc::new(policy("a", 0, true))]);
+        let second = first.with_policies(2, [policy("b", 0, true)]);
+
+        assert_eq!(second.generation(), 2);
+        assert_eq!(second.len(), 2);
+        assert!(Arc::ptr_eq(
+            first.get("a").unwrap(),
+            second.get("a").unwrap()
+        ));
+        assert_eq!(first.len(), 1);
+
+        let third = second.without_policy(3, "a").unwrap();
+        assert_eq!(third.ids().collect::<Vec<_>>(), vec!["b"]);
+        assert!(third.without_policy(4, "a").is_none());
+    }
+
+    #[test]
+    fn test_select() {
+        let set = PolicySet::new(
+            1,
+            [
+                policy("a", 1, true),
+                policy("b", 2, true),
+                policy("c", 3, false),
+            ]
+            .into_iter()
+            .map(Arc::new),
+        );
+
+        let selected = set
+            .select(&["a".to_string(), "c".to_string(), "b".to_string()])
+            .unwrap();
+        let ids: Vec<_> = selected.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["b", "a"]);
+        assert!(set.select(&["missing".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_applicable() {
+        let targeted = |id: &str, target: PolicyTarget| {
+            Arc::new(CompiledPolicy::compile(Policy::builder(id).target(target).build()).unwrap())
+        };
+        let set = PolicySet::new(
+            1,
+            [
+                Arc::new(policy("everywhere", 0, true)),
+                targeted("openai", PolicyTarget::new().with("llm.provider", ["openai"])),
+                targeted(
+                    "openai-admins",
+                    PolicyTarget::new()
+                        .with("llm.provider", ["openai", "azure"])
+                        .with("user.roles", ["admin"]),
+                ),
+                targeted("prod", PolicyTarget::new().with("project.environment", ["production"])),
+            ],
+        );
+        let applicable = |context: &EvaluationContext| {
+            let mut ids: Vec<_> = set.applicable(context).iter().map(|p| p.id.clone()).collect();
+            ids.sort();
+            ids
+        };
+
+        let context = EvaluationContext::builder()
+            .with_provider("openai")
+            .with_user("u", None, vec!["admin".to_string(), "viewer".to_string()])
+            .build();
+        assert_eq!(applicable(&context), vec!["everywhere", "openai", "openai-admins"]);
+
+        let context = EvaluationContext::builder()
+            .with_provider("azure")
+            .with_user("u", None, vec!["viewer".to_string()])
+            .build();
+        assert_eq!(applicable(&context), vec!["everywhere"]);
+    }
+}
@@ -0,0 +1,161 @@
+//! Per-request evaluation state.
+
+use crate::api::EvaluationContext;
+use crate::{Error, Result};
+
+use chrono::{DateTime, Utc};
+use std::cell::OnceCell;
+use std::time::{Duration, Instant};
+
+/// Top-level context sections exposed to CEL expressions.
+///
+/// Absent sections are declared as empty maps so that `user.id` on a request
+/// without a user reads as a missing key rather than an undeclared variable.
+const CEL_VARIABLES: [&str; 7] = [
+    "llm", "user", "team", "project", "request", "response", "metadata",
+];
+
+/// The point in time by which an evaluation must finish.
+#[derive(Debug, Clone, Copy)]
+pub struct Deadline {
+    at: Instant,
+    limit: Duration,
+}
+
+impl Deadline {
+    /// Create a deadline `limit` from now.
+    pub fn after(limit: Duration) -> Self {
+        Self {
+            at: Instant::now() + limit,
+            limit,
+        }
+    }
+
+    /// Get the time limit the deadline was created with.
+    pub fn limit(&self) -> Duration {
+        self.limit
+    }
+
+    /// Get the time left before the deadline.
+    pub fn remaining(&self) -> Duration {
+        self.at.saturating_duration_since(Instant::now())
+    }
+
+    /// Check whether the deadline has passed.
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.at
+    }
+}
+
+/// State shared by every condition evaluated for one request.
+///
+/// Derived views of the context, such as the CEL activation, are built on
+/// first use and reused by later conditions. Every time condition sees the
+/// same current time: the one the scope was given, or the system time read
+/// on first use.
+pub struct EvaluationScope<'a> {
+    context: &'a EvaluationContext,
+    cel_budget: Option<Duration>,
+    deadline: Option<Deadline>,
+    now: OnceCell<DateTime<Utc>>,
+    cel_context: OnceCell<cel_interpreter::Context<'static>>,
+}
+
+impl<'a> EvaluationScope<'a> {
+    /// Create a scope for a context.
+    pub fn new(context: &'a EvaluationContext) -> Self {
+        Self {
+            context,
+            cel_budget: None,
+            deadline: None,
+            now: OnceCell::new(),
+            cel_context: OnceCell::new(),
+        }
+    }
+
+    /// Set the time budget of a single CEL expression; see
+    /// [`CelExpression::evaluate`](super::CelExpression::evaluate).
+    pub fn with_cel_budget(mut self, budget: Option<Duration>) -> Self {
+        self.cel_budget = budget;
+        self
+    }
+
+    /// Stop the evaluation once the deadline passes.
+    pub fn with_deadline(mut self, deadline: Option<Deadline>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Set the current time for time conditions.
+    pub fn with_now(mut self, now: DateTime<Utc>) -> Self {
+        self.now = OnceCell::from(now);
+        self
+    }
+
+    /// Fail with [`Error::Timeout`] if the deadline has passed while
+    /// evaluating the given policy.
+    pub fn check_deadline(&self, policy_id: &str) -> Result<()> {
+        match self.deadline {
+            Some(deadline) if deadline.is_expired() => Err(Error::timeout_in_policy(
+                format!("Evaluation deadline exceeded in policy '{}'", policy_id),
+                deadline.limit().as_millis() as u64,
+                policy_id,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Get the evaluation deadline.
+    pub fn deadline(&self) -> Option<Deadline> {
+        self.deadline
+    }
+
+    /// Get the evaluation context.
+    pub fn context(&self) -> &'a EvaluationContext {
+        self.context
+    }
+
+    /// Get the current time, reading the system clock on first use when the
+    /// scope was not given one.
+    pub fn now(&self) -> DateTime<Utc> {
+        *self.now.get_or_init(Utc::now)
+    }
+
+    /// Get the time budget of a single CEL expression.
+    pub fn cel_budget(&self) -> Option<Duration> {
+        self.cel_budget
+    }
+
+    /// Get the CEL activation for the context, building it on first use.
+    pub(crate) fn cel_context(&self) -> Result<&cel_interpreter::Context<'static>> {
+        if let Some(cel_context) = self.cel_context.get() {
+            return Ok(cel_context);
+        }
+
+        let mut sections = match self.context.to_json() {
+            serde_json::Value::Object(sections) => sections,
+            _ => serde_json::Map::new(),
+        };
+        let mut cel_context = cel_interpreter::Context::default();
+        for name in CEL_VARIABLES {
+            let value = sections
+                .remove(name)
+                .unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::new()));
+            cel_context.add_variable(name, value).map_err(|e| {
+                Error::evaluation(format!("Failed to expose '{}' to CEL: {}", name, e))
+            })?;
+        }
+        for (namespace, value) in &self.context.attributes {
+            if CEL_VARIABLES.contains(&namespace.as_str()) {
+                continue;
+            }
+            cel_context
+                .add_variable(namespace.as_str(), value.clone())
+                .map_err(|e| {
+                    Error::evaluation(format!("Failed to expose '{}' to CEL: {}", namespace, e))
+                })?;
+        }
+
+        Ok(self.cel_context.get_or_init(|| cel_context))
+    }
+}
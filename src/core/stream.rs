@@ -0,0 +1,371 @@
+//! Incremental evaluation of streamed model responses.
+
+use super::compiled::regex;
+use super::{CompiledPolicy, Evaluator};
+use crate::api::{canonical_field, EvaluationContext, PolicyDecision};
+use crate::config::loader::REDACTED;
+use crate::policy::{DecisionType, ModificationType, PolicyPhase};
+use crate::Result;
+
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::Arc;
+
+/// Bytes of received text held back by default.
+const DEFAULT_HOLDBACK: usize = 64;
+
+/// Bytes received between policy evaluations by default.
+const DEFAULT_EVALUATION_INTERVAL: usize = 16;
+
+/// Evaluates response-phase policies over a completion while it streams.
+///
+/// Each chunk is appended to the text received so far and the policies are
+/// evaluated against the whole text, so patterns spanning chunk boundaries
+/// are found. An evaluation therefore costs time linear in the text
+/// received so far; to bound the total cost of streams made of many small
+/// chunks, policies are only re-evaluated once
+/// [`evaluation_interval`](Self::with_evaluation_interval) bytes arrived
+/// since the last evaluation. Text is released with a delay of
+/// [`holdback`](Self::with_holdback) bytes: a deny stops the stream before
+/// the text that triggered it is released, and mask matches up to that
+/// length are masked even when they arrive over several chunks. Released
+/// text never ends inside such a match; only the held-back window is
+/// searched for them.
+///
+/// Masks of `response.content` are applied to the released text; other
+/// modifications are left to [`PolicyDecision::apply_response`] on the
+/// final response. A mask of the whole content replaces the rest of the
+/// stream with a placeholder and stops it.
+pub struct ResponseStreamEvaluator {
+    evaluator: Evaluator,
+    policies: Vec<Arc<CompiledPolicy>>,
+    /// Request context whose `response.content` is the text received so far
+    context: EvaluationContext,
+    /// Bytes of the received text released so far
+    released: usize,
+    /// Bytes of the received text the policies were last evaluated on
+    evaluated: usize,
+    holdback: usize,
+    evaluation_interval: usize,
+    /// Mask patterns requested by matched rules
+    masks: Vec<Regex>,
+    decision: PolicyDecision,
+    stopped: bool,
+}
+
+/// What a [`ResponseStreamEvaluator`] returns for a chunk.
+#[derive(Debug, Clone)]
+pub struct StreamOutput {
+    /// Text that may be sent on to the client, with masks applied
+    pub text: String,
+    /// Decision for the response received so far
+    pub decision: PolicyDecision,
+    /// Whether the stream was cut off or finished; no more text follows
+    pub stopped: bool,
+}
+
+impl ResponseStreamEvaluator {
+    /// Create a stream evaluator for a request.
+    ///
+    /// Only the policies that run in the response phase are evaluated.
+    pub fn new(
+        evaluator: Evaluator,
+        policies: Vec<Arc<CompiledPolicy>>,
+        context: &EvaluationContext,
+    ) -> Self {
+        let mut context = context.clone();
+        let response = context.response.get_or_insert_with(Default::default);
+        response.content = Some(String::new());
+        Self {
+            evaluator,
+            policies: policies
+                .into_iter()
+                .filter(|policy| policy.phase.includes(PolicyPhase::Response))
+                .collect(),
+            context,
+            released: 0,
+            evaluated: 0,
+            holdback: DEFAULT_HOLDBACK,
+            evaluation_interval: DEFAULT_EVALUATION_INTERVAL,
+            masks: Vec::new(),
+            decision: PolicyDecision::not_applicable(),
+            stopped: false,
+        }
+    }
+
+    /// Set how many bytes of received text are held back.
+    ///
+    /// This bounds the length of matches that are reliably denied or
+    /// masked before any of their text is released.
+    pub fn with_holdback(mut self, bytes: usize) -> Self {
+        self.holdback = bytes;
+        self
+    }
+
+    /// Set how many bytes must arrive before the policies are evaluated
+    /// again.
+    ///
+    /// Text is only released after an evaluation, so a longer interval
+    /// releases text in larger steps but never before it was checked.
+    pub fn with_evaluation_interval(mut self, bytes: usize) -> Self {
+        self.evaluation_interval = bytes;
+        self
+    }
+
+    /// Get the text received so far, before masking.
+    pub fn content(&self) -> &str {
+        self.context
+            .response
+            .as_ref()
+            .and_then(|response| response.content.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// Check whether the stream was cut off or finished.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Evaluate the next chunk of the completion.
+    ///
+    /// Once the stream has stopped, chunks are ignored and the last
+    /// decision is returned with no text. Until the evaluation interval is
+    /// reached, the chunk is held back and the last decision is returned
+    /// with no text. An evaluation error stops the stream.
+    pub fn push(&mut self, chunk: &str) -> Result<StreamOutput> {
+        if self.stopped {
+            return Ok(self.output(String::new(), self.decision.clone()));
+        }
+        if let Some(response) = self.context.response.as_mut() {
+            response
+                .content
+                .get_or_insert_with(String::new)
+                .push_str(chunk);
+        }
+        if self.content().len() - self.evaluated < self.evaluation_interval.max(1) {
+            return Ok(self.output(String::new(), self.decision.clone()));
+        }
+        self.evaluate(false)
+    }
+
+    /// Evaluate the complete response and release the held-back text.
+    pub fn finish(&mut self, finish_reason: Option<&str>) -> Result<StreamOutput> {
+        if self.stopped {
+            return Ok(self.output(String::new(), self.decision.clone()));
+        }
+        if let Some(response) = self.context.response.as_mut() {
+            response.finish_reason = finish_reason.map(str::to_string);
+        }
+        self.evaluate(true)
+    }
+
+    fn evaluate(&mut self, last: bool) -> Result<StreamOutput> {
+        self.evaluated = self.content().len();
+        let decision = self
+            .evaluator
+            .evaluate_compiled(&self.policies, &self.context, false)
+            .inspect_err(|_| self.stopped = true)?;
+
+        self.stopped = last || decision.decision == DecisionType::Deny;
+        if decision.decision == DecisionType::Deny {
+            return Ok(self.output(String::new(), decision));
+        }
+        if self.add_masks(&decision)? {
+            self.stopped = true;
+            return Ok(self.output(REDACTED.to_string(), decision));
+        }
+
+        let end = if last {
+            self.content().len()
+        } else {
+            self.release_point()
+        };
+        let text = self.release(end);
+        Ok(self.output(text, decision))
+    }
+
+    /// Collect the content masks of a decision, returning whether the
+    /// whole content is masked.
+    fn add_masks(&mut self, decision: &PolicyDecision) -> Result<bool> {
+        for field_modification in &decision.field_modifications {
+            let modification = &field_modification.modification;
+            if modification.modification_type != ModificationType::Mask
+                || canonical_field(&modification.field) != "response.content"
+            {
+                continue;
+            }
+            match &modification.value {
+                Some(serde_json::Value::String(pattern)) => {
+                    if !self.masks.iter().any(|mask| mask.as_str() == pattern) {
+                        self.masks.push(regex(pattern)?);
+                    }
+                }
+                _ => return Ok(true),
+            }
+        }
+        Ok(false)
+    }
+
+    /// Find where the releasable text ends: before the held-back tail and
+    /// before any mask match that would otherwise be split.
+    ///
+    /// Only matches starting at most `holdback` bytes before that point are
+    /// searched for, so the search is bounded however much text arrived.
+    fn release_point(&self) -> usize {
+        let text = self.content();
+        let floor = |mut index: usize| {
+            while !text.is_char_boundary(index) {
+                index -= 1;
+            }
+            index
+        };
+        let mut end = floor(text.len().saturating_sub(self.holdback).max(self.released));
+        let window_start = floor(end.saturating_sub(self.holdback)).max(self.released);
+
+        let window = &text[window_start..];
+        loop {
+            let before = end;
+            for mask in &self.masks {
+                for found in mask.find_iter(window) {
+                    let start = window_start + found.start();
+                    if start < end && window_start + found.end() > end {
+                        end = start;
+                    }
+                }
+            }
+            if end == before {
+                return end;
+            }
+        }
+    }
+
+    /// Release the received text up to `end`, masked.
+    fn release(&mut self, end: usize) -> String {
+        let start = std::mem::replace(&mut self.released, end);
+        let mut text = self.content()[start..end].to_string();
+        for mask in &self.masks {
+            if let Cow::Owned(masked) = mask.replace_all(&text, REDACTED) {
+                text = masked;
+            }
+        }
+        text
+    }
+
+    fn output(&mut self, text: String, decision: PolicyDecision) -> StreamOutput {
+        self.decision = decision.clone();
+        StreamOutput {
+            text,
+            decision,
+            stopped: self.stopped,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::{Action, Condition, Modification, Policy, PolicyRule};
+
+    fn stream(policies: Vec<Policy>) -> ResponseStreamEvaluator {
+        let policies = policies
+            .into_iter()
+            .map(|policy| Arc::new(CompiledPolicy::compile(policy).unwrap()))
+            .collect();
+        ResponseStreamEvaluator::new(Evaluator::new(), policies, &EvaluationContext::default())
+            .with_holdback(16)
+    }
+
+    fn redact_keys() -> Policy {
+        Policy::builder("keys")
+            .phase(PolicyPhase::Response)
+            .rule(PolicyRule::new(
+                "keys",
+                "Redact API keys",
+                Condition::matches("response.content", r"sk-[a-z0-9]{8}"),
+                Action::modify(vec![Modification::mask_pattern(
+                    "response.content",
+                    r"sk-[a-z0-9]{8}",
+                )]),
+            ))
+            .build()
+    }
+
+    #[test]
+    fn test_mask_across_chunks() {
+        let mut stream = stream(vec![redact_keys()]);
+        let mut output = String::new();
+        for chunk in [
+            "Your key is s",
+            "k-abc1",
+            "2345 and that is ",
+            "all there is to it.",
+        ] {
+            let result = stream.push(chunk).unwrap();
+            assert!(!result.stopped);
+            output.push_str(&result.text);
+        }
+        let result = stream.finish(Some("stop")).unwrap();
+        assert!(result.stopped);
+        assert_eq!(result.decision.decision, DecisionType::Modify);
+        output.push_str(&result.text);
+
+        assert_eq!(
+            output,
+            "Your key is [REDACTED] and that is all there is to it."
+        );
+        assert!(stream.content().contains("sk-abc12345"));
+        assert!(stream.push("more").unwrap().text.is_empty());
+    }
+
+    #[test]
+    fn test_long_stream_of_small_chunks() {
+        let mut stream = stream(vec![redact_keys()]);
+        let text = "Key sk-abc12345 is valid. ".repeat(2000);
+        let expected = "Key [REDACTED] is valid. ".repeat(2000);
+        let mut output = String::new();
+        for chunk in text.as_bytes().chunks(3) {
+            let result = stream.push(std::str::from_utf8(chunk).unwrap()).unwrap();
+            assert!(!result.stopped);
+            output.push_str(&result.text);
+        }
+        // Only the held-back tail and the last interval are still pending
+        assert!(output.len() > expected.len() - 64);
+        output.push_str(&stream.finish(Some("stop")).unwrap().text);
+
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_deny_stops_stream() {
+        let leak = Policy::builder("leak")
+            .phase(PolicyPhase::Response)
+            .rule(PolicyRule::new(
+                "internal",
+                "Internal hostnames",
+                Condition::contains("response.content", "corp.internal"),
+                Action::deny("Internal hostname in output"),
+            ))
+            .build();
+        // Request-phase policies are not evaluated on the stream
+        let request = Policy::builder("request")
+            .rule(PolicyRule::new(
+                "all",
+                "Deny everything",
+                Condition::expression("true"),
+                Action::deny("unreachable"),
+            ))
+            .build();
+        let mut stream = stream(vec![leak, request]);
+
+        let result = stream.push("The build server is at build.corp.").unwrap();
+        assert!(result.decision.allowed);
+        assert_eq!(result.text, "The build server i");
+
+        let result = stream.push("internal, port 22").unwrap();
+        assert!(result.stopped);
+        assert_eq!(result.decision.decision, DecisionType::Deny);
+        assert!(result.text.is_empty());
+        assert!(stream.is_stopped());
+        assert!(stream.finish(None).unwrap().text.is_empty());
+    }
+}
@@ -0,0 +1,279 @@
+//! Time windows of time conditions and the clock they fall back to.
+
+use crate::policy::{ConditionOperator, ConditionValue};
+use crate::{Error, Result};
+
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use serde_json::Value;
+use std::fmt;
+
+/// Minutes in a day.
+const DAY_MINUTES: u32 = 24 * 60;
+
+/// Source of the current time for time conditions on requests without a
+/// `request.timestamp`.
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Get the current time.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock stopped at a fixed time, for deterministic tests.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+/// When a time condition holds, in local time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeWindow {
+    /// Minutes after midnight from `start` up to `end`, wrapping past
+    /// midnight when `start` is later than `end`
+    Hours {
+        /// First minute of the window
+        start: u32,
+        /// First minute after the window
+        end: u32,
+    },
+    /// Days of the week
+    Days(Vec<Weekday>),
+    /// Dates from `start` to `end`, both included
+    Dates {
+        /// First day of the window
+        start: NaiveDate,
+        /// Last day of the window
+        end: NaiveDate,
+    },
+}
+
+impl TimeWindow {
+    /// Compile the value of a time operator.
+    pub fn compile(operator: ConditionOperator, value: &ConditionValue) -> Result<Self> {
+        let invalid = |expected: &str| {
+            Error::validation(format!(
+                "{} operator requires {}, got {:?}",
+                operator, expected, value
+            ))
+        };
+
+        match operator {
+            ConditionOperator::HourBetween => {
+                let ConditionValue::Array(bounds) = value else {
+                    return Err(invalid("a [start, end] list of hours"));
+                };
+                let [start, end] = bounds.as_slice() else {
+                    return Err(invalid("a [start, end] list of hours"));
+                };
+                let minute = |bound: &ConditionValue| {
+                    time_of_day(bound).ok_or_else(|| invalid("hours or \"HH:MM\" times"))
+                };
+                let (start, end) = (minute(start)?, minute(end)?);
+                if start == end {
+                    return Err(invalid("different start and end times"));
+                }
+                Ok(Self::Hours { start, end })
+            }
+            ConditionOperator::DayOfWeekIn => {
+                let names = match value {
+                    ConditionValue::Array(names) => names.as_slice(),
+                    name => std::slice::from_ref(name),
+                };
+                let mut days = Vec::new();
+                for name in names {
+                    let ConditionValue::String(name) = name else {
+                        return Err(invalid("day names"));
+                    };
+                    days.extend(weekdays(name).ok_or_else(|| invalid("day names"))?);
+                }
+                days.sort_by_key(Weekday::num_days_from_monday);
+                days.dedup();
+                Ok(Self::Days(days))
+            }
+            ConditionOperator::DateBetween => {
+                let date = |bound: &ConditionValue| match bound {
+                    ConditionValue::String(date) => {
+                        NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+                    }
+                    _ => None,
+                };
+                let dates = match value {
+                    ConditionValue::Array(bounds) => match bounds.as_slice() {
+                        [start, end] => date(start).zip(date(end)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                let (start, end) =
+                    dates.ok_or_else(|| invalid("a [start, end] list of YYYY-MM-DD dates"))?;
+                if start > end {
+                    return Err(invalid("a start date no later than the end date"));
+                }
+                Ok(Self::Dates { start, end })
+            }
+            other => Err(Error::validation(format!(
+                "{} is not a time operator",
+                other
+            ))),
+        }
+    }
+
+    /// Get the operator the window was compiled from.
+    pub fn operator(&self) -> ConditionOperator {
+        match self {
+            Self::Hours { .. } => ConditionOperator::HourBetween,
+            Self::Days(_) => ConditionOperator::DayOfWeekIn,
+            Self::Dates { .. } => ConditionOperator::DateBetween,
+        }
+    }
+
+    /// Get the window as JSON.
+    pub fn expected(&self) -> Value {
+        let time = |minute: u32| format!("{:02}:{:02}", minute / 60, minute % 60);
+        match self {
+            Self::Hours { start, end } => serde_json::json!([time(*start), time(*end)]),
+            Self::Days(days) => Value::Array(
+                days.iter()
+                    .map(|day| Value::String(day.to_string()))
+                    .collect(),
+            ),
+            Self::Dates { start, end } => {
+                serde_json::json!([start.to_string(), end.to_string()])
+            }
+        }
+    }
+
+    /// Check whether a local time falls in the window.
+    pub fn contains(&self, time: &DateTime<Tz>) -> bool {
+        match self {
+            Self::Hours { start, end } => {
+                let minute = time.hour() * 60 + time.minute();
+                if start < end {
+                    *start <= minute && minute < *end
+                } else {
+                    minute >= *start || minute < *end
+                }
+            }
+            Self::Days(days) => days.contains(&time.weekday()),
+            Self::Dates { start, end } => (*start..=*end).contains(&time.date_naive()),
+        }
+    }
+}
+
+/// Parse an IANA time zone name.
+pub(crate) fn time_zone(name: &str) -> Result<Tz> {
+    name.parse()
+        .map_err(|_| Error::validation(format!("Unknown time zone: '{}'", name)))
+}
+
+/// Read a point in time from a context value: Unix seconds or an RFC 3339
+/// string.
+pub(crate) fn timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::Number(seconds) => DateTime::from_timestamp(seconds.as_i64()?, 0),
+        Value::String(time) => DateTime::parse_from_rfc3339(time)
+            .ok()
+            .map(|time| time.with_timezone(&Utc)),
+        _ => None,
+    }
+}
+
+/// Get the minutes after midnight of an hour (`9`) or `"HH:MM"` time.
+fn time_of_day(value: &ConditionValue) -> Option<u32> {
+    let minute = match value {
+        ConditionValue::Integer(hour) => u32::try_from(*hour).ok()?.checked_mul(60)?,
+        ConditionValue::String(time) => {
+            let (hour, minute) = time.split_once(':')?;
+            let (hour, minute): (u32, u32) = (hour.parse().ok()?, minute.parse().ok()?);
+            if minute >= 60 {
+                return None;
+            }
+            hour.checked_mul(60)? + minute
+        }
+        _ => return None,
+    };
+    (minute <= DAY_MINUTES).then_some(minute)
+}
+
+/// Get the days a day name or `weekdays`/`weekend` stands for.
+fn weekdays(name: &str) -> Option<Vec<Weekday>> {
+    use Weekday::*;
+    match name.to_lowercase().as_str() {
+        "weekdays" => Some(vec![Mon, Tue, Wed, Thu, Fri]),
+        "weekend" => Some(vec![Sat, Sun]),
+        name => name.parse().ok().map(|day| vec![day]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn berlin(time: &str) -> DateTime<Tz> {
+        timestamp(&Value::String(time.to_string()))
+            .unwrap()
+            .with_timezone(&time_zone("Europe/Berlin").unwrap())
+    }
+
+    #[test]
+    fn test_windows() {
+        let office = TimeWindow::compile(
+            ConditionOperator::HourBetween,
+            &vec![ConditionValue::Integer(9), "17:30".into()].into(),
+        )
+        .unwrap();
+        // 16:00 UTC is 18:00 in Berlin in summer and 17:00 in winter
+        assert!(!office.contains(&berlin("2025-07-01T16:00:00Z")));
+        assert!(office.contains(&berlin("2025-12-01T16:00:00Z")));
+        assert_eq!(office.expected(), serde_json::json!(["09:00", "17:30"]));
+
+        let night =
+            TimeWindow::compile(ConditionOperator::HourBetween, &vec![22, 6].into()).unwrap();
+        assert!(night.contains(&berlin("2025-07-01T23:30:00Z")));
+        assert!(!night.contains(&berlin("2025-07-01T12:00:00Z")));
+
+        let weekend =
+            TimeWindow::compile(ConditionOperator::DayOfWeekIn, &"Weekend".into()).unwrap();
+        // Saturday in Berlin, still Friday in UTC
+        assert!(weekend.contains(&berlin("2025-07-04T22:30:00Z")));
+        assert!(!weekend.contains(&berlin("2025-07-04T21:30:00Z")));
+
+        let freeze = TimeWindow::compile(
+            ConditionOperator::DateBetween,
+            &vec!["2025-12-20", "2026-01-02"].into(),
+        )
+        .unwrap();
+        assert!(freeze.contains(&berlin("2026-01-02T22:59:00Z")));
+        assert!(!freeze.contains(&berlin("2026-01-02T23:00:00Z")));
+    }
+
+    #[test]
+    fn test_invalid_windows() {
+        let compile = |operator, value: ConditionValue| TimeWindow::compile(operator, &value);
+        assert!(compile(ConditionOperator::HourBetween, vec![9].into()).is_err());
+        assert!(compile(ConditionOperator::HourBetween, vec!["9:75", "10:00"].into()).is_err());
+        assert!(compile(ConditionOperator::HourBetween, vec![8, 8].into()).is_err());
+        assert!(compile(ConditionOperator::DayOfWeekIn, vec!["someday"].into()).is_err());
+        assert!(compile(
+            ConditionOperator::DateBetween,
+            vec!["2025-02-01", "2025-01-01"].into()
+        )
+        .is_err());
+        assert!(compile(ConditionOperator::Equals, vec![1].into()).is_err());
+        assert!(time_zone("Mars/Olympus_Mons").is_err());
+        assert!(timestamp(&Value::String("yesterday".into())).is_none());
+    }
+}
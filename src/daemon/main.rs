@@ -2,10 +2,14 @@
 //!
 //! A standalone daemon that provides policy evaluation services via gRPC and HTTP APIs.
 
-use llm_policy_engine::{Config, PolicyEngine, Result};
+use llm_policy_engine::server::{GrpcServer, HttpServer};
+use llm_policy_engine::config::{ConfigLoader, ConfigSource};
+use llm_policy_engine::reload::PolicyReloader;
+use llm_policy_engine::{PolicyEngine, Result};
 
 use clap::Parser;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tracing::{info, Level};
 use tracing_subscriber::FmtSubscriber;
 
@@ -28,19 +32,19 @@ struct Args {
     policy_file: Option<PathBuf>,
 
     /// HTTP server port
-    #[arg(long, env = "PORT", default_value = "3000")]
-    port: u16,
+    #[arg(long)]
+    port: Option<u16>,
 
     /// gRPC server port
-    #[arg(long, env = "GRPC_PORT", default_value = "50051")]
-    grpc_port: u16,
+    #[arg(long)]
+    grpc_port: Option<u16>,
 
     /// Log level
-    #[arg(long, env = "LOG_LEVEL", default_value = "info")]
-    log_level: String,
+    #[arg(long)]
+    log_level: Option<String>,
 
     /// Enable JSON log format
-    #[arg(long, env = "JSON_LOGS")]
+    #[arg(long)]
     json_logs: bool,
 
     /// Disable cache
@@ -50,53 +54,70 @@ struct Args {
     /// Disable telemetry
     #[arg(long)]
     no_telemetry: bool,
+
+    /// Do not watch the policy directory and file for changes
+    #[arg(long)]
+    no_watch: bool,
+
+    /// Print the effective configuration with its sources and exit
+    #[arg(long)]
+    print_config: bool,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
-    // Initialize logging
-    init_logging(&args.log_level, args.json_logs)?;
+    // Load configuration: file, then environment, then command line flags
+    let loaded = config_loader(&args).load()?;
 
-    info!("Starting Policy Engine Daemon v{}", llm_policy_engine::VERSION);
+    if args.print_config {
+        print!("{}", loaded);
+        return Ok(());
+    }
 
-    // Load configuration
-    let mut config = Config::from_env()?;
+    let config = loaded.config.clone();
 
-    // Apply command line overrides
-    config.server.port = args.port;
-    config.server.grpc_port = args.grpc_port;
+    // Initialize logging
+    init_logging(&config.telemetry.log_level, config.telemetry.json_logs)?;
 
-    if args.no_cache {
-        config.cache.enabled = false;
+    info!("Starting Policy Engine Daemon v{}", llm_policy_engine::VERSION);
+    if let Some(path) = &args.config {
+        info!("Loaded configuration file: {:?}", path);
     }
-
-    if args.no_telemetry {
-        config.telemetry.enabled = false;
+    for (key, source) in loaded.sources() {
+        if *source != ConfigSource::Default {
+            tracing::debug!("Config {} set from {}", key, source);
+        }
     }
 
     // Validate configuration
     config.validate()?;
 
     // Build the policy engine
-    let mut builder = PolicyEngine::builder()
-        .with_config(config.clone())
-        .with_cache_enabled(config.cache.enabled)
-        .with_telemetry_enabled(config.telemetry.enabled);
+    let engine = Arc::new(
+        PolicyEngine::builder()
+            .with_config(config.clone())
+            .with_cache_enabled(config.cache.enabled)
+            .with_telemetry_enabled(config.telemetry.enabled)
+            .build()
+            .await?,
+    );
 
-    // Load policy file if specified
+    // Load policies from the policy directory and file, if specified
+    let mut reloader = PolicyReloader::new(engine.clone());
+    if let Some(policy_dir) = &args.policy_dir {
+        info!("Loading policies from directory: {:?}", policy_dir);
+        reloader = reloader.with_dir(policy_dir);
+    }
     if let Some(policy_file) = &args.policy_file {
         info!("Loading policy file: {:?}", policy_file);
-        builder = builder.with_policy_file(policy_file.to_string_lossy().to_string());
+        reloader = reloader.with_file(policy_file);
     }
-
-    let engine = builder.build().await?;
-
-    // Load policies from directory if specified
-    if let Some(policy_dir) = &args.policy_dir {
-        info!("Loading policies from directory: {:?}", policy_dir);
-        load_policies_from_dir(&engine, policy_dir).await?;
+    let has_sources = args.policy_dir.is_some() || args.policy_file.is_some();
+    let reloader = Arc::new(reloader);
+    if has_sources {
+        reloader.reload().await?;
     }
 
     info!(
@@ -107,19 +128,123 @@ async fn main() -> Result<()> {
     info!("Cache enabled: {}", config.cache.enabled);
     info!("Telemetry enabled: {}", config.telemetry.enabled);
 
-    // In a full implementation, this would start the HTTP and gRPC servers
-    // For now, we just demonstrate the daemon can be initialized
     info!("Policy Engine Daemon ready");
 
-    // Keep running until shutdown signal
-    tokio::signal::ctrl_c().await.map_err(|e| {
-        llm_policy_engine::Error::internal(format!("Failed to listen for shutdown signal: {}", e))
-    })?;
+    // Serve HTTP and gRPC until a shutdown signal is received
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(());
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        let _ = shutdown_tx.send(());
+    });
+
+    if has_sources {
+        tokio::spawn(reload_on_hangup(reloader.clone()));
+        if !args.no_watch {
+            let shutdown = wait_for_shutdown(shutdown_rx.clone());
+            tokio::spawn(async move {
+                if let Err(e) = reloader.watch(shutdown).await {
+                    tracing::error!("Policy watcher stopped: {}", e);
+                }
+            });
+        }
+    }
+
+    let http = HttpServer::new(engine.clone()).serve(wait_for_shutdown(shutdown_rx.clone()));
+    let grpc = GrpcServer::new(engine).serve(wait_for_shutdown(shutdown_rx));
+    tokio::try_join!(http, grpc)?;
 
     info!("Shutting down Policy Engine Daemon");
     Ok(())
 }
 
+/// Build the configuration loader from command line arguments.
+fn config_loader(args: &Args) -> ConfigLoader {
+    let mut loader = ConfigLoader::new();
+
+    if let Some(path) = &args.config {
+        loader = loader.with_file(path);
+    }
+    if let Some(port) = args.port {
+        loader = loader.with_override("server.port", port, "--port");
+    }
+    if let Some(grpc_port) = args.grpc_port {
+        loader = loader.with_override("server.grpc_port", grpc_port, "--grpc-port");
+    }
+    if let Some(level) = &args.log_level {
+        loader = loader.with_override("telemetry.log_level", level, "--log-level");
+    }
+    if args.json_logs {
+        loader = loader.with_override("telemetry.json_logs", true, "--json-logs");
+    }
+    if args.no_cache {
+        loader = loader.with_override("cache.enabled", false, "--no-cache");
+    }
+    if args.no_telemetry {
+        loader = loader.with_override("telemetry.enabled", false, "--no-telemetry");
+    }
+
+    loader
+}
+
+/// Resolve once the shutdown channel fires.
+async fn wait_for_shutdown(mut rx: tokio::sync::watch::Receiver<()>) {
+    let _ = rx.changed().await;
+}
+
+/// Reload policies whenever SIGHUP is received.
+async fn reload_on_hangup(reloader: Arc<PolicyReloader>) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(signal) => signal,
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGHUP: {}", e);
+                return;
+            }
+        };
+        while hangup.recv().await.is_some() {
+            info!("Received SIGHUP, reloading policies");
+            let _ = reloader.reload().await;
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = reloader;
+}
+
+/// Wait for Ctrl-C or SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
 /// Initialize the logging system.
 fn init_logging(level: &str, _json_format: bool) -> Result<()> {
     let level = match level.to_lowercase().as_str() {
@@ -145,32 +270,3 @@ fn init_logging(level: &str, _json_format: bool) -> Result<()> {
 
     Ok(())
 }
-
-/// Load policies from a directory.
-async fn load_policies_from_dir(engine: &PolicyEngine, dir: &PathBuf) -> Result<()> {
-    let entries = std::fs::read_dir(dir)?;
-    let mut loaded = 0;
-
-    for entry in entries {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path.is_file() {
-            let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-            if matches!(extension.to_lowercase().as_str(), "yaml" | "yml" | "json") {
-                match engine.load_policy_file(&path).await {
-                    Ok(ids) => {
-                        info!("Loaded {} policies from {:?}", ids.len(), path);
-                        loaded += ids.len();
-                    }
-                    Err(e) => {
-                        tracing::warn!("Failed to load policies from {:?}: {}", path, e);
-                    }
-                }
-            }
-        }
-    }
-
-    info!("Loaded {} total policies from directory", loaded);
-    Ok(())
-}
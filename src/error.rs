@@ -3,7 +3,6 @@
 //! This module defines all error types used throughout the crate, providing
 //! structured error handling with detailed context for debugging.
 
-use std::fmt;
 use thiserror::Error;
 
 /// Result type alias using the crate's Error type.
@@ -91,6 +90,8 @@ pub enum Error {
         message: String,
         /// Duration in milliseconds before timeout
         duration_ms: u64,
+        /// Policy being evaluated when time ran out, if applicable
+        policy_id: Option<String>,
     },
 
     /// I/O error
@@ -212,6 +213,20 @@ impl Error {
         Error::Timeout {
             message: message.into(),
             duration_ms,
+            policy_id: None,
+        }
+    }
+
+    /// Create a timeout error for an evaluation that ran out of time in a policy.
+    pub fn timeout_in_policy(
+        message: impl Into<String>,
+        duration_ms: u64,
+        policy_id: impl Into<String>,
+    ) -> Self {
+        Error::Timeout {
+            message: message.into(),
+            duration_ms,
+            policy_id: Some(policy_id.into()),
         }
     }
 
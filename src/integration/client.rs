@@ -1,6 +1,5 @@
 //! Base integration client functionality.
 
-use crate::Result;
 use serde::{de::DeserializeOwned, Serialize};
 use std::time::Duration;
 
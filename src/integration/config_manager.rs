@@ -11,6 +11,7 @@
 //! dependency pattern: Config Manager -> Policy Engine (consumes-from).
 
 use super::client::{IntegrationClient, IntegrationResult};
+use crate::config::NamespaceConfig;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Duration;
@@ -217,6 +218,16 @@ impl Default for EnforcementParams {
     }
 }
 
+impl EnforcementParams {
+    /// Get the evaluation overrides these parameters describe for their namespace.
+    pub fn namespace_config(&self) -> crate::Result<NamespaceConfig> {
+        Ok(NamespaceConfig {
+            default_decision: Some(self.default_decision.parse()?),
+            fail_open: Some(self.fail_open),
+        })
+    }
+}
+
 /// Rate limiting configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RateLimitConfig {
@@ -418,6 +429,10 @@ mod tests {
         assert!(!params.strict_mode);
         assert_eq!(params.default_decision, "deny");
         assert_eq!(params.max_evaluation_time_ms, 100);
+
+        let namespace = params.namespace_config().unwrap();
+        assert_eq!(namespace.default_decision, Some(crate::policy::DecisionType::Deny));
+        assert_eq!(namespace.fail_open, Some(false));
     }
 
     #[test]
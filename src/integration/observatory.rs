@@ -89,7 +89,7 @@ impl ObservatoryAdapter {
     /// Complete a trace span with results.
     pub async fn complete_span(&self, span_id: &str, result: &SpanResult) -> IntegrationResult<()> {
         let path = format!("/api/v1/spans/{}/complete", span_id);
-        self.client.post::<(), _>(&path, result).await.map(|_| ())
+        self.client.post::<(), _>(&path, result).await
     }
 
     /// Get telemetry signals for a specific context.
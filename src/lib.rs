@@ -9,12 +9,14 @@
 //! - **Rule Evaluation**: Evaluate policy rules against request contexts
 //! - **Decision Making**: Return allow/deny/warn/modify decisions
 //! - **Telemetry Integration**: Full OpenTelemetry support for distributed tracing
+//! - **HTTP API**: REST server for running the engine as a sidecar
+//! - **Hot Reload**: Watch policy directories and swap policy sets atomically
 //! - **High Performance**: Optimized for low-latency policy evaluation
 //!
 //! ## Quick Start
 //!
 //! ```rust,no_run
-//! use llm_policy_engine::{PolicyEngine, EvaluationContext, PolicyDecision};
+//! use llm_policy_engine::{DecisionType, EvaluationContext, PolicyDecision, PolicyEngine};
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -65,17 +67,20 @@ pub mod core;
 pub mod error;
 pub mod integration;
 pub mod policy;
+pub mod reload;
+pub mod server;
 pub mod telemetry;
 
 // Re-export main types for convenience
 pub use api::{
-    EvaluationContext, EvaluationContextBuilder, PolicyDecision, PolicyEngine, PolicyEngineBuilder,
+    EvaluationContext, EvaluationContextBuilder, EvaluationOptions, PolicyDecision, PolicyEngine,
+    PolicyEngineBuilder,
 };
 pub use config::Config;
 pub use error::{Error, Result};
 pub use policy::{
-    Action, ActionType, Condition, ConditionOperator, DecisionType, Policy, PolicyDocument,
-    PolicyMetadata, PolicyRule,
+    Action, ActionType, CombiningAlgorithm, Condition, ConditionOperator, DecisionType, Policy,
+    PolicyDocument, PolicyMetadata, PolicyRule, PolicyTarget,
 };
 
 /// Library version
@@ -1,6 +1,9 @@
 //! Policy action definitions.
 
-use super::DecisionType;
+use super::{DecisionType, LogLevel};
+use crate::api::{holds, tool_name, FieldPath, PathSegment};
+use crate::config::loader::REDACTED;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -69,6 +72,38 @@ impl Action {
         }
     }
 
+    /// Create an action that logs the request at a severity.
+    pub fn log(level: LogLevel) -> Self {
+        Self::obligation(ActionType::Log)
+            .with_metadata("level", serde_json::json!(level))
+    }
+
+    /// Create an action that admits at most `limit` requests per window.
+    pub fn rate_limit(limit: u64, window_secs: u64) -> Self {
+        Self::obligation(ActionType::RateLimit)
+            .with_metadata("limit", serde_json::json!(limit))
+            .with_metadata("window_secs", serde_json::json!(window_secs))
+    }
+
+    /// Create an action that notifies a channel.
+    pub fn notify(channel: impl Into<String>) -> Self {
+        Self::obligation(ActionType::Notify)
+            .with_metadata("channel", serde_json::json!(channel.into()))
+    }
+
+    fn obligation(action_type: ActionType) -> Self {
+        Self {
+            action_type,
+            ..Self::allow()
+        }
+    }
+
+    /// Mark the action's obligation as mandatory, so the request is denied
+    /// when it cannot be fulfilled.
+    pub fn mandatory(self) -> Self {
+        self.with_metadata("mandatory", serde_json::json!(true))
+    }
+
     /// Add a reason to the action.
     pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
         self.reason = Some(reason.into());
@@ -103,7 +138,32 @@ pub enum ActionType {
     /// Log the request (no decision change)
     Log,
     /// Rate limit the request
+    #[serde(alias = "rate_limit", alias = "throttle")]
     RateLimit,
+    /// Record the request in the audit trail (no decision change)
+    Audit,
+    /// Suggest an alternative to the caller (no decision change)
+    #[serde(rename = "suggest_alternative")]
+    SuggestAlternative,
+    /// Notify a channel (no decision change)
+    Notify,
+}
+
+impl ActionType {
+    /// Get the string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActionType::Allow => "allow",
+            ActionType::Deny => "deny",
+            ActionType::Warn => "warn",
+            ActionType::Modify => "modify",
+            ActionType::Log => "log",
+            ActionType::RateLimit => "ratelimit",
+            ActionType::Audit => "audit",
+            ActionType::SuggestAlternative => "suggest_alternative",
+            ActionType::Notify => "notify",
+        }
+    }
 }
 
 impl Default for ActionType {
@@ -112,6 +172,21 @@ impl Default for ActionType {
     }
 }
 
+impl std::fmt::Display for ActionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for ActionType {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .map_err(|_| crate::Error::parse(format!("Unknown action type: {}", s)))
+    }
+}
+
 /// A modification to apply to the request or response.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Modification {
@@ -144,6 +219,32 @@ impl Modification {
         }
     }
 
+    /// Create a remove modification that drops the elements of an array
+    /// named in `names`, e.g. disallowed tools from `llm.tools`.
+    pub fn remove_named(
+        field: impl Into<String>,
+        names: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            modification_type: ModificationType::Remove,
+            field: field.into(),
+            value: Some(names_value(names)),
+        }
+    }
+
+    /// Create a retain modification that keeps only the elements of an
+    /// array named in `names`, e.g. allowed tools in `llm.tools`.
+    pub fn retain(
+        field: impl Into<String>,
+        names: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            modification_type: ModificationType::Retain,
+            field: field.into(),
+            value: Some(names_value(names)),
+        }
+    }
+
     /// Create an append modification.
     pub fn append(field: impl Into<String>, value: serde_json::Value) -> Self {
         Self {
@@ -161,6 +262,284 @@ impl Modification {
             value: None,
         }
     }
+
+    /// Create a mask modification that only replaces matches of a regex.
+    pub fn mask_pattern(field: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self {
+            modification_type: ModificationType::Mask,
+            field: field.into(),
+            value: Some(serde_json::Value::String(pattern.into())),
+        }
+    }
+
+    /// Create a truncate modification limiting a string to `limit`
+    /// characters or an array to `limit` elements.
+    pub fn truncate(field: impl Into<String>, limit: usize) -> Self {
+        Self {
+            modification_type: ModificationType::Truncate,
+            field: field.into(),
+            value: Some(serde_json::json!(limit)),
+        }
+    }
+
+    /// Check that the field parses and the value suits the modification type.
+    pub fn validate(&self) -> crate::Result<()> {
+        FieldPath::parse(&self.field)?;
+        let invalid = |message: &str| {
+            Err(crate::Error::validation_field(
+                format!("Modification of '{}' {}", self.field, message),
+                "modifications",
+            ))
+        };
+
+        match (self.modification_type, &self.value) {
+            (ModificationType::Set | ModificationType::Append, None) => invalid("needs a value"),
+            (ModificationType::Mask, Some(serde_json::Value::String(pattern))) => {
+                match Regex::new(pattern) {
+                    Ok(_) => Ok(()),
+                    Err(_) => invalid("has an invalid pattern"),
+                }
+            }
+            (ModificationType::Mask, Some(_)) => invalid("needs a pattern string"),
+            (ModificationType::Truncate, Some(value)) if value.is_u64() => Ok(()),
+            (ModificationType::Truncate, _) => invalid("needs a non-negative integer limit"),
+            (ModificationType::Remove, None) => Ok(()),
+            (ModificationType::Remove | ModificationType::Retain, value) => {
+                match value.as_ref().and_then(names) {
+                    Some(_) => Ok(()),
+                    None => invalid("needs a list of names"),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Apply the modification to a JSON document.
+    ///
+    /// Numeric path segments index into arrays. Set creates missing parent
+    /// objects and append creates a missing array; removing, masking or
+    /// truncating a missing field changes nothing. Removing with a list of
+    /// names, or retaining, filters the elements of an array by name: a
+    /// string element is its own name, an object has a `name` key.
+    pub fn apply(&self, document: &mut serde_json::Value) -> crate::Result<()> {
+        let path = FieldPath::parse(&self.field)?;
+        let segments = path.segments();
+        if segments.contains(&PathSegment::Length) {
+            return Err(crate::Error::validation_field(
+                format!("Cannot modify the length of '{}'", self.field),
+                "modifications",
+            ));
+        }
+        let value = || self.value.clone().unwrap_or(serde_json::Value::Null);
+        let error = |message: String| {
+            crate::Error::evaluation(format!("Cannot modify '{}': {}", self.field, message))
+        };
+
+        let result = match self.modification_type {
+            ModificationType::Set => visit(document, segments, true, &mut |target| {
+                *target = value();
+                Ok(())
+            }),
+            ModificationType::Remove | ModificationType::Retain if self.value.is_some() => {
+                let Some(names) = self.value.as_ref().and_then(names) else {
+                    return Err(error("Expected a list of names".to_string()));
+                };
+                let keep = self.modification_type == ModificationType::Retain;
+                visit(document, segments, false, &mut |target| {
+                    if let serde_json::Value::Array(items) = target {
+                        items.retain(|item| {
+                            let named = tool_name(item).is_some_and(|name| names.contains(&name));
+                            named == keep
+                        });
+                    }
+                    Ok(())
+                })
+            }
+            ModificationType::Retain => Err("Expected a list of names".to_string()),
+            ModificationType::Remove => {
+                let (last, parents) = segments.split_last().expect("paths are never empty");
+                visit(document, parents, false, &mut |parent| {
+                    remove_child(parent, last);
+                    Ok(())
+                })
+            }
+            ModificationType::Append => {
+                visit(document, segments, true, &mut |target| match (target, value()) {
+                    (serde_json::Value::Array(items), value) => {
+                        items.push(value);
+                        Ok(())
+                    }
+                    (serde_json::Value::String(text), serde_json::Value::String(suffix)) => {
+                        text.push_str(&suffix);
+                        Ok(())
+                    }
+                    (target @ serde_json::Value::Null, value) => {
+                        *target = serde_json::json!([value]);
+                        Ok(())
+                    }
+                    (target, value) => Err(format!("Cannot append {} to {}", value, target)),
+                })
+            }
+            ModificationType::Mask => {
+                let pattern = match &self.value {
+                    Some(serde_json::Value::String(pattern)) => match Regex::new(pattern) {
+                        Ok(pattern) => Some(pattern),
+                        Err(e) => return Err(error(format!("Invalid mask pattern: {}", e))),
+                    },
+                    _ => None,
+                };
+                visit(document, segments, false, &mut |target| {
+                    match &pattern {
+                        Some(pattern) => mask_matches(target, pattern),
+                        None => *target = serde_json::json!(REDACTED),
+                    }
+                    Ok(())
+                })
+            }
+            ModificationType::Truncate => {
+                let Some(limit) = self.value.as_ref().and_then(|v| v.as_u64()) else {
+                    return Err(error("Truncate needs a non-negative integer limit".to_string()));
+                };
+                let limit = limit as usize;
+                visit(document, segments, false, &mut |target| match target {
+                    serde_json::Value::String(text) => {
+                        if let Some((end, _)) = text.char_indices().nth(limit) {
+                            text.truncate(end);
+                        }
+                        Ok(())
+                    }
+                    serde_json::Value::Array(items) => {
+                        items.truncate(limit);
+                        Ok(())
+                    }
+                    serde_json::Value::Null => Ok(()),
+                    other => Err(format!("Cannot truncate {}", other)),
+                })
+            }
+        };
+
+        result.map_err(error)
+    }
+}
+
+/// Call `f` on every value a path reaches.
+///
+/// With `create`, missing keys and the next index of an array are added as
+/// null and null parents become objects; otherwise missing fields are
+/// skipped. A wildcard visits every element of an array and a filter the
+/// elements it selects.
+fn visit(
+    value: &mut serde_json::Value,
+    segments: &[PathSegment],
+    create: bool,
+    f: &mut dyn FnMut(&mut serde_json::Value) -> Result<(), String>,
+) -> Result<(), String> {
+    let Some((segment, rest)) = segments.split_first() else {
+        return f(value);
+    };
+    if create && value.is_null() && matches!(segment, PathSegment::Key(_)) {
+        *value = serde_json::Value::Object(Default::default());
+    }
+
+    let index = |items: &Vec<serde_json::Value>| match segment {
+        PathSegment::Index(index) => Some(*index),
+        PathSegment::Key(key) => key.parse().ok(),
+        _ => None,
+    }
+    .filter(|&index| index < items.len() || (create && index == items.len()));
+
+    match (value, segment) {
+        (serde_json::Value::Array(items), PathSegment::Wildcard) => {
+            for item in items.iter_mut() {
+                visit(item, rest, create, f)?;
+            }
+            Ok(())
+        }
+        (serde_json::Value::Array(items), PathSegment::Filter { key, value }) => {
+            for item in items.iter_mut().filter(|item| holds(item, key, value)) {
+                visit(item, rest, create, f)?;
+            }
+            Ok(())
+        }
+        (_, PathSegment::Wildcard | PathSegment::Filter { .. }) => Ok(()),
+        (serde_json::Value::Object(map), PathSegment::Key(key)) => match map.get_mut(key) {
+            Some(child) => visit(child, rest, create, f),
+            None if create => visit(
+                map.entry(key.clone()).or_insert(serde_json::Value::Null),
+                rest,
+                create,
+                f,
+            ),
+            None => Ok(()),
+        },
+        (serde_json::Value::Array(items), _) => match index(items) {
+            Some(index) => {
+                if index == items.len() {
+                    items.push(serde_json::Value::Null);
+                }
+                visit(&mut items[index], rest, create, f)
+            }
+            None if create => Err(format!("Array index '{:?}' is out of range", segment)),
+            None => Ok(()),
+        },
+        (other, segment) if create => Err(format!("Cannot set {:?} inside {}", segment, other)),
+        _ => Ok(()),
+    }
+}
+
+/// Build a list of names.
+fn names_value(names: impl IntoIterator<Item = impl Into<String>>) -> serde_json::Value {
+    serde_json::Value::Array(
+        names
+            .into_iter()
+            .map(|name| serde_json::Value::String(name.into()))
+            .collect(),
+    )
+}
+
+/// Read a list of names.
+fn names(value: &serde_json::Value) -> Option<Vec<&str>> {
+    value.as_array()?.iter().map(|v| v.as_str()).collect()
+}
+
+/// Remove the child named by a key or index segment.
+fn remove_child(parent: &mut serde_json::Value, segment: &PathSegment) {
+    match (parent, segment) {
+        (serde_json::Value::Object(map), PathSegment::Key(key)) => {
+            map.remove(key);
+        }
+        (serde_json::Value::Array(items), PathSegment::Index(index)) if *index < items.len() => {
+            items.remove(*index);
+        }
+        (serde_json::Value::Array(items), PathSegment::Key(key)) => {
+            if let Some(index) = key.parse::<usize>().ok().filter(|&i| i < items.len()) {
+                items.remove(index);
+            }
+        }
+        (serde_json::Value::Array(items), PathSegment::Wildcard) => items.clear(),
+        (serde_json::Value::Array(items), PathSegment::Filter { key, value }) => {
+            items.retain(|item| !holds(item, key, value))
+        }
+        _ => {}
+    }
+}
+
+/// Replace every match of a pattern in the strings of a value.
+fn mask_matches(value: &mut serde_json::Value, pattern: &Regex) {
+    match value {
+        serde_json::Value::String(text) => {
+            if let std::borrow::Cow::Owned(masked) = pattern.replace_all(text, REDACTED) {
+                *text = masked;
+            }
+        }
+        serde_json::Value::Array(items) => {
+            items.iter_mut().for_each(|item| mask_matches(item, pattern))
+        }
+        serde_json::Value::Object(map) => {
+            map.values_mut().for_each(|item| mask_matches(item, pattern))
+        }
+        _ => {}
+    }
 }
 
 /// Type of modification to apply.
@@ -177,6 +556,37 @@ pub enum ModificationType {
     Mask,
     /// Truncate a string field
     Truncate,
+    /// Keep only the named elements of an array field
+    Retain,
+}
+
+impl ModificationType {
+    /// Get the string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModificationType::Set => "set",
+            ModificationType::Remove => "remove",
+            ModificationType::Append => "append",
+            ModificationType::Mask => "mask",
+            ModificationType::Truncate => "truncate",
+            ModificationType::Retain => "retain",
+        }
+    }
+}
+
+impl std::fmt::Display for ModificationType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for ModificationType {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .map_err(|_| crate::Error::parse(format!("Unknown modification type: {}", s)))
+    }
 }
 
 #[cfg(test)]
@@ -207,6 +617,116 @@ mod tests {
         assert_eq!(action.modifications.len(), 2);
     }
 
+    #[test]
+    fn test_apply_modifications() {
+        let mut document = serde_json::json!({
+            "llm": {"prompt": "my ssn is 123-45-6789", "stop": ["a"]},
+            "messages": [{"role": "user", "content": "hello world"}],
+        });
+
+        let modifications = [
+            Modification::set("llm.options.seed", serde_json::json!(7)),
+            Modification::remove("messages.0.role"),
+            Modification::append("llm.stop", serde_json::json!("b")),
+            Modification::append("llm.tags", serde_json::json!("new")),
+            Modification::mask_pattern("llm.prompt", r"\d{3}-\d{2}-\d{4}"),
+            Modification::mask("user.email"),
+            Modification::truncate("messages.0.content", 5),
+        ];
+        for modification in &modifications {
+            modification.validate().unwrap();
+            modification.apply(&mut document).unwrap();
+        }
+
+        assert_eq!(
+            document,
+            serde_json::json!({
+                "llm": {
+                    "prompt": "my ssn is [REDACTED]",
+                    "stop": ["a", "b"],
+                    "tags": ["new"],
+                    "options": {"seed": 7},
+                },
+                "messages": [{"content": "hello"}],
+            })
+        );
+
+        Modification::mask_pattern("messages[*].content", "l+")
+            .apply(&mut document)
+            .unwrap();
+        assert_eq!(document["messages"][0]["content"], "he[REDACTED]o");
+        assert!(Modification::remove("messages.#").apply(&mut document).is_err());
+
+        Modification::mask("llm").apply(&mut document).unwrap();
+        assert_eq!(document["llm"], "[REDACTED]");
+
+        assert!(Modification::set("llm.x", serde_json::json!(1))
+            .apply(&mut document)
+            .is_err());
+        assert!(Modification::truncate("messages", 0).apply(&mut document).is_ok());
+        assert!(Modification::mask_pattern("llm", "(").validate().is_err());
+    }
+
+    #[test]
+    fn test_modify_messages() {
+        let mut document = serde_json::json!({
+            "llm": {
+                "messages": [
+                    {"role": "system", "content": "Be brief"},
+                    {"role": "user", "content": "my password is hunter2"},
+                    {"role": "tool", "content": "secret", "tool_call_id": "call-1"},
+                ],
+            },
+        });
+
+        Modification::mask_pattern("llm.messages[role=user].content", "hunter2")
+            .apply(&mut document)
+            .unwrap();
+        Modification::remove("llm.messages[role=tool]")
+            .apply(&mut document)
+            .unwrap();
+        Modification::set("llm.messages[0].content", serde_json::json!("Be polite"))
+            .apply(&mut document)
+            .unwrap();
+        assert_eq!(
+            document["llm"]["messages"],
+            serde_json::json!([
+                {"role": "system", "content": "Be polite"},
+                {"role": "user", "content": "my password is [REDACTED]"},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_strip_tools() {
+        let mut document = serde_json::json!({
+            "llm": {
+                "tools": [{"name": "search"}, {"name": "shell"}, {"name": "exec"}],
+                "tool_calls": [{"name": "shell", "arguments": {}}],
+            },
+        });
+
+        Modification::retain("llm.tools", ["search", "shell"])
+            .apply(&mut document)
+            .unwrap();
+        Modification::remove_named("llm.tools", ["shell"])
+            .apply(&mut document)
+            .unwrap();
+        Modification::remove_named("llm.tool_calls", ["shell"])
+            .apply(&mut document)
+            .unwrap();
+        assert_eq!(
+            document["llm"],
+            serde_json::json!({"tools": [{"name": "search"}], "tool_calls": []})
+        );
+
+        let modification: Modification =
+            serde_json::from_value(serde_json::json!({"type": "retain", "field": "llm.tools"}))
+                .unwrap();
+        assert!(modification.validate().is_err());
+        assert!(modification.apply(&mut document).is_err());
+    }
+
     #[test]
     fn test_action_serialization() {
         let action = Action::deny("Rate limit exceeded")
@@ -0,0 +1,73 @@
+//! Combining algorithms for rule and policy results.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How the results of several applicable rules or policies are combined.
+///
+/// Within a policy the algorithm combines its rules; across policies the
+/// engine's algorithm combines the policies. Results are considered in
+/// priority order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CombiningAlgorithm {
+    /// Any deny wins; otherwise the permits are merged
+    #[default]
+    #[serde(alias = "deny_overrides")]
+    DenyOverrides,
+    /// Any permit (allow, warn or modify) wins; otherwise the first deny
+    #[serde(alias = "permit_overrides")]
+    PermitOverrides,
+    /// The first applicable result wins
+    #[serde(alias = "first_applicable")]
+    FirstApplicable,
+    /// Exactly one result may apply; more than one is an error
+    #[serde(alias = "only_one_applicable")]
+    OnlyOneApplicable,
+}
+
+impl CombiningAlgorithm {
+    /// Get the string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CombiningAlgorithm::DenyOverrides => "deny-overrides",
+            CombiningAlgorithm::PermitOverrides => "permit-overrides",
+            CombiningAlgorithm::FirstApplicable => "first-applicable",
+            CombiningAlgorithm::OnlyOneApplicable => "only-one-applicable",
+        }
+    }
+}
+
+impl fmt::Display for CombiningAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for CombiningAlgorithm {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .map_err(|_| crate::Error::parse(format!("Unknown combining algorithm: {}", s)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "permit-overrides".parse::<CombiningAlgorithm>().unwrap(),
+            CombiningAlgorithm::PermitOverrides
+        );
+        assert_eq!(
+            "FIRST_APPLICABLE".parse::<CombiningAlgorithm>().unwrap(),
+            CombiningAlgorithm::FirstApplicable
+        );
+        assert!("majority".parse::<CombiningAlgorithm>().is_err());
+        assert_eq!(CombiningAlgorithm::default().as_str(), "deny-overrides");
+    }
+}
@@ -16,6 +16,9 @@ pub struct Condition {
     /// Nested conditions for logical operators (AND, OR, NOT)
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub conditions: Vec<Condition>,
+    /// IANA time zone of time operators (e.g., "Europe/Berlin"); UTC when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
 }
 
 impl Condition {
@@ -26,6 +29,7 @@ impl Condition {
             field: Some(field.into()),
             value: Some(value.into()),
             conditions: Vec::new(),
+            timezone: None,
         }
     }
 
@@ -36,6 +40,7 @@ impl Condition {
             field: Some(field.into()),
             value: Some(value.into()),
             conditions: Vec::new(),
+            timezone: None,
         }
     }
 
@@ -46,6 +51,7 @@ impl Condition {
             field: Some(field.into()),
             value: Some(value.into()),
             conditions: Vec::new(),
+            timezone: None,
         }
     }
 
@@ -56,6 +62,7 @@ impl Condition {
             field: Some(field.into()),
             value: Some(value.into()),
             conditions: Vec::new(),
+            timezone: None,
         }
     }
 
@@ -66,6 +73,7 @@ impl Condition {
             field: Some(field.into()),
             value: Some(ConditionValue::Array(values)),
             conditions: Vec::new(),
+            timezone: None,
         }
     }
 
@@ -76,6 +84,7 @@ impl Condition {
             field: Some(field.into()),
             value: Some(value.into()),
             conditions: Vec::new(),
+            timezone: None,
         }
     }
 
@@ -86,6 +95,7 @@ impl Condition {
             field: Some(field.into()),
             value: None,
             conditions: Vec::new(),
+            timezone: None,
         }
     }
 
@@ -96,9 +106,161 @@ impl Condition {
             field: Some(field.into()),
             value: Some(ConditionValue::String(pattern.into())),
             conditions: Vec::new(),
+            timezone: None,
+        }
+    }
+
+    /// Create a template condition, which holds when the field equals one
+    /// of the templates with each `{placeholder}` standing for any text.
+    pub fn matches_template(
+        field: impl Into<String>,
+        templates: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            operator: ConditionOperator::MatchesTemplate,
+            field: Some(field.into()),
+            value: Some(string_list(templates)),
+            conditions: Vec::new(),
+            timezone: None,
+        }
+    }
+
+    /// Create a "tool in" condition, which holds when a tool named in the
+    /// field (e.g. `llm.tool_calls`) is one of the listed tools.
+    pub fn tool_in(
+        field: impl Into<String>,
+        tools: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            operator: ConditionOperator::ToolIn,
+            field: Some(field.into()),
+            value: Some(string_list(tools)),
+            conditions: Vec::new(),
+            timezone: None,
+        }
+    }
+
+    /// Create a "tool not in" condition, which holds when a tool named in
+    /// the field is missing from the allowlist.
+    pub fn tool_not_in(
+        field: impl Into<String>,
+        tools: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            operator: ConditionOperator::ToolNotIn,
+            field: Some(field.into()),
+            value: Some(string_list(tools)),
+            conditions: Vec::new(),
+            timezone: None,
+        }
+    }
+
+    /// Create an "in CIDR" condition, which holds when the field (e.g.
+    /// `request.ip_address`) is an IPv4 or IPv6 address in one of the
+    /// ranges (`10.0.0.0/8`, `2001:db8::/32`).
+    pub fn in_cidr(
+        field: impl Into<String>,
+        ranges: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            operator: ConditionOperator::InCidr,
+            field: Some(field.into()),
+            value: Some(string_list(ranges)),
+            conditions: Vec::new(),
+            timezone: None,
+        }
+    }
+
+    /// Create a "not in CIDR" condition, which holds when the field is not
+    /// an address in any of the ranges.
+    pub fn not_in_cidr(
+        field: impl Into<String>,
+        ranges: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            operator: ConditionOperator::NotInCidr,
+            field: Some(field.into()),
+            value: Some(string_list(ranges)),
+            conditions: Vec::new(),
+            timezone: None,
+        }
+    }
+
+    /// Create an "argument matches" condition, which holds when a call to
+    /// `tool` (`*` for any tool) has an argument at `argument` matching the
+    /// regex.
+    pub fn argument_matches(
+        tool: impl Into<String>,
+        argument: impl Into<String>,
+        pattern: impl Into<String>,
+    ) -> Self {
+        Self {
+            operator: ConditionOperator::ArgumentMatches,
+            field: Some(format!("{}.{}", tool.into(), argument.into())),
+            value: Some(ConditionValue::String(pattern.into())),
+            conditions: Vec::new(),
+            timezone: None,
+        }
+    }
+
+    /// Create a CEL expression condition.
+    pub fn expression(expression: impl Into<String>) -> Self {
+        Self {
+            operator: ConditionOperator::Expression,
+            field: None,
+            value: Some(ConditionValue::String(expression.into())),
+            conditions: Vec::new(),
+            timezone: None,
+        }
+    }
+
+    /// Create an hour-of-day condition, which holds from `start` up to
+    /// `end` local time; the window wraps past midnight when `start` is
+    /// later than `end`.
+    ///
+    /// Bounds are hours (`9`) or `"HH:MM"` strings (`"17:30"`). Time
+    /// conditions read `request.timestamp`, or the current time when the
+    /// request has none.
+    pub fn hour_between(start: impl Into<ConditionValue>, end: impl Into<ConditionValue>) -> Self {
+        Self {
+            operator: ConditionOperator::HourBetween,
+            field: None,
+            value: Some(ConditionValue::Array(vec![start.into(), end.into()])),
+            conditions: Vec::new(),
+            timezone: None,
         }
     }
 
+    /// Create a day-of-week condition, which holds on the listed days
+    /// (`"mon"`, `"Tuesday"`, or `"weekdays"` and `"weekend"`).
+    pub fn day_of_week_in(days: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            operator: ConditionOperator::DayOfWeekIn,
+            field: None,
+            value: Some(string_list(days)),
+            conditions: Vec::new(),
+            timezone: None,
+        }
+    }
+
+    /// Create a date range condition, which holds from `start` to `end`
+    /// (`"YYYY-MM-DD"`), both days included.
+    pub fn date_between(start: impl Into<String>, end: impl Into<String>) -> Self {
+        Self {
+            operator: ConditionOperator::DateBetween,
+            field: None,
+            value: Some(string_list([start.into(), end.into()])),
+            conditions: Vec::new(),
+            timezone: None,
+        }
+    }
+
+    /// Set the time zone a time condition is evaluated in.
+    pub fn with_timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.timezone = Some(timezone.into());
+        self
+    }
+
     /// Create an AND condition combining multiple conditions.
     pub fn and(conditions: Vec<Condition>) -> Self {
         Self {
@@ -106,6 +268,7 @@ impl Condition {
             field: None,
             value: None,
             conditions,
+            timezone: None,
         }
     }
 
@@ -116,6 +279,7 @@ impl Condition {
             field: None,
             value: None,
             conditions,
+            timezone: None,
         }
     }
 
@@ -126,6 +290,7 @@ impl Condition {
             field: None,
             value: None,
             conditions: vec![condition],
+            timezone: None,
         }
     }
 
@@ -151,10 +316,28 @@ impl Condition {
                 }
                 self.conditions[0].validate()?;
             }
-            ConditionOperator::Exists => {
+            ConditionOperator::Exists | ConditionOperator::NotExists => {
                 if self.field.is_none() {
+                    return Err(crate::Error::validation(format!(
+                        "{:?} operator requires a field",
+                        self.operator
+                    )));
+                }
+            }
+            ConditionOperator::HourBetween
+            | ConditionOperator::DayOfWeekIn
+            | ConditionOperator::DateBetween => {
+                if self.value.is_none() {
+                    return Err(crate::Error::validation(format!(
+                        "{:?} operator requires a value",
+                        self.operator
+                    )));
+                }
+            }
+            ConditionOperator::Expression => {
+                if !matches!(self.value, Some(ConditionValue::String(_))) {
                     return Err(crate::Error::validation(
-                        "EXISTS operator requires a field",
+                        "Expression operator requires a CEL expression string value",
                     ));
                 }
             }
@@ -177,21 +360,36 @@ impl Condition {
     }
 }
 
+fn string_list(values: impl IntoIterator<Item = impl Into<String>>) -> ConditionValue {
+    ConditionValue::Array(
+        values
+            .into_iter()
+            .map(|value| ConditionValue::String(value.into()))
+            .collect(),
+    )
+}
+
 /// Operators for condition evaluation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ConditionOperator {
     /// Equality check
+    #[serde(alias = "eq")]
     Equals,
     /// Inequality check
+    #[serde(alias = "ne")]
     NotEquals,
     /// Greater than comparison
+    #[serde(alias = "gt")]
     GreaterThan,
     /// Greater than or equal comparison
+    #[serde(alias = "gte")]
     GreaterThanOrEquals,
     /// Less than comparison
+    #[serde(alias = "lt")]
     LessThan,
     /// Less than or equal comparison
+    #[serde(alias = "lte")]
     LessThanOrEquals,
     /// Value is in a list
     In,
@@ -205,10 +403,32 @@ pub enum ConditionOperator {
     EndsWith,
     /// Regex pattern match
     Matches,
+    /// String equals one of the templates, whose `{placeholders}` match any
+    /// text
+    MatchesTemplate,
+    /// A tool in the field (a tool list, tool call list or name) is listed
+    ToolIn,
+    /// A tool in the field is not listed
+    ToolNotIn,
+    /// An argument of a tool call matches a regex; the field is
+    /// `<tool>.<argument path>`, with `*` matching any tool
+    ArgumentMatches,
+    /// IP address is in one of the CIDR ranges
+    InCidr,
+    /// IP address is in none of the CIDR ranges
+    NotInCidr,
+    /// Local time of day is in a range of hours
+    HourBetween,
+    /// Local day of the week is listed
+    DayOfWeekIn,
+    /// Local date is in a range of dates
+    DateBetween,
     /// Field exists
     Exists,
     /// Field does not exist
     NotExists,
+    /// CEL expression evaluates to true
+    Expression,
     /// Logical AND
     And,
     /// Logical OR
@@ -217,6 +437,56 @@ pub enum ConditionOperator {
     Not,
 }
 
+impl ConditionOperator {
+    /// Get the string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConditionOperator::Equals => "equals",
+            ConditionOperator::NotEquals => "not_equals",
+            ConditionOperator::GreaterThan => "greater_than",
+            ConditionOperator::GreaterThanOrEquals => "greater_than_or_equals",
+            ConditionOperator::LessThan => "less_than",
+            ConditionOperator::LessThanOrEquals => "less_than_or_equals",
+            ConditionOperator::In => "in",
+            ConditionOperator::NotIn => "not_in",
+            ConditionOperator::Contains => "contains",
+            ConditionOperator::StartsWith => "starts_with",
+            ConditionOperator::EndsWith => "ends_with",
+            ConditionOperator::Matches => "matches",
+            ConditionOperator::MatchesTemplate => "matches_template",
+            ConditionOperator::ToolIn => "tool_in",
+            ConditionOperator::ToolNotIn => "tool_not_in",
+            ConditionOperator::ArgumentMatches => "argument_matches",
+            ConditionOperator::InCidr => "in_cidr",
+            ConditionOperator::NotInCidr => "not_in_cidr",
+            ConditionOperator::HourBetween => "hour_between",
+            ConditionOperator::DayOfWeekIn => "day_of_week_in",
+            ConditionOperator::DateBetween => "date_between",
+            ConditionOperator::Exists => "exists",
+            ConditionOperator::NotExists => "not_exists",
+            ConditionOperator::Expression => "expression",
+            ConditionOperator::And => "and",
+            ConditionOperator::Or => "or",
+            ConditionOperator::Not => "not",
+        }
+    }
+}
+
+impl std::fmt::Display for ConditionOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for ConditionOperator {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .map_err(|_| crate::Error::parse(format!("Unknown condition operator: {}", s)))
+    }
+}
+
 /// A value that can be used in conditions.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(untagged)]
@@ -304,10 +574,33 @@ mod tests {
             field: None,
             value: None,
             conditions: Vec::new(),
+            timezone: None,
+        };
+        assert!(invalid.validate().is_err());
+
+        assert!(Condition::expression("llm.model == 'gpt-4'").validate().is_ok());
+        let invalid = Condition {
+            operator: ConditionOperator::Expression,
+            field: None,
+            value: Some(ConditionValue::Integer(1)),
+            conditions: Vec::new(),
+            timezone: None,
         };
         assert!(invalid.validate().is_err());
     }
 
+    #[test]
+    fn test_operator_from_str() {
+        assert_eq!("equals".parse::<ConditionOperator>().unwrap(), ConditionOperator::Equals);
+        assert_eq!(
+            "GREATER_THAN".parse::<ConditionOperator>().unwrap(),
+            ConditionOperator::GreaterThan
+        );
+        assert_eq!("gte".parse::<ConditionOperator>().unwrap(), ConditionOperator::GreaterThanOrEquals);
+        assert_eq!(ConditionOperator::NotIn.as_str(), "not_in");
+        assert!("bogus".parse::<ConditionOperator>().is_err());
+    }
+
     #[test]
     fn test_condition_serialization() {
         let cond = Condition::equals("model", "gpt-4");
@@ -15,12 +15,18 @@ pub enum DecisionType {
     Warn,
     /// Allow with modifications
     Modify,
+    /// No policy applied to the request
+    #[serde(rename = "not_applicable")]
+    NotApplicable,
 }
 
 impl DecisionType {
     /// Check if this decision allows the request.
+    ///
+    /// Nothing restricts a request no policy applied to, so not-applicable
+    /// counts as allowed.
     pub fn is_allowed(&self) -> bool {
-        matches!(self, DecisionType::Allow | DecisionType::Warn | DecisionType::Modify)
+        !self.is_denied()
     }
 
     /// Check if this decision denies the request.
@@ -35,6 +41,7 @@ impl DecisionType {
             DecisionType::Deny => "deny",
             DecisionType::Warn => "warn",
             DecisionType::Modify => "modify",
+            DecisionType::NotApplicable => "not_applicable",
         }
     }
 }
@@ -60,6 +67,7 @@ impl std::str::FromStr for DecisionType {
             "deny" => Ok(DecisionType::Deny),
             "warn" => Ok(DecisionType::Warn),
             "modify" => Ok(DecisionType::Modify),
+            "not_applicable" | "not-applicable" => Ok(DecisionType::NotApplicable),
             _ => Err(crate::Error::parse(format!("Unknown decision type: {}", s))),
         }
     }
@@ -74,6 +82,7 @@ mod tests {
         assert!(DecisionType::Allow.is_allowed());
         assert!(DecisionType::Warn.is_allowed());
         assert!(DecisionType::Modify.is_allowed());
+        assert!(DecisionType::NotApplicable.is_allowed());
         assert!(!DecisionType::Deny.is_allowed());
     }
 
@@ -98,5 +107,9 @@ mod tests {
 
         let parsed: DecisionType = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed, DecisionType::Allow);
+
+        let json = serde_json::to_string(&DecisionType::NotApplicable).unwrap();
+        assert_eq!(json, "\"not_applicable\"");
+        assert_eq!("not_applicable".parse::<DecisionType>().unwrap(), DecisionType::NotApplicable);
     }
 }
@@ -1,6 +1,6 @@
 //! Policy document parsing and management.
 
-use super::Policy;
+use super::{Policy, RuleSet};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -51,15 +51,32 @@ impl PolicyDocument {
     }
 
     /// Parse a policy document from YAML.
+    ///
+    /// Single-policy [`RuleSet`] files are accepted as well.
     pub fn from_yaml(yaml: &str) -> crate::Result<Self> {
+        let value: serde_yaml::Value = serde_yaml::from_str(yaml)?;
+        if RuleSet::is_rule_set(|key| value.get(key).is_some()) {
+            return Self::from_rule_set(RuleSet::from_yaml(yaml)?);
+        }
         serde_yaml::from_str(yaml).map_err(crate::Error::from)
     }
 
     /// Parse a policy document from JSON.
+    ///
+    /// Single-policy [`RuleSet`] files are accepted as well.
     pub fn from_json(json: &str) -> crate::Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        if RuleSet::is_rule_set(|key| value.get(key).is_some()) {
+            return Self::from_rule_set(RuleSet::from_json(json)?);
+        }
         serde_json::from_str(json).map_err(crate::Error::from)
     }
 
+    /// Create a document holding the policy of a rule set.
+    pub fn from_rule_set(rule_set: RuleSet) -> crate::Result<Self> {
+        Ok(Self::with_policies(vec![rule_set.into_policy()?]))
+    }
+
     /// Load a policy document from a file.
     pub fn from_file(path: impl AsRef<Path>) -> crate::Result<Self> {
         let path = path.as_ref();
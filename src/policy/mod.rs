@@ -4,18 +4,28 @@
 //! conditions, and actions.
 
 mod action;
+mod combining;
 mod condition;
 mod decision;
 mod document;
 mod metadata;
+mod obligation;
+mod phase;
 mod rule;
+mod ruleset;
+mod target;
 
-pub use action::{Action, ActionType, Modification};
+pub use action::{Action, ActionType, Modification, ModificationType};
+pub use combining::CombiningAlgorithm;
 pub use condition::{Condition, ConditionOperator, ConditionValue};
 pub use decision::DecisionType;
 pub use document::PolicyDocument;
 pub use metadata::PolicyMetadata;
+pub use obligation::{LogLevel, Obligation};
+pub use phase::PolicyPhase;
 pub use rule::PolicyRule;
+pub use ruleset::RuleSet;
+pub use target::PolicyTarget;
 
 use serde::{Deserialize, Serialize};
 
@@ -34,6 +44,21 @@ pub struct Policy {
     /// Policy priority (higher = evaluated first)
     #[serde(default)]
     pub priority: i32,
+    /// Requests the policy applies to (every request when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<PolicyTarget>,
+    /// Whether the policy runs before the request, after the response or both
+    #[serde(default)]
+    pub phase: PolicyPhase,
+    /// How the results of matching rules are combined (deny-overrides when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub combining_algorithm: Option<CombiningAlgorithm>,
+    /// Decision returned when no rule matches (not applicable when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_decision: Option<DecisionType>,
+    /// Action taken when a rule cannot be evaluated (the error is returned when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<Action>,
 }
 
 fn default_enabled() -> bool {
@@ -49,6 +74,11 @@ impl Policy {
             rules: Vec::new(),
             enabled: true,
             priority: 0,
+            target: None,
+            phase: PolicyPhase::default(),
+            combining_algorithm: None,
+            default_decision: None,
+            fallback: None,
         }
     }
 
@@ -80,6 +110,10 @@ impl Policy {
             ));
         }
 
+        if let Some(target) = &self.target {
+            target.validate()?;
+        }
+
         for (i, rule) in self.rules.iter().enumerate() {
             rule.validate().map_err(|e| {
                 crate::Error::validation(format!("Rule {} validation failed: {}", i, e))
@@ -102,6 +136,11 @@ pub struct PolicyBuilder {
     rules: Vec<PolicyRule>,
     enabled: bool,
     priority: i32,
+    target: Option<PolicyTarget>,
+    phase: PolicyPhase,
+    combining_algorithm: Option<CombiningAlgorithm>,
+    default_decision: Option<DecisionType>,
+    fallback: Option<Action>,
 }
 
 impl PolicyBuilder {
@@ -168,6 +207,36 @@ impl PolicyBuilder {
         self
     }
 
+    /// Restrict the requests the policy applies to.
+    pub fn target(mut self, target: PolicyTarget) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    /// Set the phase the policy runs in.
+    pub fn phase(mut self, phase: PolicyPhase) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    /// Set how the results of matching rules are combined.
+    pub fn combining_algorithm(mut self, algorithm: CombiningAlgorithm) -> Self {
+        self.combining_algorithm = Some(algorithm);
+        self
+    }
+
+    /// Set the decision returned when no rule matches.
+    pub fn default_decision(mut self, decision: DecisionType) -> Self {
+        self.default_decision = Some(decision);
+        self
+    }
+
+    /// Set the action taken when a rule cannot be evaluated.
+    pub fn fallback(mut self, action: Action) -> Self {
+        self.fallback = Some(action);
+        self
+    }
+
     /// Build the policy.
     pub fn build(self) -> Policy {
         let name = self.name.unwrap_or_else(|| self.id.clone());
@@ -190,6 +259,11 @@ impl PolicyBuilder {
             rules: self.rules,
             enabled: self.enabled,
             priority: self.priority,
+            target: self.target,
+            phase: self.phase,
+            combining_algorithm: self.combining_algorithm,
+            default_decision: self.default_decision,
+            fallback: self.fallback,
         }
     }
 }
@@ -238,6 +312,11 @@ mod tests {
             rules: vec![],
             enabled: true,
             priority: 0,
+            target: None,
+            phase: PolicyPhase::Request,
+            combining_algorithm: None,
+            default_decision: None,
+            fallback: None,
         };
         assert!(invalid_policy.validate().is_err());
     }
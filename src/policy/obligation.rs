@@ -0,0 +1,202 @@
+//! Typed obligations carried by rule actions.
+
+use super::{Action, ActionType};
+use serde::{Deserialize, Serialize};
+
+/// Something that must happen when a rule matches, beyond its decision.
+///
+/// Obligations are read from log, rate-limit and notify actions. Their
+/// parameters come from the action metadata; a rate-limit action without a
+/// `limit` asks the caller to throttle the request instead:
+///
+/// ```yaml
+/// actions:
+///   - type: log
+///     metadata: { level: warning }
+///   - type: rate_limit
+///     metadata: { key: user.id, limit: 100, window_secs: 60, mandatory: true }
+///   - type: notify
+///     reason: "Large request"
+///     metadata: { channel: security }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Obligation {
+    /// Log the request at a severity
+    Log {
+        /// Severity of the log entry
+        level: LogLevel,
+        /// Message to log
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    /// Admit at most `limit` requests per window
+    RateLimit {
+        /// Context field whose value partitions the limit (one shared
+        /// limit when unset)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        key: Option<String>,
+        /// Requests admitted per window
+        limit: u64,
+        /// Window length in seconds
+        window_secs: u64,
+    },
+    /// Slow the request down before retrying it
+    Throttle {
+        /// Delay before the request proceeds
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        delay_ms: Option<u64>,
+        /// Retries the caller may attempt
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_retries: Option<u64>,
+    },
+    /// Send a notification to a channel
+    Notify {
+        /// Channel to notify
+        channel: String,
+        /// Notification message
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+}
+
+/// Severity of a log obligation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    /// Debugging detail
+    Debug,
+    /// Routine information
+    #[default]
+    Info,
+    /// Something worth attention
+    #[serde(alias = "warn")]
+    Warning,
+    /// A failure
+    Error,
+}
+
+/// Window used by rate-limit obligations that do not set one.
+const DEFAULT_WINDOW_SECS: u64 = 60;
+
+impl Obligation {
+    /// Read the obligation requested by an action, if it requests one.
+    pub fn from_action(action: &Action) -> crate::Result<Option<Self>> {
+        let invalid = |message: String| Err(crate::Error::validation_field(message, "actions"));
+        let metadata = &action.metadata;
+        let text = |key: &str| metadata.get(key).and_then(|v| v.as_str()).map(String::from);
+
+        let obligation = match action.action_type {
+            ActionType::Log => {
+                let level = match metadata.get("level") {
+                    Some(level) => match serde_json::from_value(level.clone()) {
+                        Ok(level) => level,
+                        Err(_) => return invalid(format!("Unknown log level: {}", level)),
+                    },
+                    None => LogLevel::default(),
+                };
+                Self::Log {
+                    level,
+                    message: action.reason.clone(),
+                }
+            }
+            ActionType::RateLimit => {
+                let limit = match metadata.get("limit") {
+                    Some(limit) => match limit.as_u64() {
+                        Some(limit) => limit,
+                        None => return invalid(format!("Invalid rate limit: {}", limit)),
+                    },
+                    None => {
+                        return Ok(Some(Self::Throttle {
+                            delay_ms: metadata.get("delay_ms").and_then(|v| v.as_u64()),
+                            max_retries: metadata.get("max_retries").and_then(|v| v.as_u64()),
+                        }))
+                    }
+                };
+                let window_secs = match metadata.get("window_secs").or(metadata.get("window")) {
+                    Some(window) => match window.as_u64() {
+                        Some(window) if window > 0 => window,
+                        _ => return invalid(format!("Invalid rate limit window: {}", window)),
+                    },
+                    None => DEFAULT_WINDOW_SECS,
+                };
+                let key = text("key");
+                if let Some(key) = &key {
+                    crate::api::FieldPath::parse(key)?;
+                }
+                Self::RateLimit {
+                    key,
+                    limit,
+                    window_secs,
+                }
+            }
+            ActionType::Notify => {
+                let Some(channel) = text("channel") else {
+                    return invalid("Notify action needs a 'channel'".to_string());
+                };
+                Self::Notify {
+                    channel,
+                    message: action.reason.clone(),
+                }
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(obligation))
+    }
+
+    /// Whether the action marks its obligation as mandatory, so that the
+    /// request is denied when it cannot be fulfilled.
+    pub fn is_mandatory(action: &Action) -> bool {
+        action
+            .metadata
+            .get("mandatory")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_action() {
+        let log = Action::log(LogLevel::Warning).with_metadata("level", serde_json::json!("warn"));
+        assert_eq!(
+            Obligation::from_action(&log).unwrap(),
+            Some(Obligation::Log {
+                level: LogLevel::Warning,
+                message: None,
+            })
+        );
+
+        let rate_limit = Action::rate_limit(10, 30)
+            .with_metadata("key", serde_json::json!("user.id"))
+            .mandatory();
+        assert_eq!(
+            Obligation::from_action(&rate_limit).unwrap(),
+            Some(Obligation::RateLimit {
+                key: Some("user.id".to_string()),
+                limit: 10,
+                window_secs: 30,
+            })
+        );
+        assert!(Obligation::is_mandatory(&rate_limit));
+
+        let mut throttle = rate_limit.with_metadata("delay_ms", serde_json::json!(100));
+        throttle.metadata.remove("limit");
+        assert_eq!(
+            Obligation::from_action(&throttle).unwrap(),
+            Some(Obligation::Throttle {
+                delay_ms: Some(100),
+                max_retries: None,
+            })
+        );
+
+        let mut notify = Action::notify("security");
+        notify.metadata.remove("channel");
+        assert!(Obligation::from_action(&notify).is_err());
+        assert_eq!(Obligation::from_action(&Action::allow()).unwrap(), None);
+    }
+}
@@ -0,0 +1,53 @@
+//! Evaluation phases of policies.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// When a policy runs relative to the model call.
+///
+/// Request-phase policies see the request before it is sent; response-phase
+/// policies also see the model's output in the `response` context section.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyPhase {
+    /// Before the request is sent to the model
+    #[default]
+    #[serde(alias = "pre_request", alias = "pre")]
+    Request,
+    /// After the model has responded
+    #[serde(alias = "post_response", alias = "post")]
+    Response,
+    /// In both phases
+    Both,
+}
+
+impl PolicyPhase {
+    /// Get the string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PolicyPhase::Request => "request",
+            PolicyPhase::Response => "response",
+            PolicyPhase::Both => "both",
+        }
+    }
+
+    /// Check whether a policy declared for this phase runs in `phase`.
+    pub fn includes(self, phase: PolicyPhase) -> bool {
+        self == phase || self == PolicyPhase::Both || phase == PolicyPhase::Both
+    }
+}
+
+impl fmt::Display for PolicyPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for PolicyPhase {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .map_err(|_| crate::Error::parse(format!("Unknown policy phase: {}", s)))
+    }
+}
@@ -17,6 +17,9 @@ pub struct PolicyRule {
     pub condition: Condition,
     /// The action to take when the condition matches
     pub action: Action,
+    /// Further actions requested when the condition matches (audit, log, suggestions)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<Action>,
     /// Whether this rule is enabled
     #[serde(default = "default_enabled")]
     pub enabled: bool,
@@ -43,6 +46,7 @@ impl PolicyRule {
             description: None,
             condition,
             action,
+            actions: Vec::new(),
             enabled: true,
             priority: 0,
         }
@@ -59,6 +63,12 @@ impl PolicyRule {
         self
     }
 
+    /// Add an action requested alongside the rule's decision.
+    pub fn with_action(mut self, action: Action) -> Self {
+        self.actions.push(action);
+        self
+    }
+
     /// Set the priority.
     pub fn with_priority(mut self, priority: i32) -> Self {
         self.priority = priority;
@@ -82,6 +92,12 @@ impl PolicyRule {
         }
 
         self.condition.validate()?;
+        for modification in &self.action.modifications {
+            modification.validate()?;
+        }
+        for action in std::iter::once(&self.action).chain(&self.actions) {
+            super::Obligation::from_action(action)?;
+        }
 
         Ok(())
     }
@@ -163,6 +179,7 @@ impl PolicyRuleBuilder {
             description: self.description,
             condition,
             action,
+            actions: Vec::new(),
             enabled: self.enabled,
             priority: self.priority,
         })
@@ -220,6 +237,7 @@ mod tests {
             description: None,
             condition: Condition::equals("field", "value"),
             action: Action::allow(),
+            actions: Vec::new(),
             enabled: true,
             priority: 0,
         };
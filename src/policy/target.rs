@@ -0,0 +1,109 @@
+//! Policy target selectors.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Selects the requests a policy applies to.
+///
+/// Each entry maps a context field path to the values it must take; a
+/// policy applies when every field holds one of its listed values. When a
+/// field holds a list, any element may match. Policies that do not apply
+/// are skipped without evaluating their rules.
+///
+/// ```yaml
+/// target:
+///   llm.provider: [openai, anthropic]
+///   project.environment: production
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PolicyTarget {
+    selectors: BTreeMap<String, TargetValues>,
+}
+
+/// Accepted values of one target field, written as a single value or a list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum TargetValues {
+    One(String),
+    Any(Vec<String>),
+}
+
+impl PolicyTarget {
+    /// Create an empty target, which applies to every request.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require a field to hold one of the given values.
+    pub fn with(
+        mut self,
+        field: impl Into<String>,
+        values: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        let values = values.into_iter().map(Into::into).collect();
+        self.selectors
+            .insert(field.into(), TargetValues::Any(values));
+        self
+    }
+
+    /// Iterate over the selected fields and their accepted values.
+    pub fn selectors(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.selectors.iter().map(|(field, values)| {
+            let values = match values {
+                TargetValues::One(value) => std::slice::from_ref(value),
+                TargetValues::Any(values) => values.as_slice(),
+            };
+            (field.as_str(), values)
+        })
+    }
+
+    /// Check whether the target selects nothing.
+    pub fn is_empty(&self) -> bool {
+        self.selectors.is_empty()
+    }
+
+    /// Check that every selector names a field and at least one value.
+    pub fn validate(&self) -> crate::Result<()> {
+        for (field, values) in self.selectors() {
+            crate::api::FieldPath::parse(field)?;
+            if values.is_empty() {
+                return Err(crate::Error::validation_field(
+                    format!("Target field '{}' lists no values", field),
+                    "target",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_yaml() {
+        let target: PolicyTarget = serde_yaml::from_str(
+            "llm.provider: [openai, anthropic]\nproject.environment: production\n",
+        )
+        .unwrap();
+
+        let selectors: Vec<_> = target.selectors().collect();
+        assert_eq!(
+            selectors,
+            vec![
+                (
+                    "llm.provider",
+                    &["openai".to_string(), "anthropic".to_string()][..]
+                ),
+                ("project.environment", &["production".to_string()][..]),
+            ]
+        );
+        assert!(target.validate().is_ok());
+        assert!(PolicyTarget::new()
+            .with("llm.provider", Vec::<String>::new())
+            .validate()
+            .is_err());
+    }
+}
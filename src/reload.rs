@@ -0,0 +1,439 @@
+//! Hot reloading of policy files and directories.
+//!
+//! A [`PolicyReloader`] owns the set of policy sources for an engine. Each
+//! reload re-parses every document, validates the complete set and only then
+//! swaps it into the engine, so a single bad file leaves the running policies
+//! untouched. Policies loaded through the API are replaced by the next reload.
+//!
+//! Directories are scanned recursively. Hidden entries are skipped, which keeps
+//! the `..data` and timestamped directories of Kubernetes ConfigMap volumes from
+//! being loaded twice while the visible symlinks into them are still followed.
+
+use crate::api::PolicyEngine;
+use crate::policy::{Policy, PolicyDocument};
+use crate::{Error, Result};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+/// Time to wait for a burst of file events to settle before reloading.
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Outcome of a successful reload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReloadSummary {
+    /// Number of policy files read
+    pub files: usize,
+    /// Number of policies now loaded
+    pub policies: usize,
+    /// Whether the policy set was swapped (false when nothing changed)
+    pub changed: bool,
+}
+
+/// Loads policies from files and directories and keeps an engine up to date.
+pub struct PolicyReloader {
+    engine: Arc<PolicyEngine>,
+    dirs: Vec<PathBuf>,
+    files: Vec<PathBuf>,
+    fingerprint: Mutex<Option<blake3::Hash>>,
+    reload_lock: tokio::sync::Mutex<()>,
+}
+
+impl PolicyReloader {
+    /// Create a reloader for the given engine with no sources.
+    pub fn new(engine: Arc<PolicyEngine>) -> Self {
+        Self {
+            engine,
+            dirs: Vec::new(),
+            files: Vec::new(),
+            fingerprint: Mutex::new(None),
+            reload_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Add a directory scanned recursively for `.yaml`, `.yml` and `.json` files.
+    pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dirs.push(dir.into());
+        self
+    }
+
+    /// Add a single policy file.
+    pub fn with_file(mut self, file: impl Into<PathBuf>) -> Self {
+        self.files.push(file.into());
+        self
+    }
+
+    /// Re-read all sources and swap the policy set in.
+    pub async fn reload(&self) -> Result<ReloadSummary> {
+        self.reload_inner(true).await
+    }
+
+    /// Re-read all sources, swapping only if their contents changed.
+    pub async fn reload_if_changed(&self) -> Result<ReloadSummary> {
+        self.reload_inner(false).await
+    }
+
+    async fn reload_inner(&self, force: bool) -> Result<ReloadSummary> {
+        let _guard = self.reload_lock.lock().await;
+        let start = Instant::now();
+
+        let result = match self.load_sources() {
+            Ok((policies, files, fingerprint)) => {
+                let unchanged = *self.fingerprint.lock() == Some(fingerprint);
+                if unchanged && !force {
+                    debug!("Policy sources unchanged, skipping reload");
+                    return Ok(ReloadSummary {
+                        files,
+                        policies: self.engine.policy_count(),
+                        changed: false,
+                    });
+                }
+
+                self.engine.replace_policies(policies).await.map(|count| {
+                    *self.fingerprint.lock() = Some(fingerprint);
+                    ReloadSummary {
+                        files,
+                        policies: count,
+                        changed: true,
+                    }
+                })
+            }
+            Err(e) => Err(e),
+        };
+
+        let telemetry = self.engine.telemetry();
+        match &result {
+            Ok(summary) => {
+                info!(
+                    "Reloaded {} policies from {} files in {:?}",
+                    summary.policies,
+                    summary.files,
+                    start.elapsed()
+                );
+                if let Some(telemetry) = telemetry {
+                    telemetry.record_reload(true);
+                }
+            }
+            Err(e) => {
+                error!("Policy reload rejected, keeping current policies: {}", e);
+                if let Some(telemetry) = telemetry {
+                    telemetry.record_reload(false);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Parse every source into one validated policy set.
+    fn load_sources(&self) -> Result<(Vec<Policy>, usize, blake3::Hash)> {
+        let mut paths = self.files.clone();
+        for dir in &self.dirs {
+            collect_policy_files(dir, &mut HashSet::new(), &mut paths)?;
+        }
+
+        let mut hasher = blake3::Hasher::new();
+        let mut policies = Vec::new();
+        let mut origins: HashMap<String, PathBuf> = HashMap::new();
+        let mut errors = Vec::new();
+
+        for path in &paths {
+            let document = std::fs::read(path)
+                .map_err(Error::from)
+                .and_then(|content| {
+                    hasher.update(path.to_string_lossy().as_bytes());
+                    hasher.update(&content);
+                    parse_document(path, &content)
+                })
+                .and_then(|document| self.engine.validate_document(&document).map(|_| document));
+
+            match document {
+                Ok(document) => {
+                    for policy in document.policies {
+                        if let Some(previous) = origins.get(&policy.id) {
+                            errors.push(format!(
+                                "{}: duplicate policy ID '{}' (also in {})",
+                                path.display(),
+                                policy.id,
+                                previous.display()
+                            ));
+                            continue;
+                        }
+                        origins.insert(policy.id.clone(), path.clone());
+                        policies.push(policy);
+                    }
+                }
+                Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(Error::validation(format!(
+                "{} invalid policy file(s): {}",
+                errors.len(),
+                errors.join("; ")
+            )));
+        }
+
+        Ok((policies, paths.len(), hasher.finalize()))
+    }
+
+    /// Watch all sources and reload on change until `shutdown` resolves.
+    pub async fn watch<F>(self: Arc<Self>, shutdown: F) -> Result<()>
+    where
+        F: Future<Output = ()>,
+    {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut watcher = RecommendedWatcher::new(
+            move |event: notify::Result<notify::Event>| {
+                let _ = tx.send(event);
+            },
+            notify::Config::default(),
+        )
+        .map_err(watch_error)?;
+
+        for dir in &self.dirs {
+            watcher
+                .watch(dir, RecursiveMode::Recursive)
+                .map_err(watch_error)?;
+        }
+        for file in &self.files {
+            // Watch the parent so atomic renames over the file are seen
+            let parent = file
+                .parent()
+                .filter(|p| !p.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            watcher
+                .watch(parent, RecursiveMode::NonRecursive)
+                .map_err(watch_error)?;
+        }
+
+        info!(
+            "Watching {} policy directories and {} files for changes",
+            self.dirs.len(),
+            self.files.len()
+        );
+
+        tokio::pin!(shutdown);
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                event = rx.recv() => match event {
+                    Some(Ok(event)) if event.kind.is_access() => continue,
+                    Some(Ok(_)) => {
+                        // Let the rest of a burst (e.g. a ConfigMap swap) arrive
+                        tokio::time::sleep(DEBOUNCE).await;
+                        while rx.try_recv().is_ok() {}
+                        let _ = self.reload_if_changed().await;
+                    }
+                    Some(Err(e)) => warn!("Policy watcher error: {}", e),
+                    None => break,
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Recursively collect policy files, following symlinks and skipping hidden entries.
+fn collect_policy_files(
+    dir: &Path,
+    visited: &mut HashSet<PathBuf>,
+    out: &mut Vec<PathBuf>,
+) -> Result<()> {
+    if !visited.insert(dir.canonicalize()?) {
+        return Ok(());
+    }
+
+    let mut entries = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for path in entries {
+        match path.file_name().and_then(|n| n.to_str()) {
+            Some(name) if !name.starts_with('.') => {}
+            _ => continue,
+        }
+
+        // fs::metadata follows symlinks
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            collect_policy_files(&path, visited, out)?;
+        } else if metadata.is_file() && is_policy_file(&path) {
+            out.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn is_policy_file(path: &Path) -> bool {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    matches!(extension.to_lowercase().as_str(), "yaml" | "yml" | "json")
+}
+
+fn parse_document(path: &Path, content: &[u8]) -> Result<PolicyDocument> {
+    let content =
+        std::str::from_utf8(content).map_err(|e| Error::parse(format!("Invalid UTF-8: {}", e)))?;
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    if extension.eq_ignore_ascii_case("json") {
+        PolicyDocument::from_json(content)
+    } else {
+        PolicyDocument::from_yaml(content)
+    }
+}
+
+fn watch_error(e: notify::Error) -> Error {
+    Error::internal(format!("Failed to watch policy sources: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::{Action, Condition, PolicyRule};
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("policy-reload-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn policy_yaml(id: &str) -> String {
+        let policy = Policy::builder(id)
+            .name(id)
+            .rule(PolicyRule::new(
+                "rule-1",
+                "Deny guests",
+                Condition::equals("user.role", "guest"),
+                Action::deny("Guests are not allowed"),
+            ))
+            .build();
+        PolicyDocument::with_policies(vec![policy])
+            .to_yaml()
+            .unwrap()
+    }
+
+    async fn engine() -> Arc<PolicyEngine> {
+        Arc::new(
+            PolicyEngine::builder()
+                .with_telemetry_enabled(true)
+                .build()
+                .await
+                .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_reload_nested_directory() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(dir.join("team/a")).unwrap();
+        std::fs::create_dir_all(dir.join(".hidden")).unwrap();
+        std::fs::write(dir.join("root.yaml"), policy_yaml("root")).unwrap();
+        std::fs::write(dir.join("team/a/nested.yml"), policy_yaml("nested")).unwrap();
+        std::fs::write(dir.join(".hidden/skip.yaml"), policy_yaml("hidden")).unwrap();
+        std::fs::write(dir.join("README.md"), "not a policy").unwrap();
+
+        let engine = engine().await;
+        let reloader = PolicyReloader::new(engine.clone()).with_dir(&dir);
+        let summary = reloader.reload().await.unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(summary.files, 2);
+        assert_eq!(summary.policies, 2);
+        assert!(engine.get_policy("root").is_some());
+        assert!(engine.get_policy("nested").is_some());
+        assert!(engine.get_policy("hidden").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_file_keeps_current_set() {
+        let dir = temp_dir();
+        std::fs::write(dir.join("a.yaml"), policy_yaml("a")).unwrap();
+
+        let engine = engine().await;
+        let reloader = PolicyReloader::new(engine.clone()).with_dir(&dir);
+        reloader.reload().await.unwrap();
+
+        std::fs::write(dir.join("b.yaml"), policy_yaml("b")).unwrap();
+        std::fs::write(dir.join("c.yaml"), "policies: [{{").unwrap();
+        let err = reloader.reload().await.unwrap_err();
+        assert!(err.to_string().contains("c.yaml"));
+
+        std::fs::write(dir.join("c.yaml"), policy_yaml("a")).unwrap();
+        let err = reloader.reload().await.unwrap_err();
+        assert!(err.to_string().contains("duplicate policy ID 'a'"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(engine.list_policies(), vec!["a".to_string()]);
+        let metrics = engine.telemetry().unwrap().metrics();
+        assert_eq!(metrics.reloads_succeeded, 1);
+        assert_eq!(metrics.reloads_failed, 2);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_configmap_symlink_swap() {
+        use std::os::unix::fs::symlink;
+
+        // Mirror the layout kubelet uses for ConfigMap volumes
+        let dir = temp_dir();
+        std::fs::create_dir_all(dir.join("..v1")).unwrap();
+        std::fs::write(dir.join("..v1/policy.yaml"), policy_yaml("v1")).unwrap();
+        symlink("..v1", dir.join("..data")).unwrap();
+        symlink("..data/policy.yaml", dir.join("policy.yaml")).unwrap();
+
+        let engine = engine().await;
+        let reloader = PolicyReloader::new(engine.clone()).with_dir(&dir);
+        reloader.reload().await.unwrap();
+        assert_eq!(engine.list_policies(), vec!["v1".to_string()]);
+
+        std::fs::create_dir_all(dir.join("..v2")).unwrap();
+        std::fs::write(dir.join("..v2/policy.yaml"), policy_yaml("v2")).unwrap();
+        symlink("..v2", dir.join("..data_tmp")).unwrap();
+        std::fs::rename(dir.join("..data_tmp"), dir.join("..data")).unwrap();
+
+        let summary = reloader.reload_if_changed().await.unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(summary.changed);
+        assert_eq!(engine.list_policies(), vec!["v2".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_watch_reloads_on_change() {
+        let dir = temp_dir();
+        std::fs::write(dir.join("a.yaml"), policy_yaml("a")).unwrap();
+
+        let engine = engine().await;
+        let reloader = Arc::new(PolicyReloader::new(engine.clone()).with_dir(&dir));
+        reloader.reload().await.unwrap();
+
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let watch = tokio::spawn(reloader.clone().watch(async {
+            let _ = rx.await;
+        }));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        std::fs::write(dir.join("b.yaml"), policy_yaml("b")).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while engine.policy_count() < 2 && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let _ = tx.send(());
+        watch.await.unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(engine.policy_count(), 2);
+    }
+}
@@ -0,0 +1,451 @@
+//! gRPC `PolicyService` implementation.
+//!
+//! Implements every RPC of `proto/policy.proto` on top of a shared
+//! [`PolicyEngine`]. Evaluation requests honour `policy_ids`, `trace`,
+//! `dry_run`, `namespace` and `phase` through [`EvaluationOptions`].
+
+use super::proto::policy_service_server::{PolicyService, PolicyServiceServer};
+use super::proto::{
+    CreatePolicyRequest, CreatePolicyResponse, DeletePolicyRequest, DeletePolicyResponse,
+    EvaluatePolicyRequest, EvaluatePolicyResponse, GetPolicyRequest, GetPolicyResponse,
+    ListPoliciesRequest, ListPoliciesResponse, Policy, UpdatePolicyRequest, UpdatePolicyResponse,
+    STATUS_ACTIVE, STATUS_DISABLED,
+};
+use crate::api::{EvaluationContext, EvaluationOptions, PolicyEngine};
+use crate::policy::Policy as DomainPolicy;
+use crate::{Error, Result};
+
+use futures::{Stream, StreamExt};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{info, warn};
+
+/// gRPC server wrapping a shared policy engine.
+pub struct GrpcServer {
+    engine: Arc<PolicyEngine>,
+}
+
+impl GrpcServer {
+    /// Create a new gRPC server for the given engine.
+    ///
+    /// Host, port and request timeout are taken from the engine's configuration.
+    pub fn new(engine: Arc<PolicyEngine>) -> Self {
+        Self { engine }
+    }
+
+    /// Get the socket address the server binds to.
+    pub fn addr(&self) -> String {
+        let server = &self.engine.config().server;
+        format!("{}:{}", server.host, server.grpc_port)
+    }
+
+    /// Build the tonic service.
+    pub fn service(&self) -> PolicyServiceServer<PolicyGrpcService> {
+        PolicyServiceServer::new(PolicyGrpcService::new(self.engine.clone()))
+    }
+
+    /// Bind to the configured address and serve until `shutdown` resolves.
+    pub async fn serve<F>(self, shutdown: F) -> Result<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let addr = self
+            .addr()
+            .parse()
+            .map_err(|e| Error::config(format!("Invalid gRPC address {}: {}", self.addr(), e)))?;
+        info!("gRPC server listening on {}", addr);
+
+        Server::builder()
+            .timeout(self.engine.config().server.request_timeout())
+            .add_service(self.service())
+            .serve_with_shutdown(addr, shutdown)
+            .await
+            .map_err(|e| Error::internal(format!("gRPC server error: {}", e)))
+    }
+}
+
+/// Tonic implementation of `PolicyService`.
+pub struct PolicyGrpcService {
+    engine: Arc<PolicyEngine>,
+}
+
+impl PolicyGrpcService {
+    /// Create a new service for the given engine.
+    pub fn new(engine: Arc<PolicyEngine>) -> Self {
+        Self { engine }
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn get_existing(&self, id: &str) -> std::result::Result<DomainPolicy, Status> {
+        self.engine
+            .get_policy(id)
+            .ok_or_else(|| Status::not_found(format!("Policy not found: {}", id)))
+    }
+}
+
+type EvaluateStream =
+    Pin<Box<dyn Stream<Item = std::result::Result<EvaluatePolicyResponse, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl PolicyService for PolicyGrpcService {
+    async fn create_policy(
+        &self,
+        request: Request<CreatePolicyRequest>,
+    ) -> std::result::Result<Response<CreatePolicyResponse>, Status> {
+        let request = request.into_inner();
+        let mut policy = decode_policy(request.policy)?;
+        if !request.created_by.is_empty() {
+            policy.metadata.created_by = Some(request.created_by);
+        }
+
+        let id = policy.id.clone();
+        if !self.engine.load_new_policy(policy).await? {
+            return Err(Status::already_exists(format!(
+                "Policy already exists: {}",
+                id
+            )));
+        }
+
+        let policy = self.get_existing(&id)?;
+        Ok(Response::new(CreatePolicyResponse {
+            policy: Some(Policy::from(&policy)),
+        }))
+    }
+
+    async fn get_policy(
+        &self,
+        request: Request<GetPolicyRequest>,
+    ) -> std::result::Result<Response<GetPolicyResponse>, Status> {
+        let policy = self.get_existing(&request.into_inner().id)?;
+        Ok(Response::new(GetPolicyResponse {
+            policy: Some(Policy::from(&policy)),
+            cached: false,
+        }))
+    }
+
+    async fn update_policy(
+        &self,
+        request: Request<UpdatePolicyRequest>,
+    ) -> std::result::Result<Response<UpdatePolicyResponse>, Status> {
+        let request = request.into_inner();
+        let existing = self.get_existing(&request.id)?;
+        let mut policy = decode_policy(request.policy)?;
+
+        if !policy.id.is_empty() && policy.id != request.id {
+            return Err(Status::invalid_argument(format!(
+                "Policy ID {} does not match request ID {}",
+                policy.id, request.id
+            )));
+        }
+        policy.id = request.id;
+        policy.metadata.created_at = existing.metadata.created_at;
+        policy.metadata.created_by = existing.metadata.created_by;
+        policy.metadata.touch();
+
+        let id = self.engine.load_policy(policy).await?;
+        let policy = self.get_existing(&id)?;
+        Ok(Response::new(UpdatePolicyResponse {
+            policy: Some(Policy::from(&policy)),
+        }))
+    }
+
+    async fn delete_policy(
+        &self,
+        request: Request<DeletePolicyRequest>,
+    ) -> std::result::Result<Response<DeletePolicyResponse>, Status> {
+        let id = request.into_inner().id;
+        self.get_existing(&id)?;
+        self.engine.unload_policy(&id).await?;
+        Ok(Response::new(DeletePolicyResponse { success: true }))
+    }
+
+    async fn list_policies(
+        &self,
+        request: Request<ListPoliciesRequest>,
+    ) -> std::result::Result<Response<ListPoliciesResponse>, Status> {
+        let request = request.into_inner();
+
+        let mut policies: Vec<_> = self
+            .engine
+            .list_policies()
+            .iter()
+            .filter_map(|id| self.engine.get_policy(id))
+            .filter(|p| {
+                request.namespace.is_empty()
+                    || p.metadata.namespace.as_deref() == Some(request.namespace.as_str())
+            })
+            .filter(|p| match request.status.as_str() {
+                "" => true,
+                STATUS_ACTIVE => p.enabled,
+                STATUS_DISABLED => !p.enabled,
+                _ => false,
+            })
+            .collect();
+        policies.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let total = policies.len() as i32;
+        let offset = request.offset.max(0) as usize;
+        let limit = if request.limit > 0 {
+            request.limit as usize
+        } else {
+            usize::MAX
+        };
+
+        Ok(Response::new(ListPoliciesResponse {
+            policies: policies
+                .iter()
+                .skip(offset)
+                .take(limit)
+                .map(Policy::from)
+                .collect(),
+            total,
+        }))
+    }
+
+    async fn evaluate_policy(
+        &self,
+        request: Request<EvaluatePolicyRequest>,
+    ) -> std::result::Result<Response<EvaluatePolicyResponse>, Status> {
+        let response = evaluate(&self.engine, request.into_inner()).await?;
+        Ok(Response::new(response))
+    }
+
+    type EvaluatePolicyStreamStream = EvaluateStream;
+
+    async fn evaluate_policy_stream(
+        &self,
+        request: Request<Streaming<EvaluatePolicyRequest>>,
+    ) -> std::result::Result<Response<Self::EvaluatePolicyStreamStream>, Status> {
+        let engine = self.engine.clone();
+        let stream = request.into_inner().then(move |message| {
+            let engine = engine.clone();
+            async move {
+                match message {
+                    Ok(request) => evaluate(&engine, request).await,
+                    Err(status) => {
+                        warn!("gRPC evaluation stream error: {}", status);
+                        Err(status)
+                    }
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// Evaluate a single request, honouring its evaluation options.
+async fn evaluate(
+    engine: &PolicyEngine,
+    request: EvaluatePolicyRequest,
+) -> std::result::Result<EvaluatePolicyResponse, Status> {
+    let context = request
+        .context
+        .map(EvaluationContext::from)
+        .ok_or_else(|| Status::invalid_argument("Evaluation context is required"))?;
+
+    let mut options = EvaluationOptions::new()
+        .with_policy_ids(request.policy_ids)
+        .with_trace(request.trace)
+        .with_dry_run(request.dry_run);
+    if !request.namespace.is_empty() {
+        options = options.with_namespace(request.namespace);
+    }
+    if !request.phase.is_empty() {
+        options = options.with_phase(request.phase.parse()?);
+    }
+
+    let decision = engine.evaluate_with_options(&context, &options).await?;
+
+    let request_id = if request.request_id.is_empty() {
+        uuid::Uuid::new_v4().to_string()
+    } else {
+        request.request_id
+    };
+
+    Ok(EvaluatePolicyResponse {
+        request_id,
+        decision: Some((&decision).into()),
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        cached: decision.cached,
+        trace: decision.trace.as_ref().map(Into::into),
+    })
+}
+
+#[allow(clippy::result_large_err)]
+fn decode_policy(policy: Option<Policy>) -> std::result::Result<DomainPolicy, Status> {
+    let policy = policy.ok_or_else(|| Status::invalid_argument("Policy is required"))?;
+    Ok(DomainPolicy::try_from(policy)?)
+}
+
+impl From<Error> for Status {
+    fn from(error: Error) -> Self {
+        let message = error.to_string();
+        match error {
+            Error::Validation { .. }
+            | Error::Parse { .. }
+            | Error::Yaml(_)
+            | Error::Serialization(_) => Status::invalid_argument(message),
+            Error::Timeout { .. } => Status::deadline_exceeded(message),
+            Error::Integration { .. } => Status::unavailable(message),
+            Error::Evaluation { .. } | Error::Expression { .. } => {
+                Status::failed_precondition(message)
+            }
+            _ => Status::internal(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::{Action, Condition, PolicyRule};
+    use crate::server::proto;
+
+    fn deny_policy(id: &str, model: &str) -> DomainPolicy {
+        DomainPolicy::builder(id)
+            .name(id)
+            .namespace("models")
+            .rule(PolicyRule::new(
+                "deny-model",
+                "Deny model",
+                Condition::equals("llm.model", model),
+                Action::deny(format!("{} is not allowed", model)),
+            ))
+            .build()
+    }
+
+    async fn service() -> PolicyGrpcService {
+        let engine = PolicyEngine::builder()
+            .with_policy(deny_policy("deny-gpt4", "gpt-4"))
+            .with_policy(deny_policy("deny-claude", "claude-3"))
+            .build()
+            .await
+            .unwrap();
+        PolicyGrpcService::new(Arc::new(engine))
+    }
+
+    fn evaluate_request(model: &str) -> EvaluatePolicyRequest {
+        let context = EvaluationContext::builder().with_model(model).build();
+        EvaluatePolicyRequest {
+            request_id: "req-1".to_string(),
+            context: Some(proto::EvaluationContext::from(&context)),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_policy() {
+        let service = service().await;
+
+        let response = service
+            .evaluate_policy(Request::new(evaluate_request("gpt-4")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.request_id, "req-1");
+        let decision = response.decision.unwrap();
+        assert_eq!(decision.decision, "deny");
+        assert_eq!(decision.matched_policies, vec!["deny-gpt4"]);
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_honours_policy_ids_trace_and_dry_run() {
+        let service = service().await;
+
+        let mut request = evaluate_request("gpt-4");
+        request.policy_ids = vec!["deny-claude".to_string()];
+        request.trace = true;
+        request.dry_run = true;
+
+        let response = service
+            .evaluate_policy(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner();
+        let decision = response.decision.unwrap();
+        assert_eq!(decision.decision, "not_applicable");
+        assert!(decision.allowed);
+        assert!(!decision.metadata.contains_key("trace"));
+        let trace = response.trace.unwrap();
+        assert_eq!(trace.policies_evaluated, 1);
+        let condition = &trace.steps[0];
+        assert_eq!(condition.step_type, "condition_evaluated");
+        assert_eq!(condition.field, "llm.model");
+        assert_eq!(condition.actual, "\"gpt-4\"");
+        assert_eq!(condition.outcome, Some(false));
+        assert!(trace
+            .steps
+            .iter()
+            .any(|step| step.step_type == "policy_evaluated" && step.id == "deny-claude"));
+        assert_eq!(decision.metadata["dry_run"], "true");
+        assert_eq!(service.engine.cache_stats().unwrap().size, 0);
+
+        let mut request = evaluate_request("gpt-4");
+        request.policy_ids = vec!["missing".to_string()];
+        let status = service.evaluate_policy(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_policy_crud() {
+        let service = service().await;
+
+        let created = service
+            .create_policy(Request::new(CreatePolicyRequest {
+                policy: Some(Policy::from(&deny_policy("deny-llama", "llama"))),
+                created_by: "alice".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .policy
+            .unwrap();
+        assert_eq!(created.metadata.unwrap().created_by, "alice");
+
+        let duplicate = service
+            .create_policy(Request::new(CreatePolicyRequest {
+                policy: Some(Policy::from(&deny_policy("deny-llama", "llama"))),
+                created_by: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(duplicate.code(), tonic::Code::AlreadyExists);
+
+        let mut disabled = Policy::from(&deny_policy("deny-llama", "llama"));
+        disabled.status = STATUS_DISABLED.to_string();
+        service
+            .update_policy(Request::new(UpdatePolicyRequest {
+                id: "deny-llama".to_string(),
+                policy: Some(disabled),
+            }))
+            .await
+            .unwrap();
+
+        let listed = service
+            .list_policies(Request::new(ListPoliciesRequest {
+                status: STATUS_ACTIVE.to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(listed.total, 2);
+
+        service
+            .delete_policy(Request::new(DeletePolicyRequest {
+                id: "deny-llama".to_string(),
+            }))
+            .await
+            .unwrap();
+        let missing = service
+            .get_policy(Request::new(GetPolicyRequest {
+                id: "deny-llama".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(missing.code(), tonic::Code::NotFound);
+    }
+}
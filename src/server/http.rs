@@ -0,0 +1,467 @@
+//! HTTP REST API for the policy engine.
+//!
+//! Routes mirror the TypeScript service so clients can switch between the two:
+//!
+//! | Method   | Path                      | Description                        |
+//! |----------|---------------------------|------------------------------------|
+//! | `GET`    | `/health`                 | Liveness and loaded policy count   |
+//! | `POST`   | `/api/evaluate`           | Evaluate policies against a context|
+//! | `GET`    | `/api/policies`           | List loaded policy IDs             |
+//! | `POST`   | `/api/policies`           | Load a policy document (JSON/YAML) |
+//! | `POST`   | `/api/policies/validate`  | Validate a document without loading|
+//! | `GET`    | `/api/policies/:id`       | Get a policy                       |
+//! | `DELETE` | `/api/policies/:id`       | Unload a policy                    |
+//! | `GET`    | `/api/cache/stats`        | Decision cache statistics          |
+//! | `DELETE` | `/api/cache`              | Clear the decision cache           |
+
+use crate::api::{CacheStats, EvaluationContext, EvaluationOptions, PolicyDecision, PolicyEngine};
+use crate::policy::{Policy, PolicyDocument, PolicyPhase};
+use crate::{Error, Result};
+
+use axum::extract::{DefaultBodyLimit, Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tower_http::timeout::TimeoutLayer;
+use tower_http::trace::TraceLayer;
+use tracing::info;
+
+/// HTTP server wrapping a shared policy engine.
+pub struct HttpServer {
+    engine: Arc<PolicyEngine>,
+}
+
+impl HttpServer {
+    /// Create a new HTTP server for the given engine.
+    ///
+    /// Host, port, request timeout and body limit are taken from the engine's
+    /// configuration.
+    pub fn new(engine: Arc<PolicyEngine>) -> Self {
+        Self { engine }
+    }
+
+    /// Get the socket address the server binds to.
+    pub fn addr(&self) -> String {
+        let server = &self.engine.config().server;
+        format!("{}:{}", server.host, server.port)
+    }
+
+    /// Build the axum router with all routes and middleware.
+    pub fn router(&self) -> Router {
+        let config = self.engine.config();
+        let body_limit = config.performance.max_policy_size_mb * 1024 * 1024;
+
+        Router::new()
+            .route("/health", get(health))
+            .route("/api/evaluate", post(evaluate))
+            .route("/api/policies", get(list_policies).post(load_policies))
+            .route("/api/policies/validate", post(validate_policies))
+            .route("/api/policies/:id", get(get_policy).delete(unload_policy))
+            .route("/api/cache/stats", get(cache_stats))
+            .route("/api/cache", axum::routing::delete(clear_cache))
+            .layer(DefaultBodyLimit::max(body_limit))
+            .layer(TimeoutLayer::new(config.server.request_timeout()))
+            .layer(TraceLayer::new_for_http())
+            .with_state(self.engine.clone())
+    }
+
+    /// Bind to the configured address and serve until `shutdown` resolves.
+    pub async fn serve<F>(self, shutdown: F) -> Result<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let addr = self.addr();
+        let listener = TcpListener::bind(&addr).await?;
+        info!("HTTP server listening on {}", addr);
+        self.serve_with_listener(listener, shutdown).await
+    }
+
+    /// Serve on an already bound listener until `shutdown` resolves.
+    pub async fn serve_with_listener<F>(self, listener: TcpListener, shutdown: F) -> Result<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        axum::serve(listener, self.router())
+            .with_graceful_shutdown(shutdown)
+            .await?;
+        Ok(())
+    }
+}
+
+type AppState = Arc<PolicyEngine>;
+
+/// Request body for `POST /api/evaluate`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateRequest {
+    /// Context to evaluate policies against
+    pub context: EvaluationContext,
+    /// Only evaluate these policy IDs (all enabled policies when empty)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policies: Vec<String>,
+    /// Include an evaluation trace in the decision
+    #[serde(default)]
+    pub trace: bool,
+    /// Evaluate without caching the decision or recording metrics
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Only evaluate policies in this namespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Only evaluate policies that run in this phase
+    #[serde(default)]
+    pub phase: PolicyPhase,
+}
+
+impl EvaluateRequest {
+    /// Create a request evaluating all policies against `context`.
+    pub fn new(context: EvaluationContext) -> Self {
+        Self {
+            context,
+            policies: Vec::new(),
+            trace: false,
+            dry_run: false,
+            namespace: None,
+            phase: PolicyPhase::default(),
+        }
+    }
+}
+
+/// Health check response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthResponse {
+    /// Service status
+    pub status: String,
+    /// Crate version
+    pub version: String,
+    /// Number of loaded policies
+    pub policies: usize,
+}
+
+/// Response listing loaded policies.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyListResponse {
+    /// IDs of loaded policies
+    pub policies: Vec<String>,
+    /// Number of loaded policies
+    pub count: usize,
+}
+
+/// Response after loading a policy document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadPoliciesResponse {
+    /// IDs of the loaded policies
+    pub loaded: Vec<String>,
+}
+
+/// Response after validating a policy document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidateResponse {
+    /// Whether the document is valid
+    pub valid: bool,
+    /// Validation errors, if any
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// Response for cache statistics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheStatsResponse {
+    /// Whether the decision cache is enabled
+    pub enabled: bool,
+    /// Cache statistics (if caching is enabled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<CacheStats>,
+}
+
+/// Error returned by HTTP handlers.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            code: "POLICY_NOT_FOUND",
+            message: message.into(),
+        }
+    }
+}
+
+impl From<Error> for ApiError {
+    fn from(error: Error) -> Self {
+        let (status, code) = match &error {
+            Error::Validation { .. } => (StatusCode::BAD_REQUEST, "VALIDATION_ERROR"),
+            Error::Parse { .. } | Error::Yaml(_) | Error::Serialization(_) => {
+                (StatusCode::BAD_REQUEST, "PARSE_ERROR")
+            }
+            Error::Timeout { .. } => (StatusCode::GATEWAY_TIMEOUT, "TIMEOUT"),
+            Error::Integration { .. } => (StatusCode::BAD_GATEWAY, "INTEGRATION_ERROR"),
+            Error::Evaluation { .. } | Error::Expression { .. } => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "EVALUATION_ERROR")
+            }
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR"),
+        };
+        Self {
+            status,
+            code,
+            message: error.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({
+            "error": self.code,
+            "message": self.message,
+        });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+async fn health(State(engine): State<AppState>) -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "healthy".to_string(),
+        version: crate::VERSION.to_string(),
+        policies: engine.policy_count(),
+    })
+}
+
+async fn evaluate(
+    State(engine): State<AppState>,
+    Json(request): Json<EvaluateRequest>,
+) -> std::result::Result<Json<PolicyDecision>, ApiError> {
+    let options = EvaluationOptions {
+        policy_ids: request.policies,
+        trace: request.trace,
+        dry_run: request.dry_run,
+        namespace: request.namespace,
+        phase: request.phase,
+    };
+    let decision = engine.evaluate_with_options(&request.context, &options).await?;
+    Ok(Json(decision))
+}
+
+async fn list_policies(State(engine): State<AppState>) -> Json<PolicyListResponse> {
+    let mut policies = engine.list_policies();
+    policies.sort();
+    Json(PolicyListResponse {
+        count: policies.len(),
+        policies,
+    })
+}
+
+async fn load_policies(
+    State(engine): State<AppState>,
+    headers: HeaderMap,
+    body: String,
+) -> std::result::Result<(StatusCode, Json<LoadPoliciesResponse>), ApiError> {
+    let loaded = if is_yaml(&headers) {
+        engine.load_policy_yaml(&body).await?
+    } else {
+        engine.load_policy_json(&body).await?
+    };
+    info!("Loaded {} policies via HTTP API", loaded.len());
+    Ok((StatusCode::CREATED, Json(LoadPoliciesResponse { loaded })))
+}
+
+async fn validate_policies(
+    State(engine): State<AppState>,
+    headers: HeaderMap,
+    body: String,
+) -> Json<ValidateResponse> {
+    let parsed = if is_yaml(&headers) {
+        PolicyDocument::from_yaml(&body)
+    } else {
+        PolicyDocument::from_json(&body)
+    };
+    let result = parsed.and_then(|document| engine.validate_document(&document));
+
+    Json(match result {
+        Ok(()) => ValidateResponse {
+            valid: true,
+            errors: Vec::new(),
+        },
+        Err(e) => ValidateResponse {
+            valid: false,
+            errors: vec![e.to_string()],
+        },
+    })
+}
+
+async fn get_policy(
+    State(engine): State<AppState>,
+    Path(id): Path<String>,
+) -> std::result::Result<Json<Policy>, ApiError> {
+    engine
+        .get_policy(&id)
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("Policy not found: {}", id)))
+}
+
+async fn unload_policy(
+    State(engine): State<AppState>,
+    Path(id): Path<String>,
+) -> std::result::Result<StatusCode, ApiError> {
+    if engine.get_policy(&id).is_none() {
+        return Err(ApiError::not_found(format!("Policy not found: {}", id)));
+    }
+    engine.unload_policy(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn cache_stats(State(engine): State<AppState>) -> Json<CacheStatsResponse> {
+    let stats = engine.cache_stats();
+    Json(CacheStatsResponse {
+        enabled: stats.is_some(),
+        stats,
+    })
+}
+
+async fn clear_cache(State(engine): State<AppState>) -> StatusCode {
+    engine.clear_cache();
+    StatusCode::NO_CONTENT
+}
+
+/// Check whether the request body is declared as YAML.
+fn is_yaml(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.contains("yaml"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::{Action, Condition, PolicyRule};
+
+    fn sample_policy() -> Policy {
+        Policy::builder("deny-gpt4")
+            .name("Deny GPT-4")
+            .rule(PolicyRule::new(
+                "rule-1",
+                "Deny gpt-4",
+                Condition::equals("llm.model", "gpt-4"),
+                Action::deny("gpt-4 is not allowed"),
+            ))
+            .build()
+    }
+
+    async fn spawn_server() -> (String, Arc<PolicyEngine>) {
+        let engine = Arc::new(
+            PolicyEngine::builder()
+                .with_policy(sample_policy())
+                .build()
+                .await
+                .unwrap(),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let server = HttpServer::new(engine.clone());
+        tokio::spawn(server.serve_with_listener(listener, std::future::pending()));
+        (base, engine)
+    }
+
+    #[tokio::test]
+    async fn test_health_and_evaluate() {
+        let (base, _engine) = spawn_server().await;
+        let client = reqwest::Client::new();
+
+        let health: HealthResponse = client
+            .get(format!("{}/health", base))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(health.policies, 1);
+
+        let request = EvaluateRequest::new(EvaluationContext::builder().with_model("gpt-4").build());
+        let decision: PolicyDecision = client
+            .post(format!("{}/api/evaluate", base))
+            .json(&request)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(!decision.allowed);
+    }
+
+    #[tokio::test]
+    async fn test_policy_crud() {
+        let (base, engine) = spawn_server().await;
+        let client = reqwest::Client::new();
+
+        let yaml = PolicyDocument::with_policies(vec![Policy::new("second", "Second")])
+            .to_yaml()
+            .unwrap();
+        let response = client
+            .post(format!("{}/api/policies", base))
+            .header("content-type", "application/yaml")
+            .body(yaml)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 201);
+        assert_eq!(engine.policy_count(), 2);
+
+        let list: PolicyListResponse = client
+            .get(format!("{}/api/policies", base))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(list.policies, vec!["deny-gpt4", "second"]);
+
+        let response = client
+            .delete(format!("{}/api/policies/second", base))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 204);
+
+        let response = client
+            .get(format!("{}/api/policies/second", base))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_validate_rejects_invalid_document() {
+        let (base, _engine) = spawn_server().await;
+
+        let invalid = PolicyDocument::with_policies(vec![Policy::new("", "No ID")])
+            .to_json()
+            .unwrap();
+        let result: ValidateResponse = reqwest::Client::new()
+            .post(format!("{}/api/policies/validate", base))
+            .body(invalid)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(!result.valid);
+        assert_eq!(result.errors.len(), 1);
+    }
+}
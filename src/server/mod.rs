@@ -0,0 +1,14 @@
+//! Network servers exposing the policy engine.
+//!
+//! This module wraps a shared [`PolicyEngine`](crate::PolicyEngine) in the
+//! transports served by the `policy-engine` daemon:
+//!
+//! - **HTTP**: JSON REST API built on axum
+//! - **gRPC**: `PolicyService` from `proto/policy.proto` built on tonic
+
+pub mod grpc;
+pub mod http;
+pub mod proto;
+
+pub use grpc::{GrpcServer, PolicyGrpcService};
+pub use http::HttpServer;
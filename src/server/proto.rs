@@ -0,0 +1,779 @@
+//! Protobuf messages for `proto/policy.proto` and conversions to and from the
+//! crate's domain types.
+//!
+//! The wire format carries free-form values (condition values, metadata and
+//! modifications) as strings. They are encoded as JSON text and decoded by
+//! parsing JSON first and falling back to a plain string, so values written by
+//! clients that send bare strings (e.g. `gpt-4`) are still accepted.
+
+use crate::api::{
+    EvaluationContext as DomainContext, EvaluationTrace as DomainTrace,
+    LlmContext as DomainLlmContext, PolicyDecision as DomainDecision,
+    ProjectContext as DomainProjectContext, RequestContext as DomainRequestContext,
+    ResponseContext as DomainResponseContext, TeamContext as DomainTeamContext,
+    TokenUsage as DomainTokenUsage, TraceStep as DomainTraceStep,
+    ChatMessage as DomainChatMessage, MessageRole, ToolCall as DomainToolCall,
+    ToolDefinition as DomainToolDefinition, UserContext as DomainUserContext,
+};
+use crate::policy::{
+    Action as DomainAction, ActionType, Condition as DomainCondition, ConditionValue,
+    DecisionType, Modification as DomainModification, ModificationType, Policy as DomainPolicy,
+    PolicyMetadata as DomainMetadata, PolicyRule as DomainRule, PolicyTarget,
+};
+use crate::{Error, Result};
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[allow(missing_docs, clippy::all)]
+mod generated {
+    tonic::include_proto!("llmpolicy");
+}
+
+pub use generated::*;
+
+/// Policy status reported for enabled policies.
+pub const STATUS_ACTIVE: &str = "active";
+
+/// Policy status reported for disabled policies.
+pub const STATUS_DISABLED: &str = "disabled";
+
+impl From<&DomainPolicy> for Policy {
+    fn from(policy: &DomainPolicy) -> Self {
+        let metadata = &policy.metadata;
+        Self {
+            metadata: Some(PolicyMetadata {
+                id: policy.id.clone(),
+                name: metadata.name.clone(),
+                description: metadata.description.clone().unwrap_or_default(),
+                version: metadata.version.clone(),
+                namespace: metadata.namespace.clone().unwrap_or_default(),
+                tags: metadata.tags.clone(),
+                priority: policy.priority,
+                created_at: metadata.created_at.to_rfc3339(),
+                updated_at: metadata.updated_at.to_rfc3339(),
+                created_by: metadata.created_by.clone().unwrap_or_default(),
+                labels: metadata.labels.clone(),
+            }),
+            rules: policy.rules.iter().map(PolicyRule::from).collect(),
+            status: if policy.enabled {
+                STATUS_ACTIVE
+            } else {
+                STATUS_DISABLED
+            }
+            .to_string(),
+            phase: policy.phase.to_string(),
+            target: policy
+                .target
+                .iter()
+                .flat_map(PolicyTarget::selectors)
+                .map(|(field, values)| {
+                    let values = values.to_vec();
+                    (field.to_string(), TargetValues { values })
+                })
+                .collect(),
+            combining_algorithm: policy
+                .combining_algorithm
+                .map(|algorithm| algorithm.to_string())
+                .unwrap_or_default(),
+            default_decision: policy
+                .default_decision
+                .map(|decision| decision.to_string())
+                .unwrap_or_default(),
+            fallback: policy.fallback.as_ref().map(Action::from),
+        }
+    }
+}
+
+impl TryFrom<Policy> for DomainPolicy {
+    type Error = Error;
+
+    fn try_from(policy: Policy) -> Result<Self> {
+        let metadata = policy
+            .metadata
+            .ok_or_else(|| Error::validation_field("Policy metadata is required", "metadata"))?;
+
+        let mut domain_metadata = DomainMetadata::new(metadata.name);
+        domain_metadata.description = non_empty(metadata.description);
+        domain_metadata.namespace = non_empty(metadata.namespace);
+        domain_metadata.tags = metadata.tags;
+        domain_metadata.created_by = non_empty(metadata.created_by);
+        domain_metadata.labels = metadata.labels;
+        if !metadata.version.is_empty() {
+            domain_metadata.version = metadata.version;
+        }
+        if let Some(created_at) = parse_timestamp(&metadata.created_at, "metadata.created_at")? {
+            domain_metadata.created_at = created_at;
+        }
+        if let Some(updated_at) = parse_timestamp(&metadata.updated_at, "metadata.updated_at")? {
+            domain_metadata.updated_at = updated_at;
+        }
+
+        let rules = policy
+            .rules
+            .into_iter()
+            .map(DomainRule::try_from)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(DomainPolicy {
+            id: metadata.id,
+            metadata: domain_metadata,
+            rules,
+            enabled: policy.status.is_empty() || policy.status == STATUS_ACTIVE,
+            priority: metadata.priority,
+            target: (!policy.target.is_empty()).then(|| {
+                policy
+                    .target
+                    .into_iter()
+                    .fold(PolicyTarget::new(), |target, (field, values)| {
+                        target.with(field, values.values)
+                    })
+            }),
+            phase: parse_non_empty(&policy.phase)?.unwrap_or_default(),
+            combining_algorithm: parse_non_empty(&policy.combining_algorithm)?,
+            default_decision: parse_non_empty(&policy.default_decision)?,
+            fallback: policy.fallback.map(DomainAction::try_from).transpose()?,
+        })
+    }
+}
+
+impl From<&DomainRule> for PolicyRule {
+    fn from(rule: &DomainRule) -> Self {
+        Self {
+            id: rule.id.clone(),
+            name: rule.name.clone(),
+            description: rule.description.clone().unwrap_or_default(),
+            condition: Some(Condition::from(&rule.condition)),
+            action: Some(Action::from(&rule.action)),
+            enabled: Some(rule.enabled),
+            actions: rule.actions.iter().map(Action::from).collect(),
+            priority: rule.priority,
+        }
+    }
+}
+
+impl TryFrom<PolicyRule> for DomainRule {
+    type Error = Error;
+
+    fn try_from(rule: PolicyRule) -> Result<Self> {
+        let condition = rule
+            .condition
+            .ok_or_else(|| Error::validation_field("Rule condition is required", "condition"))?;
+        let action = rule.action.map(DomainAction::try_from).transpose()?;
+
+        Ok(DomainRule {
+            id: rule.id,
+            name: rule.name,
+            description: non_empty(rule.description),
+            condition: DomainCondition::try_from(condition)?,
+            action: action.unwrap_or_else(DomainAction::allow),
+            actions: rule
+                .actions
+                .into_iter()
+                .map(DomainAction::try_from)
+                .collect::<Result<_>>()?,
+            enabled: rule.enabled.unwrap_or(true),
+            priority: rule.priority,
+        })
+    }
+}
+
+impl From<&DomainCondition> for Condition {
+    fn from(condition: &DomainCondition) -> Self {
+        Self {
+            operator: condition.operator.as_str().to_string(),
+            field: condition.field.clone().unwrap_or_default(),
+            value: condition
+                .value
+                .as_ref()
+                .map(|v| serde_json::to_string(v).unwrap_or_default())
+                .unwrap_or_default(),
+            conditions: condition.conditions.iter().map(Condition::from).collect(),
+            timezone: condition.timezone.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl TryFrom<Condition> for DomainCondition {
+    type Error = Error;
+
+    fn try_from(condition: Condition) -> Result<Self> {
+        let value = if condition.value.is_empty() {
+            None
+        } else {
+            Some(
+                serde_json::from_str::<ConditionValue>(&condition.value)
+                    .unwrap_or(ConditionValue::String(condition.value)),
+            )
+        };
+
+        Ok(DomainCondition {
+            operator: condition.operator.parse()?,
+            field: non_empty(condition.field),
+            value,
+            conditions: condition
+                .conditions
+                .into_iter()
+                .map(DomainCondition::try_from)
+                .collect::<Result<Vec<_>>>()?,
+            timezone: non_empty(condition.timezone),
+        })
+    }
+}
+
+impl From<&DomainAction> for Action {
+    fn from(action: &DomainAction) -> Self {
+        // `set` modifications are also written by field for clients that read
+        // the map.
+        let modifications = action
+            .modifications
+            .iter()
+            .filter(|m| m.modification_type == ModificationType::Set)
+            .filter_map(|m| m.value.as_ref().map(|v| (m.field.clone(), encode_value(v))))
+            .collect();
+
+        Self {
+            decision: action.decision.as_str().to_string(),
+            reason: action.reason.clone().unwrap_or_default(),
+            metadata: encode_map(&action.metadata),
+            modifications,
+            action_type: action.action_type.to_string(),
+            modification_list: action.modifications.iter().map(Modification::from).collect(),
+        }
+    }
+}
+
+impl TryFrom<Action> for DomainAction {
+    type Error = Error;
+
+    fn try_from(action: Action) -> Result<Self> {
+        let decision: DecisionType = if action.decision.is_empty() {
+            DecisionType::Allow
+        } else {
+            action.decision.parse()?
+        };
+
+        let modifications = if action.modification_list.is_empty() {
+            let mut modifications: Vec<_> = action
+                .modifications
+                .into_iter()
+                .map(|(field, value)| DomainModification::set(field, decode_value(value)))
+                .collect();
+            modifications.sort_by(|a, b| a.field.cmp(&b.field));
+            modifications
+        } else {
+            action
+                .modification_list
+                .into_iter()
+                .map(DomainModification::try_from)
+                .collect::<Result<_>>()?
+        };
+
+        Ok(DomainAction {
+            action_type: parse_non_empty(&action.action_type)?
+                .unwrap_or_else(|| action_type_for(decision)),
+            decision,
+            reason: non_empty(action.reason),
+            modifications,
+            metadata: decode_map(action.metadata),
+        })
+    }
+}
+
+impl From<&DomainModification> for Modification {
+    fn from(modification: &DomainModification) -> Self {
+        Self {
+            r#type: modification.modification_type.to_string(),
+            field: modification.field.clone(),
+            value: modification
+                .value
+                .as_ref()
+                .map(encode_value)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl TryFrom<Modification> for DomainModification {
+    type Error = Error;
+
+    fn try_from(modification: Modification) -> Result<Self> {
+        Ok(DomainModification {
+            modification_type: modification.r#type.parse()?,
+            field: modification.field,
+            value: non_empty(modification.value).map(decode_value),
+        })
+    }
+}
+
+impl From<&DomainContext> for EvaluationContext {
+    fn from(context: &DomainContext) -> Self {
+        Self {
+            llm: context.llm.as_ref().map(|llm| LlmContext {
+                provider: llm.provider.clone().unwrap_or_default(),
+                model: llm.model.clone().unwrap_or_default(),
+                prompt: llm.prompt.clone().unwrap_or_default(),
+                max_tokens: llm.max_tokens.map(|v| v as i32).unwrap_or_default(),
+                temperature: llm.temperature.map(f64::from).unwrap_or_default(),
+                tools: llm
+                    .tools
+                    .iter()
+                    .map(|tool| ToolDefinition {
+                        name: tool.name.clone(),
+                        description: tool.description.clone().unwrap_or_default(),
+                        parameters: tool
+                            .parameters
+                            .as_ref()
+                            .map(encode_value)
+                            .unwrap_or_default(),
+                    })
+                    .collect(),
+                tool_calls: llm.tool_calls.iter().map(ToolCall::from).collect(),
+                messages: llm
+                    .messages
+                    .iter()
+                    .map(|message| ChatMessage {
+                        role: message.role.to_string(),
+                        content: message.content.clone(),
+                        name: message.name.clone().unwrap_or_default(),
+                        tool_call_id: message.tool_call_id.clone().unwrap_or_default(),
+                    })
+                    .collect(),
+                estimated_prompt_tokens: llm.estimated_prompt_tokens.unwrap_or_default(),
+                estimated_cost_usd: llm.estimated_cost_usd.unwrap_or_default(),
+            }),
+            user: context.user.as_ref().map(|user| UserContext {
+                id: user.id.clone(),
+                email: user.email.clone().unwrap_or_default(),
+                roles: user.roles.clone(),
+                permissions: user.permissions.clone(),
+            }),
+            team: context.team.as_ref().map(|team| TeamContext {
+                id: team.id.clone(),
+                name: team.name.clone().unwrap_or_default(),
+                tier: team.tier.clone().unwrap_or_default(),
+            }),
+            project: context.project.as_ref().map(|project| ProjectContext {
+                id: project.id.clone(),
+                name: project.name.clone().unwrap_or_default(),
+                environment: project.environment.clone().unwrap_or_default(),
+            }),
+            request: context.request.as_ref().map(|request| RequestContext {
+                id: request.id.clone(),
+                timestamp: request.timestamp.unwrap_or_default(),
+                ip_address: request.ip_address.clone().unwrap_or_default(),
+                user_agent: request.user_agent.clone().unwrap_or_default(),
+            }),
+            metadata: encode_map(&context.metadata),
+            attributes: encode_map(&context.attributes),
+            response: context.response.as_ref().map(|response| ResponseContext {
+                content: response.content.clone().unwrap_or_default(),
+                finish_reason: response.finish_reason.clone().unwrap_or_default(),
+                usage: response.usage.map(|usage| TokenUsage {
+                    prompt_tokens: usage.prompt_tokens,
+                    completion_tokens: usage.completion_tokens,
+                    total_tokens: usage.total_tokens,
+                }),
+                model: response.model.clone().unwrap_or_default(),
+                tool_calls: response.tool_calls.iter().map(ToolCall::from).collect(),
+            }),
+        }
+    }
+}
+
+impl From<EvaluationContext> for DomainContext {
+    fn from(context: EvaluationContext) -> Self {
+        // proto3 scalars cannot distinguish "unset" from zero, so zero values
+        // are treated as absent.
+        Self {
+            llm: context.llm.map(|llm| DomainLlmContext {
+                provider: non_empty(llm.provider),
+                model: non_empty(llm.model),
+                prompt: non_empty(llm.prompt),
+                max_tokens: u32::try_from(llm.max_tokens).ok().filter(|v| *v > 0),
+                temperature: Some(llm.temperature as f32).filter(|v| *v != 0.0),
+                tools: llm
+                    .tools
+                    .into_iter()
+                    .map(|tool| DomainToolDefinition {
+                        name: tool.name,
+                        description: non_empty(tool.description),
+                        parameters: non_empty(tool.parameters).map(decode_value),
+                    })
+                    .collect(),
+                tool_calls: llm.tool_calls.into_iter().map(DomainToolCall::from).collect(),
+                // Messages with unknown roles are treated as untrusted user input
+                messages: llm
+                    .messages
+                    .into_iter()
+                    .map(|message| DomainChatMessage {
+                        role: message.role.parse().unwrap_or(MessageRole::User),
+                        content: message.content,
+                        name: non_empty(message.name),
+                        tool_call_id: non_empty(message.tool_call_id),
+                    })
+                    .collect(),
+                estimated_prompt_tokens: Some(llm.estimated_prompt_tokens).filter(|v| *v > 0),
+                estimated_cost_usd: Some(llm.estimated_cost_usd).filter(|v| *v != 0.0),
+            }),
+            user: context.user.map(|user| DomainUserContext {
+                id: user.id,
+                email: non_empty(user.email),
+                roles: user.roles,
+                permissions: user.permissions,
+            }),
+            team: context.team.map(|team| DomainTeamContext {
+                id: team.id,
+                name: non_empty(team.name),
+                tier: non_empty(team.tier),
+            }),
+            project: context.project.map(|project| DomainProjectContext {
+                id: project.id,
+                name: non_empty(project.name),
+                environment: non_empty(project.environment),
+            }),
+            request: context.request.map(|request| DomainRequestContext {
+                id: request.id,
+                timestamp: Some(request.timestamp).filter(|v| *v != 0),
+                ip_address: non_empty(request.ip_address),
+                user_agent: non_empty(request.user_agent),
+            }),
+            metadata: decode_map(context.metadata),
+            attributes: decode_map(context.attributes),
+            response: context.response.map(|response| DomainResponseContext {
+                content: non_empty(response.content),
+                finish_reason: non_empty(response.finish_reason),
+                usage: response.usage.map(|usage| DomainTokenUsage {
+                    prompt_tokens: usage.prompt_tokens,
+                    completion_tokens: usage.completion_tokens,
+                    total_tokens: usage.total_tokens,
+                }),
+                model: non_empty(response.model),
+                tool_calls: response
+                    .tool_calls
+                    .into_iter()
+                    .map(DomainToolCall::from)
+                    .collect(),
+            }),
+        }
+    }
+}
+
+impl From<&DomainToolCall> for ToolCall {
+    fn from(call: &DomainToolCall) -> Self {
+        Self {
+            id: call.id.clone().unwrap_or_default(),
+            name: call.name.clone(),
+            arguments: encode_value(&call.arguments),
+        }
+    }
+}
+
+impl From<ToolCall> for DomainToolCall {
+    fn from(call: ToolCall) -> Self {
+        Self {
+            id: non_empty(call.id),
+            name: call.name,
+            arguments: non_empty(call.arguments)
+                .map(decode_value)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&DomainDecision> for PolicyDecision {
+    fn from(decision: &DomainDecision) -> Self {
+        Self {
+            decision: decision.decision.as_str().to_string(),
+            allowed: decision.allowed,
+            reason: decision.reason.clone().unwrap_or_default(),
+            matched_policies: decision.matched_policies.clone(),
+            matched_rules: decision.matched_rules.clone(),
+            evaluation_time_ms: decision.evaluation_time_ms.round() as i64,
+            modifications: encode_map(&decision.modifications),
+            metadata: encode_map(&decision.metadata),
+            obligations: decision
+                .obligations
+                .iter()
+                .filter_map(|obligation| serde_json::to_string(obligation).ok())
+                .collect(),
+        }
+    }
+}
+
+impl From<&DomainTrace> for EvaluationTrace {
+    fn from(trace: &DomainTrace) -> Self {
+        Self {
+            steps: trace.steps.iter().map(TraceStep::from).collect(),
+            policies_evaluated: trace.policies_evaluated as u64,
+            rules_evaluated: trace.rules_evaluated as u64,
+            policies_skipped: trace.policies_skipped as u64,
+            cached: trace.cached,
+        }
+    }
+}
+
+impl From<&DomainTraceStep> for TraceStep {
+    fn from(step: &DomainTraceStep) -> Self {
+        Self {
+            step_type: step.step_type.as_str().to_string(),
+            id: step.id.clone(),
+            result: step.result.clone(),
+            duration_us: step.duration_us,
+            operator: step.operator.clone().unwrap_or_default(),
+            field: step.field.clone().unwrap_or_default(),
+            actual: step.actual.as_ref().map(encode_value).unwrap_or_default(),
+            expected: step.expected.as_ref().map(encode_value).unwrap_or_default(),
+            outcome: step.outcome,
+            error: step.error.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl TryFrom<PolicyDecision> for DomainDecision {
+    type Error = Error;
+
+    fn try_from(decision: PolicyDecision) -> Result<Self> {
+        let decision_type: DecisionType = decision.decision.parse()?;
+        let mut result = match decision_type {
+            DecisionType::Allow => DomainDecision::allow(),
+            DecisionType::Deny => DomainDecision::deny(decision.reason.clone()),
+            DecisionType::Warn => DomainDecision::warn(decision.reason.clone()),
+            DecisionType::Modify => DomainDecision::modify(decode_map(decision.modifications)),
+            DecisionType::NotApplicable => DomainDecision::not_applicable(),
+        };
+        result.allowed = decision.allowed;
+        result.reason = non_empty(decision.reason);
+        result.matched_policies = decision.matched_policies;
+        result.matched_rules = decision.matched_rules;
+        result.evaluation_time_ms = decision.evaluation_time_ms as f64;
+        result.metadata = decode_map(decision.metadata);
+        result.obligations = decision
+            .obligations
+            .iter()
+            .map(|obligation| {
+                serde_json::from_str(obligation)
+                    .map_err(|e| Error::parse(format!("Invalid obligation: {}", e)))
+            })
+            .collect::<Result<_>>()?;
+        Ok(result)
+    }
+}
+
+/// Map a decision to the action type that produces it, for actions sent
+/// without an action type.
+fn action_type_for(decision: DecisionType) -> ActionType {
+    match decision {
+        DecisionType::Allow => ActionType::Allow,
+        DecisionType::Deny => ActionType::Deny,
+        DecisionType::Warn => ActionType::Warn,
+        DecisionType::Modify => ActionType::Modify,
+        DecisionType::NotApplicable => ActionType::Log,
+    }
+}
+
+fn non_empty(value: String) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+fn parse_non_empty<T: FromStr<Err = Error>>(value: &str) -> Result<Option<T>> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    value.parse().map(Some)
+}
+
+fn parse_timestamp(value: &str, field: &str) -> Result<Option<DateTime<Utc>>> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| Some(t.with_timezone(&Utc)))
+        .map_err(|e| Error::validation_field(format!("Invalid timestamp: {}", e), field))
+}
+
+fn encode_value(value: &serde_json::Value) -> String {
+    value.to_string()
+}
+
+fn decode_value(value: String) -> serde_json::Value {
+    serde_json::from_str(&value).unwrap_or(serde_json::Value::String(value))
+}
+
+fn encode_map(map: &HashMap<String, serde_json::Value>) -> HashMap<String, String> {
+    map.iter()
+        .map(|(k, v)| (k.clone(), encode_value(v)))
+        .collect()
+}
+
+fn decode_map(map: HashMap<String, String>) -> HashMap<String, serde_json::Value> {
+    map.into_iter()
+        .map(|(k, v)| (k, decode_value(v)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_policy() -> DomainPolicy {
+        DomainPolicy::builder("budget")
+            .name("Budget")
+            .namespace("finance")
+            .priority(7)
+            .rule(DomainRule::new(
+                "cap-tokens",
+                "Cap max tokens",
+                DomainCondition::and(vec![
+                    DomainCondition::greater_than("llm.maxTokens", 4000i64),
+                    DomainCondition::equals("team.tier", "123"),
+                ]),
+                DomainAction::modify(vec![DomainModification::set(
+                    "llm.maxTokens",
+                    serde_json::json!(4000),
+                )])
+                .with_metadata("source", serde_json::json!("finance")),
+            ))
+            .build()
+    }
+
+    #[test]
+    fn test_policy_round_trip() {
+        let policy = sample_policy();
+        let wire = Policy::from(&policy);
+        assert_eq!(wire.status, STATUS_ACTIVE);
+
+        let parsed = DomainPolicy::try_from(wire).unwrap();
+        assert_eq!(parsed.id, "budget");
+        assert_eq!(parsed.priority, 7);
+        assert_eq!(parsed.metadata.namespace, Some("finance".to_string()));
+        assert_eq!(parsed.metadata.created_at, policy.metadata.created_at);
+
+        let rule = &parsed.rules[0];
+        assert_eq!(rule.condition.conditions.len(), 2);
+        // String values that look like numbers keep their type.
+        assert_eq!(
+            rule.condition.conditions[1].value,
+            Some(ConditionValue::String("123".to_string()))
+        );
+        assert_eq!(rule.action.decision, DecisionType::Modify);
+        assert_eq!(rule.action.modifications[0].value, Some(serde_json::json!(4000)));
+        assert_eq!(rule.action.metadata["source"], serde_json::json!("finance"));
+    }
+
+    #[test]
+    fn test_policy_round_trip_is_lossless() {
+        use crate::policy::{CombiningAlgorithm, LogLevel, PolicyPhase};
+
+        let mut policy = DomainPolicy::builder("pii")
+            .name("PII")
+            .target(PolicyTarget::new().with("llm.provider", ["openai", "anthropic"]))
+            .phase(PolicyPhase::Response)
+            .combining_algorithm(CombiningAlgorithm::FirstApplicable)
+            .default_decision(DecisionType::Deny)
+            .fallback(DomainAction::warn("PII check failed"))
+            .rule(
+                DomainRule::new(
+                    "redact",
+                    "Redact emails",
+                    DomainCondition::exists("response.content"),
+                    DomainAction::modify(vec![
+                        DomainModification::mask_pattern("response.content", "[a-z]+@[a-z.]+"),
+                        DomainModification::remove_named("llm.tools", ["shell"]),
+                        DomainModification::truncate("llm.prompt", 100),
+                        DomainModification::append("llm.stop", serde_json::json!("###")),
+                        DomainModification::set("llm.temperature", serde_json::json!(0)),
+                    ]),
+                )
+                .with_action(DomainAction::log(LogLevel::Warning))
+                .with_action(DomainAction::rate_limit(10, 60).mandatory())
+                .with_action(DomainAction::notify("privacy")),
+            )
+            .build();
+        policy.rules[0].priority = 5;
+        policy.metadata.labels.insert("owner".to_string(), "privacy".to_string());
+
+        let parsed = DomainPolicy::try_from(Policy::from(&policy)).unwrap();
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::to_value(&policy).unwrap()
+        );
+        assert_eq!(parsed.rules[0].actions[1].action_type, ActionType::RateLimit);
+    }
+
+    #[test]
+    fn test_rule_enabled_unless_disabled() {
+        let rule = |enabled| PolicyRule {
+            id: "r1".to_string(),
+            condition: Some(Condition {
+                operator: "exists".to_string(),
+                field: "llm.model".to_string(),
+                ..Default::default()
+            }),
+            enabled,
+            ..Default::default()
+        };
+        assert!(DomainRule::try_from(rule(None)).unwrap().enabled);
+        assert!(DomainRule::try_from(rule(Some(true))).unwrap().enabled);
+        assert!(!DomainRule::try_from(rule(Some(false))).unwrap().enabled);
+    }
+
+    #[test]
+    fn test_condition_accepts_bare_strings() {
+        let wire = Condition {
+            operator: "equals".to_string(),
+            field: "llm.model".to_string(),
+            value: "gpt-4".to_string(),
+            ..Default::default()
+        };
+        let condition = DomainCondition::try_from(wire).unwrap();
+        assert_eq!(condition.value, Some(ConditionValue::String("gpt-4".to_string())));
+
+        let invalid = Condition {
+            operator: "approximately".to_string(),
+            ..Default::default()
+        };
+        assert!(DomainCondition::try_from(invalid).is_err());
+    }
+
+    #[test]
+    fn test_context_round_trip() {
+        let context = DomainContext::builder()
+            .with_provider("openai")
+            .with_model("gpt-4")
+            .with_max_tokens(1000)
+            .with_user("user-1", None, vec!["admin".to_string()])
+            .with_metadata("region", serde_json::json!("eu"))
+            .build();
+
+        let parsed = DomainContext::from(EvaluationContext::from(&context));
+        let llm = parsed.llm.unwrap();
+        assert_eq!(llm.model, Some("gpt-4".to_string()));
+        assert_eq!(llm.max_tokens, Some(1000));
+        assert_eq!(llm.temperature, None);
+        assert_eq!(parsed.user.unwrap().roles, vec!["admin"]);
+        assert_eq!(parsed.metadata["region"], serde_json::json!("eu"));
+    }
+
+    #[test]
+    fn test_decision_round_trip() {
+        let decision = DomainDecision::deny("Over budget")
+            .with_matched_policy("budget")
+            .with_matched_rule("cap-tokens")
+            .with_evaluation_time_ms(2.6);
+
+        let wire = PolicyDecision::from(&decision);
+        assert_eq!(wire.decision, "deny");
+        assert_eq!(wire.evaluation_time_ms, 3);
+
+        let parsed = DomainDecision::try_from(wire).unwrap();
+        assert!(!parsed.allowed);
+        assert_eq!(parsed.reason, Some("Over budget".to_string()));
+        assert_eq!(parsed.matched_rules, vec!["cap-tokens"]);
+    }
+}
@@ -20,6 +20,7 @@ pub struct Telemetry {
     evaluations_deny: AtomicU64,
     evaluations_warn: AtomicU64,
     evaluations_modify: AtomicU64,
+    evaluations_not_applicable: AtomicU64,
     /// Cache hit/miss counters
     cache_hits: AtomicU64,
     cache_misses: AtomicU64,
@@ -27,6 +28,9 @@ pub struct Telemetry {
     errors: AtomicU64,
     /// Total evaluation time in microseconds
     total_evaluation_time_us: AtomicU64,
+    /// Policy reload counters
+    reloads_succeeded: AtomicU64,
+    reloads_failed: AtomicU64,
 }
 
 impl Telemetry {
@@ -40,10 +44,13 @@ impl Telemetry {
             evaluations_deny: AtomicU64::new(0),
             evaluations_warn: AtomicU64::new(0),
             evaluations_modify: AtomicU64::new(0),
+            evaluations_not_applicable: AtomicU64::new(0),
             cache_hits: AtomicU64::new(0),
             cache_misses: AtomicU64::new(0),
             errors: AtomicU64::new(0),
             total_evaluation_time_us: AtomicU64::new(0),
+            reloads_succeeded: AtomicU64::new(0),
+            reloads_failed: AtomicU64::new(0),
         })
     }
 
@@ -55,6 +62,9 @@ impl Telemetry {
             DecisionType::Deny => self.evaluations_deny.fetch_add(1, Ordering::Relaxed),
             DecisionType::Warn => self.evaluations_warn.fetch_add(1, Ordering::Relaxed),
             DecisionType::Modify => self.evaluations_modify.fetch_add(1, Ordering::Relaxed),
+            DecisionType::NotApplicable => {
+                self.evaluations_not_applicable.fetch_add(1, Ordering::Relaxed)
+            }
         };
 
         // Increment cache counter
@@ -75,12 +85,24 @@ impl Telemetry {
         self.errors.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Record a policy reload attempt.
+    pub fn record_reload(&self, success: bool) {
+        let (counter, result) = if success {
+            (&self.reloads_succeeded, "success")
+        } else {
+            (&self.reloads_failed, "failure")
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        increment_counter("policy_reloads_total", &[("result", result)]);
+    }
+
     /// Get current metrics.
     pub fn metrics(&self) -> TelemetryMetrics {
         let total_evaluations = self.evaluations_allow.load(Ordering::Relaxed)
             + self.evaluations_deny.load(Ordering::Relaxed)
             + self.evaluations_warn.load(Ordering::Relaxed)
-            + self.evaluations_modify.load(Ordering::Relaxed);
+            + self.evaluations_modify.load(Ordering::Relaxed)
+            + self.evaluations_not_applicable.load(Ordering::Relaxed);
 
         let cache_hits = self.cache_hits.load(Ordering::Relaxed);
         let cache_misses = self.cache_misses.load(Ordering::Relaxed);
@@ -104,11 +126,14 @@ impl Telemetry {
             evaluations_deny: self.evaluations_deny.load(Ordering::Relaxed),
             evaluations_warn: self.evaluations_warn.load(Ordering::Relaxed),
             evaluations_modify: self.evaluations_modify.load(Ordering::Relaxed),
+            evaluations_not_applicable: self.evaluations_not_applicable.load(Ordering::Relaxed),
             cache_hits,
             cache_misses,
             cache_hit_rate,
             avg_evaluation_time_ms,
             errors: self.errors.load(Ordering::Relaxed),
+            reloads_succeeded: self.reloads_succeeded.load(Ordering::Relaxed),
+            reloads_failed: self.reloads_failed.load(Ordering::Relaxed),
         }
     }
 
@@ -136,6 +161,9 @@ pub struct TelemetryMetrics {
     pub evaluations_warn: u64,
     /// Number of modify decisions
     pub evaluations_modify: u64,
+    /// Number of evaluations no policy applied to
+    #[serde(default)]
+    pub evaluations_not_applicable: u64,
     /// Cache hits
     pub cache_hits: u64,
     /// Cache misses
@@ -146,6 +174,12 @@ pub struct TelemetryMetrics {
     pub avg_evaluation_time_ms: f64,
     /// Total errors
     pub errors: u64,
+    /// Successful policy reloads
+    #[serde(default)]
+    pub reloads_succeeded: u64,
+    /// Rejected policy reloads
+    #[serde(default)]
+    pub reloads_failed: u64,
 }
 
 /// A span for tracing operations.